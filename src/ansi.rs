@@ -1,61 +1,316 @@
 use crate::{
-    markdown::{
-        elements::{Line, Text},
-        text::WeightedLine,
-    },
+    markdown::elements::{Line, Text},
     style::{Color, TextStyle},
 };
 use ansi_parser::{AnsiParser, AnsiSequence, Output};
+use std::{borrow::Cow, iter::Peekable, mem, str::Chars};
+use unicode_width::UnicodeWidthChar;
 
-pub(crate) struct AnsiSplitter {
-    lines: Vec<WeightedLine>,
-    current_line: Line,
-    current_style: TextStyle,
+const TAB_WIDTH: usize = 8;
+
+// The cell that follows a double width character, which is covered by it.
+const WIDE_CHARACTER_TAIL: char = '\0';
+
+/// A terminal screen that interprets a program's output the way a vt100 style terminal would.
+///
+/// Unlike a real terminal, lines that scroll past the top of the screen are kept so the entire output can be
+/// displayed. Cursor positions are relative to the last `rows` lines, which are the ones the program sees as the screen.
+#[derive(Debug)]
+pub(crate) struct TerminalScreen {
+    lines: Vec<Vec<Cell>>,
+    columns: usize,
+    rows: usize,
+    cursor: Cursor,
+    saved_cursor: Cursor,
+    style: TextStyle,
+    pending: Vec<u8>,
 }
 
-impl AnsiSplitter {
-    pub(crate) fn new(current_style: TextStyle) -> Self {
-        Self { lines: Default::default(), current_line: Default::default(), current_style }
+impl TerminalScreen {
+    pub(crate) fn new(columns: u16, rows: u16) -> Self {
+        Self {
+            lines: vec![Vec::new()],
+            columns: usize::from(columns).max(1),
+            rows: usize::from(rows).max(1),
+            cursor: Default::default(),
+            saved_cursor: Default::default(),
+            style: Default::default(),
+            pending: Default::default(),
+        }
+    }
+
+    /// Process some output.
+    ///
+    /// Characters and escape sequences that are cut in half are held back until the rest of them is fed.
+    pub(crate) fn feed(&mut self, data: &[u8]) {
+        self.pending.extend_from_slice(data);
+        let pending = mem::take(&mut self.pending);
+        let (text, rest) = decode_complete(&pending);
+        let split = text.rfind('\x1b').filter(|index| is_incomplete_escape(&text[*index..])).unwrap_or(text.len());
+        let (text, tail) = text.split_at(split);
+        for output in text.ansi_parse() {
+            match output {
+                Output::TextBlock(text) => self.write_text(text),
+                Output::Escape(sequence) => self.handle_escape(&sequence),
+            }
+        }
+        self.pending = [tail.as_bytes(), rest].concat();
+    }
+
+    /// Get the lines in this screen, without any trailing empty ones.
+    pub(crate) fn lines(&self) -> Vec<Line> {
+        let mut lines: Vec<_> = self.lines.iter().map(|cells| Self::build_line(cells)).collect();
+        while lines.last().is_some_and(|line| line.0.is_empty()) {
+            lines.pop();
+        }
+        lines
+    }
+
+    fn build_line(cells: &[Cell]) -> Line {
+        let length = cells.iter().rposition(|cell| !cell.is_blank()).map(|index| index + 1).unwrap_or(0);
+        let mut line = Line::default();
+        for cell in &cells[..length] {
+            if cell.character == WIDE_CHARACTER_TAIL {
+                continue;
+            }
+            match line.0.last_mut() {
+                Some(text) if text.style == cell.style => text.content.push(cell.character),
+                _ => line.0.push(Text::new(cell.character, cell.style)),
+            }
+        }
+        line
+    }
+
+    fn write_text(&mut self, text: &str) {
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                // Anything `ansi_parser` can't parse shows up as text.
+                '\x1b' => self.handle_unparsed_escape(&mut chars),
+                '\n' | '\x0b' | '\x0c' => self.line_feed(),
+                '\r' => self.cursor.column = 0,
+                '\x08' => self.cursor.column = self.cursor.column.min(self.columns - 1).saturating_sub(1),
+                '\t' => self.cursor.column = ((self.cursor.column / TAB_WIDTH + 1) * TAB_WIDTH).min(self.columns - 1),
+                c if c.is_control() => (),
+                c => self.write_char(c),
+            }
+        }
+    }
+
+    fn write_char(&mut self, c: char) {
+        let width = c.width().unwrap_or(0);
+        if width == 0 {
+            return;
+        }
+        if self.cursor.column + width > self.columns {
+            self.cursor.column = 0;
+            self.line_feed();
+        }
+        let Cursor { row, column } = self.cursor;
+        let style = self.style;
+        let line = &mut self.lines[row];
+        if line.len() < column + width {
+            line.resize(column + width, Cell::default());
+        }
+        line[column] = Cell { character: c, style };
+        if width == 2 {
+            line[column + 1] = Cell { character: WIDE_CHARACTER_TAIL, style };
+        }
+        self.cursor.column += width;
+    }
+
+    fn line_feed(&mut self) {
+        self.cursor.row += 1;
+        if self.cursor.row == self.lines.len() {
+            self.lines.push(Vec::new());
+        }
+    }
+
+    fn top(&self) -> usize {
+        self.lines.len().saturating_sub(self.rows)
+    }
+
+    fn move_to_row(&mut self, row: usize) {
+        let top = self.top();
+        self.cursor.row = row.clamp(top, top + self.rows - 1);
+        if self.cursor.row >= self.lines.len() {
+            self.lines.resize(self.cursor.row + 1, Vec::new());
+        }
+    }
+
+    fn move_to_column(&mut self, column: usize) {
+        self.cursor.column = column.min(self.columns - 1);
+    }
+
+    fn handle_escape(&mut self, sequence: &AnsiSequence) {
+        match sequence {
+            AnsiSequence::CursorPos(row, column) => {
+                self.move_to_row(self.top() + (*row as usize).saturating_sub(1));
+                self.move_to_column((*column as usize).saturating_sub(1));
+            }
+            AnsiSequence::CursorUp(amount) => self.move_to_row(self.cursor.row.saturating_sub(*amount as usize)),
+            AnsiSequence::CursorDown(amount) => self.move_to_row(self.cursor.row + *amount as usize),
+            AnsiSequence::CursorForward(amount) => self.move_to_column(self.cursor.column + *amount as usize),
+            AnsiSequence::CursorBackward(amount) => {
+                self.move_to_column(self.cursor.column.saturating_sub(*amount as usize))
+            }
+            AnsiSequence::CursorSave => self.saved_cursor = self.cursor,
+            AnsiSequence::CursorRestore => {
+                self.move_to_row(self.saved_cursor.row);
+                self.move_to_column(self.saved_cursor.column);
+            }
+            AnsiSequence::EraseDisplay => self.erase_display(2),
+            AnsiSequence::EraseLine => self.erase_line(0),
+            AnsiSequence::SetGraphicsMode(code) => GraphicsCode(code).update(&mut self.style),
+            _ => (),
+        }
     }
 
-    pub(crate) fn split_lines(mut self, lines: &[String]) -> (Vec<WeightedLine>, TextStyle) {
-        for line in lines {
-            for p in line.ansi_parse() {
-                match p {
-                    Output::TextBlock(text) => {
-                        self.current_line.0.push(Text::new(text, self.current_style));
+    fn handle_unparsed_escape(&mut self, chars: &mut Peekable<Chars>) {
+        match chars.next() {
+            Some('[') => {
+                let mut parameters = String::new();
+                for c in chars.by_ref() {
+                    if ('\x40'..='\x7e').contains(&c) {
+                        self.handle_control_sequence(&parameters, c);
+                        break;
                     }
-                    Output::Escape(s) => self.handle_escape(&s),
+                    parameters.push(c);
                 }
             }
-            let current_line = std::mem::take(&mut self.current_line);
-            self.lines.push(current_line.into());
-        }
+            // Operating system commands, like setting the window title, end with a bell or a string terminator.
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == '\x07' {
+                        break;
+                    }
+                    if c == '\x1b' {
+                        chars.next_if_eq(&'\\');
+                        break;
+                    }
+                }
+            }
+            Some('(' | ')') => {
+                chars.next();
+            }
+            _ => (),
+        };
+    }
 
-        (self.lines, self.current_style)
+    fn handle_control_sequence(&mut self, parameters: &str, action: char) {
+        let value = parameters.parse::<usize>().ok();
+        match action {
+            'J' => self.erase_display(value.unwrap_or(0)),
+            'K' => self.erase_line(value.unwrap_or(0)),
+            'G' => self.move_to_column(value.unwrap_or(1).saturating_sub(1)),
+            'A' => self.move_to_row(self.cursor.row.saturating_sub(value.unwrap_or(1))),
+            'B' => self.move_to_row(self.cursor.row + value.unwrap_or(1)),
+            'C' => self.move_to_column(self.cursor.column + value.unwrap_or(1)),
+            'D' => self.move_to_column(self.cursor.column.saturating_sub(value.unwrap_or(1))),
+            _ => (),
+        }
     }
 
-    fn handle_escape(&mut self, s: &AnsiSequence) {
-        match s {
-            AnsiSequence::SetGraphicsMode(code) => {
-                let code = GraphicsCode(code);
-                code.update(&mut self.current_style);
+    fn erase_display(&mut self, mode: usize) {
+        let top = self.top();
+        let Cursor { row, column } = self.cursor;
+        match mode {
+            0 => {
+                self.lines[row].truncate(column);
+                self.lines[row + 1..].iter_mut().for_each(Vec::clear);
+            }
+            1 => {
+                self.lines[top..row].iter_mut().for_each(Vec::clear);
+                self.erase_line(1);
             }
-            AnsiSequence::EraseDisplay => {
-                self.lines.clear();
-                self.current_line.0.clear();
+            2 => self.lines[top..].iter_mut().for_each(Vec::clear),
+            // This one also gets rid of the lines that scrolled past the top of the screen.
+            3 => {
+                self.lines.drain(..top);
+                self.cursor.row -= top;
+                self.saved_cursor.row = self.saved_cursor.row.saturating_sub(top);
             }
             _ => (),
         }
     }
+
+    fn erase_line(&mut self, mode: usize) {
+        let column = self.cursor.column;
+        let line = &mut self.lines[self.cursor.row];
+        match mode {
+            0 => line.truncate(column),
+            1 => line.iter_mut().take(column + 1).for_each(|cell| *cell = Cell::default()),
+            2 => line.clear(),
+            _ => (),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct Cursor {
+    row: usize,
+    column: usize,
+}
+
+#[derive(Clone, Debug)]
+struct Cell {
+    character: char,
+    style: TextStyle,
+}
+
+impl Cell {
+    fn is_blank(&self) -> bool {
+        self.character == ' ' && self.style == TextStyle::default()
+    }
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Self { character: ' ', style: TextStyle::default() }
+    }
+}
+
+// Splits the input into the longest valid UTF-8 prefix and a trailing incomplete character, if any.
+fn decode_complete(data: &[u8]) -> (Cow<'_, str>, &[u8]) {
+    match std::str::from_utf8(data) {
+        Ok(text) => (text.into(), &[]),
+        Err(e) if e.error_len().is_none() => {
+            let (valid, rest) = data.split_at(e.valid_up_to());
+            (String::from_utf8_lossy(valid), rest)
+        }
+        Err(_) => (String::from_utf8_lossy(data), &[]),
+    }
+}
+
+fn is_incomplete_escape(sequence: &str) -> bool {
+    let mut chars = sequence.chars().skip(1);
+    match chars.next() {
+        None => true,
+        Some('[') => !chars.any(|c| ('\x40'..='\x7e').contains(&c)),
+        Some(']') => !chars.any(|c| c == '\x07'),
+        Some('(' | ')') => chars.next().is_none(),
+        _ => false,
+    }
 }
 
 struct GraphicsCode<'a>(&'a [u8]);
 
 impl GraphicsCode<'_> {
     fn update(&self, style: &mut TextStyle) {
-        // RGB mode
         let codes = self.0;
+        // 256 color mode
+        if codes.starts_with(&[38, 5]) || codes.starts_with(&[48, 5]) {
+            if codes.len() == 3 {
+                let color = indexed_color(codes[2]);
+                if codes[0] == 38 {
+                    *style = style.fg_color(color);
+                } else {
+                    *style = style.bg_color(color);
+                }
+            }
+            return;
+        }
+        // RGB mode
         if codes.starts_with(&[38, 2]) || codes.starts_with(&[48, 2]) {
             if codes.len() == 5 {
                 let color = Color::new(codes[2], codes[3], codes[4]);
@@ -97,3 +352,96 @@ impl GraphicsCode<'_> {
         }
     }
 }
+
+// Maps one of the colors in the 256 color palette to a color.
+fn indexed_color(index: u8) -> Color {
+    const NAMED: [Color; 16] = [
+        Color::Black,
+        Color::DarkRed,
+        Color::DarkGreen,
+        Color::DarkYellow,
+        Color::DarkBlue,
+        Color::DarkMagenta,
+        Color::DarkCyan,
+        Color::Grey,
+        Color::DarkGrey,
+        Color::Red,
+        Color::Green,
+        Color::Yellow,
+        Color::Blue,
+        Color::Magenta,
+        Color::Cyan,
+        Color::White,
+    ];
+    const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+    match index {
+        0..=15 => NAMED[usize::from(index)],
+        16..=231 => {
+            let index = usize::from(index - 16);
+            Color::new(LEVELS[index / 36], LEVELS[index / 6 % 6], LEVELS[index % 6])
+        }
+        _ => {
+            let level = 8 + (index - 232) * 10;
+            Color::new(level, level, level)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+
+    fn text_lines(screen: &TerminalScreen) -> Vec<String> {
+        screen.lines().iter().map(Line::text).collect()
+    }
+
+    #[rstest]
+    #[case::lines(b"hello\r\nworld\r\n", &["hello", "world"])]
+    #[case::carriage_return(b"10%\r100%\r\n", &["100%"])]
+    #[case::cursor_up(b"a\r\nb\r\n\x1b[2Ac\r\n", &["c", "b"])]
+    #[case::erase_line(b"hello you\r\x1b[5C\x1b[K\r\n", &["hello"])]
+    #[case::erase_entire_line(b"50%\r\x1b[2Kdone\r\n", &["done"])]
+    #[case::wrap(b"0123456789abc", &["0123456789", "abc"])]
+    #[case::tab(b"a\tb", &["a       b"])]
+    #[case::backspace(b"ab\x08c", &["ac"])]
+    #[case::clear(b"old\r\n\x1b[H\x1b[2Jnew", &["new"])]
+    #[case::cursor_position(b"\x1b[2;3Hx", &["", "  x"])]
+    #[case::window_title(b"\x1b]0;title\x07text", &["text"])]
+    #[case::wide_characters("你好你好你好".as_bytes(), &["你好你好你", "好"])]
+    fn text(#[case] input: &[u8], #[case] expected: &[&str]) {
+        let mut screen = TerminalScreen::new(10, 5);
+        screen.feed(input);
+        assert_eq!(text_lines(&screen), expected);
+    }
+
+    #[test]
+    fn cursor_position_relative_to_screen() {
+        let mut screen = TerminalScreen::new(10, 2);
+        screen.feed(b"1\r\n2\r\n3\r\n\x1b[1;1Hx");
+        assert_eq!(text_lines(&screen), &["1", "2", "x"]);
+    }
+
+    #[test]
+    fn styles() {
+        let mut screen = TerminalScreen::new(20, 5);
+        screen.feed(b"\x1b[31mred\x1b[0m \x1b[38;5;16mblack");
+        let expected = Line(vec![
+            Text::new("red", TextStyle::default().fg_color(Color::Red)),
+            Text::new(" ", TextStyle::default()),
+            Text::new("black", TextStyle::default().fg_color(Color::new(0, 0, 0))),
+        ]);
+        assert_eq!(screen.lines(), &[expected]);
+    }
+
+    #[test]
+    fn split_input() {
+        let mut screen = TerminalScreen::new(20, 5);
+        let input = "\x1b[31mñ\x1b[0m".as_bytes();
+        for byte in input {
+            screen.feed(&[*byte]);
+        }
+        let expected = Line(vec![Text::new("ñ", TextStyle::default().fg_color(Color::Red))]);
+        assert_eq!(screen.lines(), &[expected]);
+    }
+}
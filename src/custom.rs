@@ -1,5 +1,6 @@
 use crate::{
     GraphicsMode,
+    execute::ExecutionTimeout,
     input::user::KeyBinding,
     media::{emulator::TerminalEmulator, kitty::KittyMode},
     processing::code::SnippetLanguage,
@@ -76,6 +77,12 @@ pub struct DefaultsConfig {
     /// Validate that the presentation does not overflow the terminal screen.
     #[serde(default)]
     pub validate_overflows: ValidateOverflows,
+
+    /// Whether the mouse wheel can be used to scroll slides that don't fit on the screen.
+    ///
+    /// This captures the mouse so text can't be selected with it while presenting.
+    #[serde(default)]
+    pub mouse_scroll: bool,
 }
 
 impl Default for DefaultsConfig {
@@ -85,6 +92,7 @@ impl Default for DefaultsConfig {
             terminal_font_size: default_font_size(),
             image_protocol: Default::default(),
             validate_overflows: Default::default(),
+            mouse_scroll: Default::default(),
         }
     }
 }
@@ -123,6 +131,9 @@ pub struct OptionsConfig {
 
     /// Whether to be strict about parsing the presentation's front matter.
     pub strict_front_matter_parsing: Option<bool>,
+
+    /// Whether text surrounded by dollar signs is rendered as a math formula.
+    pub math: Option<bool>,
 }
 
 #[derive(Clone, Debug, Default, Deserialize, JsonSchema)]
@@ -229,6 +240,32 @@ pub struct LanguageSnippetExecutionConfig {
 
     /// The prefix to use to hide lines visually but still execute them.
     pub hidden_line_prefix: Option<String>,
+
+    /// The maximum amount of time snippets can run for before they're killed, e.g. `5s`.
+    #[serde(default)]
+    pub timeout: Option<ExecutionTimeout>,
+
+    /// How to run snippets in a long lived interpreter shared by every snippet in the same session.
+    #[serde(default)]
+    pub session: Option<SnippetSessionConfig>,
+}
+
+/// The configuration for running snippets in a long lived interpreter.
+#[derive(Clone, Debug, Deserialize, JsonSchema)]
+pub struct SnippetSessionConfig {
+    /// The command that starts the interpreter, which must read the code to run from its standard input.
+    pub command: Vec<String>,
+
+    /// The line of code sent to the interpreter to run a snippet.
+    ///
+    /// `$pwd` is replaced with the directory the snippet file is in.
+    pub run: String,
+
+    /// The line of code sent to the interpreter to print a line, which is used to know when a snippet finished running.
+    ///
+    /// `$marker` is replaced with the contents of the line. It can be followed by the snippet's status, e.g.
+    /// `echo '$marker' $?`, in which case anything other than zero marks the snippet as failed.
+    pub echo: String,
 }
 
 #[derive(Clone, Debug, Default, Deserialize, ValueEnum, JsonSchema)]
@@ -326,6 +363,10 @@ pub struct KeyBindingsConfig {
     #[serde(default = "default_execute_code_bindings")]
     pub(crate) execute_code: Vec<KeyBinding>,
 
+    /// The key binding to stop the execution of a piece of code.
+    #[serde(default = "default_cancel_execution_bindings")]
+    pub(crate) cancel_execution: Vec<KeyBinding>,
+
     /// The key binding to reload the presentation.
     #[serde(default = "default_reload_bindings")]
     pub(crate) reload: Vec<KeyBinding>,
@@ -342,6 +383,18 @@ pub struct KeyBindingsConfig {
     #[serde(default = "default_close_modal_bindings")]
     pub(crate) close_modal: Vec<KeyBinding>,
 
+    /// The key binding to search the presentation.
+    #[serde(default = "default_search_bindings")]
+    pub(crate) search: Vec<KeyBinding>,
+
+    /// The key binding to scroll up slides that don't fit on the screen.
+    #[serde(default = "default_scroll_up_bindings")]
+    pub(crate) scroll_up: Vec<KeyBinding>,
+
+    /// The key binding to scroll down slides that don't fit on the screen.
+    #[serde(default = "default_scroll_down_bindings")]
+    pub(crate) scroll_down: Vec<KeyBinding>,
+
     /// The key binding to close the application.
     #[serde(default = "default_exit_bindings")]
     pub(crate) exit: Vec<KeyBinding>,
@@ -362,10 +415,14 @@ impl Default for KeyBindingsConfig {
             last_slide: default_last_slide_bindings(),
             go_to_slide: default_go_to_slide_bindings(),
             execute_code: default_execute_code_bindings(),
+            cancel_execution: default_cancel_execution_bindings(),
             reload: default_reload_bindings(),
             toggle_slide_index: default_toggle_index_bindings(),
             toggle_bindings: default_toggle_bindings_modal_bindings(),
             close_modal: default_close_modal_bindings(),
+            search: default_search_bindings(),
+            scroll_up: default_scroll_up_bindings(),
+            scroll_down: default_scroll_down_bindings(),
             exit: default_exit_bindings(),
             suspend: default_suspend_bindings(),
         }
@@ -412,6 +469,10 @@ fn default_execute_code_bindings() -> Vec<KeyBinding> {
     make_keybindings(["<c-e>"])
 }
 
+fn default_cancel_execution_bindings() -> Vec<KeyBinding> {
+    make_keybindings(["<c-x>"])
+}
+
 fn default_reload_bindings() -> Vec<KeyBinding> {
     make_keybindings(["<c-r>"])
 }
@@ -428,6 +489,18 @@ fn default_close_modal_bindings() -> Vec<KeyBinding> {
     make_keybindings(["<esc>"])
 }
 
+fn default_search_bindings() -> Vec<KeyBinding> {
+    make_keybindings(["/"])
+}
+
+fn default_scroll_up_bindings() -> Vec<KeyBinding> {
+    make_keybindings(["K"])
+}
+
+fn default_scroll_down_bindings() -> Vec<KeyBinding> {
+    make_keybindings(["J"])
+}
+
 fn default_exit_bindings() -> Vec<KeyBinding> {
     make_keybindings(["<c-c>", "q"])
 }
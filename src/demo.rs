@@ -5,7 +5,7 @@ use crate::{
         source::Command,
         user::{CommandKeyBindings, UserInput},
     },
-    markdown::elements::MarkdownElement,
+    markdown::elements::{MarkdownElement, SourcePosition},
     presentation::Presentation,
     processing::builder::{BuildError, PresentationBuilder},
     render::{draw::TerminalDrawer, terminal::TerminalWrite},
@@ -62,7 +62,7 @@ impl<W: TerminalWrite> ThemesDemo<W> {
         }
         let mut current = 0;
         loop {
-            self.drawer.render_slide(&presentations[current])?;
+            self.drawer.render_slide(&presentations[current], 0)?;
 
             let command = self.next_command()?;
             match command {
@@ -94,7 +94,7 @@ impl<W: TerminalWrite> ThemesDemo<W> {
 
     fn build(
         &self,
-        base_elements: &[MarkdownElement],
+        base_elements: &[(MarkdownElement, SourcePosition)],
         theme_name: &str,
         theme: &PresentationTheme,
     ) -> Result<Presentation, BuildError> {
@@ -114,7 +114,8 @@ impl<W: TerminalWrite> ThemesDemo<W> {
             bindings_config,
             options,
         );
-        let mut elements = vec![MarkdownElement::SetexHeading { text: format!("theme: {theme_name}").into() }];
+        let heading = MarkdownElement::SetexHeading { text: format!("theme: {theme_name}").into() };
+        let mut elements = vec![(heading, SourcePosition::default())];
         elements.extend(base_elements.iter().cloned());
         builder.build(elements)
     }
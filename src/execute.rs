@@ -1,23 +1,37 @@
 //! Code execution.
 
 use crate::{
-    custom::LanguageSnippetExecutionConfig,
-    processing::code::{Snippet, SnippetLanguage},
+    custom::{LanguageSnippetExecutionConfig, SnippetSessionConfig},
+    processing::code::{Snippet, SnippetLanguage, SnippetStdin},
 };
 use once_cell::sync::Lazy;
+#[cfg(not(unix))]
 use os_pipe::PipeReader;
+use schemars::JsonSchema;
+use serde_with::DeserializeFromStr;
 use std::{
     collections::{BTreeMap, HashMap},
     fmt::{self, Debug},
     fs::File,
-    io::{self, BufRead, BufReader, Write},
+    io::{self, Read, Write},
     path::{Path, PathBuf},
-    process::{self, Child, Stdio},
-    sync::{Arc, Mutex},
+    process::{self, Child, ChildStdin, Stdio},
+    str::FromStr,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, RecvTimeoutError},
+    },
     thread,
+    time::{Duration, Instant},
 };
 use tempfile::TempDir;
 
+const PROCESS_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+// There's nobody around to stop snippets that run without being displayed, so they can't run forever.
+const CAPTURED_DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+
 static EXECUTORS: Lazy<BTreeMap<SnippetLanguage, LanguageSnippetExecutionConfig>> =
     Lazy::new(|| serde_yaml::from_slice(include_bytes!("../executors.yaml")).expect("executors.yaml is broken"));
 
@@ -25,6 +39,7 @@ static EXECUTORS: Lazy<BTreeMap<SnippetLanguage, LanguageSnippetExecutionConfig>
 pub struct SnippetExecutor {
     executors: BTreeMap<SnippetLanguage, LanguageSnippetExecutionConfig>,
     cwd: PathBuf,
+    sessions: Mutex<BTreeMap<(SnippetLanguage, String), Session>>,
 }
 
 impl SnippetExecutor {
@@ -46,27 +61,57 @@ impl SnippetExecutor {
                     return Err(InvalidSnippetConfig(language.clone(), "empty command given"));
                 }
             }
+            if config.session.as_ref().is_some_and(|session| session.command.is_empty()) {
+                return Err(InvalidSnippetConfig(language.clone(), "empty session command given"));
+            }
         }
-        Ok(Self { executors, cwd })
+        Ok(Self { executors, cwd, sessions: Default::default() })
     }
 
     pub(crate) fn is_execution_supported(&self, language: &SnippetLanguage) -> bool {
         self.executors.contains_key(language)
     }
 
-    /// Execute a piece of code asynchronously.
-    pub(crate) fn execute_async(&self, snippet: &Snippet) -> Result<ExecutionHandle, CodeExecuteError> {
+    pub(crate) fn is_session_supported(&self, language: &SnippetLanguage) -> bool {
+        self.executors.get(language).is_some_and(|config| config.session.is_some())
+    }
+
+    /// Execute a piece of code asynchronously, on a pseudo terminal of the given size.
+    pub(crate) fn execute_async(
+        &self,
+        snippet: &Snippet,
+        terminal_size: TerminalSize,
+    ) -> Result<ExecutionHandle, CodeExecuteError> {
+        self.execute(snippet, OutputTarget::Terminal(terminal_size))
+    }
+
+    fn execute(&self, snippet: &Snippet, target: OutputTarget) -> Result<ExecutionHandle, CodeExecuteError> {
         let config = self.language_config(snippet)?;
         let script_dir = Self::write_snippet(snippet, config)?;
         let state: Arc<Mutex<ExecutionState>> = Default::default();
-        let reader_handle = CommandsRunner::spawn(
-            state.clone(),
-            script_dir,
-            config.commands.clone(),
-            config.environment.clone(),
-            self.cwd.to_path_buf(),
-        );
-        let handle = ExecutionHandle { state, reader_handle };
+        let cancelled: Arc<AtomicBool> = Default::default();
+        let timeout = snippet.attributes.timeout.or(config.timeout).map(|timeout| timeout.0);
+        if let Some(name) = &snippet.attributes.session {
+            let job = SessionJob {
+                script_directory: script_dir,
+                state: state.clone(),
+                cancelled: cancelled.clone(),
+                timeout,
+                target,
+            };
+            self.run_in_session(snippet, name, config, job)?;
+            return Ok(ExecutionHandle { state, cancelled, reader_handle: None });
+        }
+        let commands = Self::prepare_commands(snippet, config, &script_dir);
+        let runner = CommandsRunner {
+            state: state.clone(),
+            script_directory: script_dir,
+            cancelled: cancelled.clone(),
+            target,
+            stdin: snippet.attributes.stdin.clone(),
+        };
+        let reader_handle = runner.spawn(commands, config.environment.clone(), self.cwd.to_path_buf(), timeout);
+        let handle = ExecutionHandle { state, cancelled, reader_handle: Some(reader_handle) };
         Ok(handle)
     }
 
@@ -74,11 +119,7 @@ impl SnippetExecutor {
     pub(crate) fn execute_sync(&self, snippet: &Snippet) -> Result<(), CodeExecuteError> {
         let config = self.language_config(snippet)?;
         let script_dir = Self::write_snippet(snippet, config)?;
-        let script_dir_path = script_dir.path().to_string_lossy();
-        for mut commands in config.commands.clone() {
-            for command in &mut commands {
-                *command = command.replace("$pwd", &script_dir_path);
-            }
+        for commands in Self::prepare_commands(snippet, config, &script_dir) {
             let (command, args) = commands.split_first().expect("no commands");
             let child = process::Command::new(command)
                 .args(args)
@@ -97,6 +138,44 @@ impl SnippetExecutor {
         Ok(())
     }
 
+    /// Executes a piece of code synchronously, capturing what it writes to stdout and stderr separately.
+    ///
+    /// Snippets that don't have a timeout, either on their own or in their language's executor, get a default one.
+    pub(crate) fn execute_captured(&self, snippet: &Snippet) -> Result<CapturedOutput, CodeExecuteError> {
+        let config = self.language_config(snippet)?;
+        let mut snippet = snippet.clone();
+        if snippet.attributes.timeout.is_none() && config.timeout.is_none() {
+            snippet.attributes.timeout = Some(ExecutionTimeout(CAPTURED_DEFAULT_TIMEOUT));
+        }
+        let handle = self.execute(&snippet, OutputTarget::Pipes)?;
+        let state = loop {
+            let state = handle.state.lock().unwrap();
+            if state.status.is_finished() {
+                break state.clone();
+            }
+            drop(state);
+            thread::sleep(PROCESS_POLL_INTERVAL);
+        };
+        let output = String::from_utf8_lossy(&state.output).replace("\r\n", "\n");
+        let errors = String::from_utf8_lossy(&state.errors).replace("\r\n", "\n");
+        Ok(CapturedOutput { output, errors, status: state.status })
+    }
+
+    fn run_in_session(
+        &self,
+        snippet: &Snippet,
+        name: &str,
+        config: &LanguageSnippetExecutionConfig,
+        job: SessionJob,
+    ) -> Result<(), CodeExecuteError> {
+        let session_config = config.session.as_ref().ok_or(CodeExecuteError::UnsupportedSession)?;
+        let mut sessions = self.sessions.lock().unwrap();
+        let session = sessions.entry((snippet.language.clone(), name.to_string())).or_insert_with(|| {
+            Session::spawn(SessionRunner::new(session_config.clone(), config.environment.clone(), self.cwd.clone()))
+        });
+        session.jobs.send(job).map_err(|_| CodeExecuteError::SessionStopped)
+    }
+
     pub(crate) fn hidden_line_prefix(&self, language: &SnippetLanguage) -> Option<&str> {
         self.executors.get(language).and_then(|lang| lang.hidden_line_prefix.as_deref())
     }
@@ -108,6 +187,25 @@ impl SnippetExecutor {
         self.executors.get(&snippet.language).ok_or(CodeExecuteError::UnsupportedExecution)
     }
 
+    // Replaces the placeholders in the commands used to run a snippet and appends its arguments to
+    // the last one.
+    fn prepare_commands(
+        snippet: &Snippet,
+        config: &LanguageSnippetExecutionConfig,
+        script_directory: &TempDir,
+    ) -> Vec<Vec<String>> {
+        let script_dir = script_directory.path().to_string_lossy();
+        let mut commands: Vec<Vec<String>> = config
+            .commands
+            .iter()
+            .map(|command| command.iter().map(|part| part.replace("$pwd", &script_dir)).collect())
+            .collect();
+        if let Some(last) = commands.last_mut() {
+            last.extend(snippet.attributes.args.iter().cloned());
+        }
+        commands
+    }
+
     fn write_snippet(snippet: &Snippet, config: &LanguageSnippetExecutionConfig) -> Result<TempDir, CodeExecuteError> {
         let hide_prefix = config.hidden_line_prefix.as_deref();
         let code = snippet.executable_contents(hide_prefix);
@@ -155,118 +253,542 @@ pub(crate) enum CodeExecuteError {
     #[error("error creating pipe: {0}")]
     Pipe(io::Error),
 
+    #[cfg(unix)]
+    #[error("error creating pseudo terminal: {0}")]
+    Pty(io::Error),
+
     #[error("error waiting for process to run: {0}")]
     Waiting(io::Error),
 
     #[error("error running process: {0}")]
     Running(String),
+
+    #[error("code language doesn't support sessions")]
+    UnsupportedSession,
+
+    #[error("session is no longer running")]
+    SessionStopped,
+
+    #[error("error opening stdin file '{0}': {1}")]
+    StdinFile(PathBuf, io::Error),
 }
 
 /// A handle for the execution of a piece of code.
 #[derive(Debug)]
 pub(crate) struct ExecutionHandle {
     pub(crate) state: Arc<Mutex<ExecutionState>>,
+    cancelled: Arc<AtomicBool>,
     #[allow(dead_code)]
-    reader_handle: thread::JoinHandle<()>,
+    reader_handle: Option<thread::JoinHandle<()>>,
+}
+
+impl ExecutionHandle {
+    /// Stop the execution, killing any process it's running.
+    pub(crate) fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+/// The size of the terminal a piece of code runs on.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct TerminalSize {
+    pub(crate) columns: u16,
+    pub(crate) rows: u16,
+}
+
+/// Where a process writes its output to.
+#[derive(Clone, Copy, Debug)]
+enum OutputTarget {
+    /// A pseudo terminal of the given size, which gets both stdout and stderr.
+    Terminal(TerminalSize),
+
+    /// Separate pipes for stdout and stderr.
+    Pipes,
+}
+
+// Where processes write their output to.
+#[cfg(unix)]
+type OutputReader = File;
+#[cfg(not(unix))]
+type OutputReader = PipeReader;
+
+/// The ends of a process' output we read from.
+struct OutputReaders {
+    output: OutputReader,
+    // Only set if stderr isn't written to the same place as stdout.
+    errors: Option<OutputReader>,
 }
 
 /// Consumes the output of a process and stores it in a shared state.
 struct CommandsRunner {
     state: Arc<Mutex<ExecutionState>>,
+    // Kept around so the snippet isn't deleted before the commands that use it are run.
+    #[allow(dead_code)]
     script_directory: TempDir,
+    cancelled: Arc<AtomicBool>,
+    target: OutputTarget,
+    stdin: Option<SnippetStdin>,
 }
 
 impl CommandsRunner {
     fn spawn(
-        state: Arc<Mutex<ExecutionState>>,
-        script_directory: TempDir,
+        self,
         commands: Vec<Vec<String>>,
         env: HashMap<String, String>,
         cwd: PathBuf,
+        timeout: Option<Duration>,
     ) -> thread::JoinHandle<()> {
-        let reader = Self { state, script_directory };
-        thread::spawn(|| reader.run(commands, env, cwd))
+        thread::spawn(move || self.run(commands, env, cwd, timeout))
     }
 
-    fn run(self, commands: Vec<Vec<String>>, env: HashMap<String, String>, cwd: PathBuf) {
-        let mut last_result = true;
-        for command in commands {
-            last_result = self.run_command(command, &env, &cwd);
-            if !last_result {
+    fn run(self, commands: Vec<Vec<String>>, env: HashMap<String, String>, cwd: PathBuf, timeout: Option<Duration>) {
+        // The timeout applies to all commands as a whole.
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        let mut status = ProcessStatus::Success;
+        let last_index = commands.len() - 1;
+        for (index, command) in commands.into_iter().enumerate() {
+            // Only the command that runs the snippet gets its input.
+            let stdin = self.stdin.as_ref().filter(|_| index == last_index);
+            status = self.run_command(command, stdin, &env, &cwd, deadline);
+            if !matches!(status, ProcessStatus::Success) {
                 break;
             }
         }
-        let status = match last_result {
-            true => ProcessStatus::Success,
-            false => ProcessStatus::Failure,
-        };
         self.state.lock().unwrap().status = status;
     }
 
-    fn run_command(&self, command: Vec<String>, env: &HashMap<String, String>, cwd: &Path) -> bool {
-        let (mut child, reader) = match self.launch_process(command, env, cwd) {
+    fn run_command(
+        &self,
+        command: Vec<String>,
+        stdin: Option<&SnippetStdin>,
+        env: &HashMap<String, String>,
+        cwd: &Path,
+        deadline: Option<Instant>,
+    ) -> ProcessStatus {
+        let (mut child, readers) = match self.launch_process(command, stdin, env, cwd) {
             Ok(inner) => inner,
             Err(e) => {
-                let mut state = self.state.lock().unwrap();
-                state.status = ProcessStatus::Failure;
-                state.output.push(e.to_string());
-                return false;
+                self.state.lock().unwrap().push_error(&e, self.target);
+                return ProcessStatus::Failure;
             }
         };
-        let _ = Self::process_output(self.state.clone(), reader);
-
-        match child.wait() {
-            Ok(code) => code.success(),
-            _ => false,
+        let state = self.state.clone();
+        let mut reader_handles = vec![thread::spawn(move || {
+            read_output(readers.output, |output| state.lock().unwrap().output.extend_from_slice(output))
+        })];
+        if let Some(reader) = readers.errors {
+            let state = self.state.clone();
+            reader_handles.push(thread::spawn(move || {
+                read_output(reader, |output| state.lock().unwrap().errors.extend_from_slice(output))
+            }));
         }
+        let mut status = wait_for_process(&mut child, deadline, &self.cancelled);
+        // Anything the process left running in the background keeps its output open, so it's
+        // still subject to the deadline and to being cancelled until that's closed.
+        while reader_handles.iter().any(|handle| !handle.is_finished()) {
+            if self.cancelled.load(Ordering::Relaxed) {
+                kill_process(&mut child);
+                status = ProcessStatus::Cancelled;
+                break;
+            }
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                kill_process(&mut child);
+                status = ProcessStatus::TimedOut;
+                break;
+            }
+            thread::sleep(PROCESS_POLL_INTERVAL);
+        }
+        status
     }
 
     fn launch_process(
         &self,
-        mut commands: Vec<String>,
+        commands: Vec<String>,
+        stdin: Option<&SnippetStdin>,
         env: &HashMap<String, String>,
         cwd: &Path,
-    ) -> Result<(Child, PipeReader), CodeExecuteError> {
-        let (reader, writer) = os_pipe::pipe().map_err(CodeExecuteError::Pipe)?;
-        let writer_clone = writer.try_clone().map_err(CodeExecuteError::Pipe)?;
-        let script_dir = self.script_directory.path().to_string_lossy();
-        for command in &mut commands {
-            *command = command.replace("$pwd", &script_dir);
-        }
+    ) -> Result<(Child, OutputReaders), CodeExecuteError> {
         let (command, args) = commands.split_first().expect("no commands");
-        let child = process::Command::new(command)
-            .args(args)
-            .envs(env)
-            .current_dir(cwd)
-            .stdin(Stdio::null())
-            .stdout(writer)
-            .stderr(writer_clone)
-            .spawn()
-            .map_err(|e| CodeExecuteError::SpawnProcess(command.clone(), e))?;
-        Ok((child, reader))
-    }
-
-    fn process_output(state: Arc<Mutex<ExecutionState>>, reader: os_pipe::PipeReader) -> io::Result<()> {
-        let reader = BufReader::new(reader);
-        for line in reader.lines() {
-            let mut line = line?;
-            if line.contains('\t') {
-                line = line.replace('\t', "    ");
+        let mut process = process::Command::new(command);
+        process.args(args).envs(env).current_dir(cwd);
+        let input = attach_input(&mut process, stdin, cwd)?;
+        let readers = attach_output(&mut process, self.target)?;
+        let mut child = process.spawn().map_err(|e| CodeExecuteError::SpawnProcess(command.clone(), e))?;
+        feed_input(&mut child, input);
+        Ok((child, readers))
+    }
+}
+
+/// A long lived interpreter that runs snippets one after the other.
+struct Session {
+    jobs: mpsc::Sender<SessionJob>,
+}
+
+impl Session {
+    fn spawn(runner: SessionRunner) -> Self {
+        let (jobs, receiver) = mpsc::channel();
+        thread::spawn(move || runner.run(receiver));
+        Self { jobs }
+    }
+}
+
+/// A snippet to be run in a session.
+struct SessionJob {
+    script_directory: TempDir,
+    state: Arc<Mutex<ExecutionState>>,
+    cancelled: Arc<AtomicBool>,
+    timeout: Option<Duration>,
+    target: OutputTarget,
+}
+
+/// Runs the snippets sent to a session, starting its interpreter whenever it's not running.
+struct SessionRunner {
+    config: SnippetSessionConfig,
+    environment: HashMap<String, String>,
+    cwd: PathBuf,
+    marker: String,
+    process: Option<SessionProcess>,
+}
+
+struct SessionProcess {
+    child: Child,
+    stdin: ChildStdin,
+    output: mpsc::Receiver<Vec<u8>>,
+    errors: Option<mpsc::Receiver<Vec<u8>>>,
+}
+
+impl SessionRunner {
+    fn new(config: SnippetSessionConfig, environment: HashMap<String, String>, cwd: PathBuf) -> Self {
+        // This is printed after every snippet so it can't be something a snippet would print.
+        let marker = format!("presenterm-session-{:016x}", rand::random::<u64>());
+        Self { config, environment, cwd, marker, process: None }
+    }
+
+    fn run(mut self, jobs: mpsc::Receiver<SessionJob>) {
+        for job in jobs {
+            let status = self.run_job(&job);
+            job.state.lock().unwrap().status = status;
+        }
+        self.stop();
+    }
+
+    fn run_job(&mut self, job: &SessionJob) -> ProcessStatus {
+        if job.cancelled.load(Ordering::Relaxed) {
+            return ProcessStatus::Cancelled;
+        }
+        if self.process.is_none() {
+            match self.launch(job.target) {
+                Ok(process) => self.process = Some(process),
+                Err(e) => {
+                    job.state.lock().unwrap().push_error(&e, job.target);
+                    return ProcessStatus::Failure;
+                }
+            };
+        }
+        let process = self.process.as_mut().expect("no process");
+        let script_dir = job.script_directory.path().to_string_lossy();
+        let run = self.config.run.replace("$pwd", &script_dir);
+        let echo = self.config.echo.replace("$marker", &self.marker);
+        if process.stdin.write_all(format!("{run}\n{echo}\n").as_bytes()).and_then(|_| process.stdin.flush()).is_err() {
+            self.stop();
+            return ProcessStatus::Failure;
+        }
+
+        let deadline = job.timeout.map(|timeout| Instant::now() + timeout);
+        let marker = self.marker.as_bytes();
+        let mut pending = Vec::new();
+        let mut finished = false;
+        let status = loop {
+            if job.cancelled.load(Ordering::Relaxed) {
+                break ProcessStatus::Cancelled;
+            }
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                break ProcessStatus::TimedOut;
+            }
+            if let Some(errors) = &process.errors {
+                job.state.lock().unwrap().errors.extend(errors.try_iter().flatten());
+            }
+            match process.output.recv_timeout(PROCESS_POLL_INTERVAL) {
+                Ok(output) => {
+                    pending.extend(output);
+                    if let Some(index) = pending.windows(marker.len()).position(|window| window == marker) {
+                        // The marker only counts once its whole line was written.
+                        if let Some(length) = pending[index..].iter().position(|byte| *byte == b'\n') {
+                            let status = Self::parse_status(&pending[index + marker.len()..index + length]);
+                            pending.truncate(index);
+                            finished = true;
+                            break status;
+                        }
+                        continue;
+                    }
+                    // Hold on to anything that could be the beginning of the marker.
+                    let complete = pending.len().saturating_sub(marker.len() - 1);
+                    job.state.lock().unwrap().output.extend(pending.drain(..complete));
+                }
+                Err(RecvTimeoutError::Timeout) => (),
+                Err(RecvTimeoutError::Disconnected) => break ProcessStatus::Failure,
+            };
+        };
+        job.state.lock().unwrap().output.extend(pending);
+        if let Some(errors) = &process.errors {
+            // stderr is read on its own so whatever was written to it right before the marker may still be on its way.
+            while let Ok(output) = errors.recv_timeout(PROCESS_POLL_INTERVAL) {
+                job.state.lock().unwrap().errors.extend(output);
             }
-            // TODO: consider not locking per line...
-            state.lock().unwrap().output.push(line);
         }
-        Ok(())
+        // Whatever the interpreter is doing can't be interrupted so it needs to be started again.
+        if !finished {
+            self.stop();
+        }
+        status
+    }
+
+    // The marker can be followed by the snippet's status, where anything other than zero means it failed.
+    fn parse_status(status: &[u8]) -> ProcessStatus {
+        match String::from_utf8_lossy(status).trim() {
+            "" => ProcessStatus::Success,
+            status if status.parse::<u64>() == Ok(0) => ProcessStatus::Success,
+            _ => ProcessStatus::Failure,
+        }
+    }
+
+    fn launch(&self, target: OutputTarget) -> Result<SessionProcess, CodeExecuteError> {
+        let (command, args) = self.config.command.split_first().expect("no command");
+        let mut process = process::Command::new(command);
+        process.args(args).envs(&self.environment).current_dir(&self.cwd).stdin(Stdio::piped());
+        let readers = attach_output(&mut process, target)?;
+        let mut child = process.spawn().map_err(|e| CodeExecuteError::SpawnProcess(command.clone(), e))?;
+        let stdin = child.stdin.take().expect("no stdin");
+        let output = Self::forward_output(readers.output);
+        let errors = readers.errors.map(Self::forward_output);
+        Ok(SessionProcess { child, stdin, output, errors })
+    }
+
+    fn forward_output(reader: OutputReader) -> mpsc::Receiver<Vec<u8>> {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            read_output(reader, |output| {
+                let _ = sender.send(output.to_vec());
+            })
+        });
+        receiver
+    }
+
+    fn stop(&mut self) {
+        if let Some(mut process) = self.process.take() {
+            kill_process(&mut process.child);
+        }
+    }
+}
+
+// Programs write to a pty so they behave just like they would when running on a terminal.
+#[cfg(unix)]
+fn attach_output(process: &mut process::Command, target: OutputTarget) -> Result<OutputReaders, CodeExecuteError> {
+    use std::os::{fd::OwnedFd, unix::process::CommandExt};
+
+    let readers = match target {
+        OutputTarget::Terminal(terminal_size) => {
+            let (master, slave) = open_pty(terminal_size).map_err(CodeExecuteError::Pty)?;
+            let slave_clone = slave.try_clone().map_err(CodeExecuteError::Pty)?;
+            process.stdout(slave).stderr(slave_clone);
+            OutputReaders { output: master, errors: None }
+        }
+        OutputTarget::Pipes => {
+            let (output, errors) = attach_pipes(process)?;
+            OutputReaders { output: OwnedFd::from(output).into(), errors: Some(OwnedFd::from(errors).into()) }
+        }
+    };
+    unsafe {
+        process.pre_exec(|| {
+            // A new session also means a new process group, which lets us kill anything the process spawns.
+            if libc::setsid() == -1 {
+                return Err(io::Error::last_os_error());
+            }
+            // Make the pty the controlling terminal so `/dev/tty` works, but it's fine if this fails.
+            libc::ioctl(libc::STDOUT_FILENO, libc::TIOCSCTTY as _, 0);
+            Ok(())
+        });
+    }
+    Ok(readers)
+}
+
+#[cfg(not(unix))]
+fn attach_output(process: &mut process::Command, target: OutputTarget) -> Result<OutputReaders, CodeExecuteError> {
+    match target {
+        OutputTarget::Terminal(_) => {
+            let (reader, writer) = os_pipe::pipe().map_err(CodeExecuteError::Pipe)?;
+            let writer_clone = writer.try_clone().map_err(CodeExecuteError::Pipe)?;
+            process.stdout(writer).stderr(writer_clone);
+            Ok(OutputReaders { output: reader, errors: None })
+        }
+        OutputTarget::Pipes => {
+            let (output, errors) = attach_pipes(process)?;
+            Ok(OutputReaders { output, errors: Some(errors) })
+        }
+    }
+}
+
+// Sends stdout and stderr to pipes of their own, returning the ends they can be read from.
+fn attach_pipes(
+    process: &mut process::Command,
+) -> Result<(os_pipe::PipeReader, os_pipe::PipeReader), CodeExecuteError> {
+    let (output, output_writer) = os_pipe::pipe().map_err(CodeExecuteError::Pipe)?;
+    let (errors, errors_writer) = os_pipe::pipe().map_err(CodeExecuteError::Pipe)?;
+    process.stdout(output_writer).stderr(errors_writer);
+    Ok((output, errors))
+}
+
+/// Read the output of a process until it's gone.
+fn read_output(mut reader: OutputReader, mut consume: impl FnMut(&[u8])) {
+    let mut buffer = [0; 4096];
+    loop {
+        let bytes_read = match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(bytes_read) => bytes_read,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            // Reading from a pty fails rather than hitting EOF once every process using it is gone.
+            Err(_) => break,
+        };
+        let output = &buffer[..bytes_read];
+        // Without a pty there's nothing turning line feeds into carriage returns followed by line feeds.
+        #[cfg(not(unix))]
+        let output = &output
+            .iter()
+            .flat_map(|byte| if *byte == b'\n' { b"\r\n".as_slice() } else { std::slice::from_ref(byte) })
+            .copied()
+            .collect::<Vec<_>>();
+        consume(output);
+    }
+}
+
+/// Open a pseudo terminal of the given size, returning its master and slave ends.
+#[cfg(unix)]
+fn open_pty(size: TerminalSize) -> io::Result<(File, File)> {
+    use std::os::fd::{AsRawFd, FromRawFd};
+
+    let mut master = 0;
+    let mut slave = 0;
+    let mut window_size = libc::winsize { ws_row: size.rows, ws_col: size.columns, ws_xpixel: 0, ws_ypixel: 0 };
+    let result = unsafe {
+        libc::openpty(
+            &mut master,
+            &mut slave,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            std::ptr::addr_of_mut!(window_size),
+        )
+    };
+    if result != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let (master, slave) = unsafe { (File::from_raw_fd(master), File::from_raw_fd(slave)) };
+    // Neither end should leak into the processes we spawn other than as their output.
+    for fd in [master.as_raw_fd(), slave.as_raw_fd()] {
+        if unsafe { libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC) } == -1 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok((master, slave))
+}
+
+// Sets up the standard input of a process, returning anything that needs to be written to it.
+fn attach_input(
+    process: &mut process::Command,
+    stdin: Option<&SnippetStdin>,
+    cwd: &Path,
+) -> Result<Option<String>, CodeExecuteError> {
+    match stdin {
+        None => {
+            process.stdin(Stdio::null());
+            Ok(None)
+        }
+        Some(SnippetStdin::File(path)) => {
+            let file = File::open(cwd.join(path)).map_err(|e| CodeExecuteError::StdinFile(path.clone(), e))?;
+            process.stdin(file);
+            Ok(None)
+        }
+        Some(SnippetStdin::Contents(contents)) => {
+            process.stdin(Stdio::piped());
+            Ok(Some(contents.clone()))
+        }
+    }
+}
+
+// This happens on a separate thread so a process that doesn't read all of its input can't block us.
+fn feed_input(child: &mut Child, input: Option<String>) {
+    if let (Some(mut stdin), Some(input)) = (child.stdin.take(), input) {
+        thread::spawn(move || {
+            let _ = stdin.write_all(input.as_bytes());
+        });
+    }
+}
+
+/// Wait for a process to finish, killing it if it's cancelled or it doesn't finish in time.
+fn wait_for_process(child: &mut Child, deadline: Option<Instant>, cancelled: &AtomicBool) -> ProcessStatus {
+    loop {
+        match child.try_wait() {
+            Ok(Some(code)) if code.success() => return ProcessStatus::Success,
+            Ok(Some(_)) | Err(_) => return ProcessStatus::Failure,
+            Ok(None) => (),
+        };
+        if cancelled.load(Ordering::Relaxed) {
+            kill_process(child);
+            return ProcessStatus::Cancelled;
+        }
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            kill_process(child);
+            return ProcessStatus::TimedOut;
+        }
+        thread::sleep(PROCESS_POLL_INTERVAL);
+    }
+}
+
+// Processes run in their own process group so anything they spawn is killed along with them.
+fn kill_process(child: &mut Child) {
+    #[cfg(unix)]
+    unsafe {
+        libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL);
     }
+    #[cfg(not(unix))]
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+/// The output of a piece of code that was executed synchronously.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct CapturedOutput {
+    /// Everything the process wrote to stdout.
+    pub(crate) output: String,
+
+    /// Everything the process wrote to stderr.
+    pub(crate) errors: String,
+
+    pub(crate) status: ProcessStatus,
 }
 
 /// The state of the execution of a process.
 #[derive(Clone, Default, Debug)]
 pub(crate) struct ExecutionState {
-    pub(crate) output: Vec<String>,
+    /// Everything the process wrote to its terminal, or to stdout if stderr is captured separately.
+    pub(crate) output: Vec<u8>,
+
+    /// Everything the process wrote to stderr, if it's captured separately.
+    pub(crate) errors: Vec<u8>,
+
     pub(crate) status: ProcessStatus,
 }
 
+impl ExecutionState {
+    // Errors running a process go wherever the errors it writes itself would.
+    fn push_error(&mut self, error: &CodeExecuteError, target: OutputTarget) {
+        let output = match target {
+            OutputTarget::Terminal(_) => &mut self.output,
+            OutputTarget::Pipes => &mut self.errors,
+        };
+        output.extend(format!("{error}\r\n").as_bytes());
+    }
+}
+
 /// The status of a process.
 #[derive(Clone, Debug, Default)]
 pub(crate) enum ProcessStatus {
@@ -274,19 +796,55 @@ pub(crate) enum ProcessStatus {
     Running,
     Success,
     Failure,
+    TimedOut,
+    Cancelled,
 }
 
 impl ProcessStatus {
     /// Check whether the underlying process is finished.
     pub(crate) fn is_finished(&self) -> bool {
-        matches!(self, ProcessStatus::Success | ProcessStatus::Failure)
+        !matches!(self, ProcessStatus::Running)
+    }
+}
+
+/// The maximum amount of time a snippet can run for, e.g. `5s`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, DeserializeFromStr, JsonSchema)]
+pub struct ExecutionTimeout(#[schemars(with = "String")] pub(crate) Duration);
+
+impl FromStr for ExecutionTimeout {
+    type Err = ExecutionTimeoutParseError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let index = input.find(|c: char| !c.is_ascii_digit()).ok_or(ExecutionTimeoutParseError::Unit)?;
+        let (value, unit) = input.split_at(index);
+        let value: u64 = value.parse().map_err(|_| ExecutionTimeoutParseError::Value)?;
+        let duration = match unit {
+            "ms" => Duration::from_millis(value),
+            "s" => Duration::from_secs(value),
+            "m" => Duration::from_secs(value * 60),
+            _ => return Err(ExecutionTimeoutParseError::Unit),
+        };
+        Ok(Self(duration))
     }
 }
 
+#[derive(thiserror::Error, Debug)]
+pub enum ExecutionTimeoutParseError {
+    #[error("value must be a number")]
+    Value,
+
+    #[error("unit must be one of 'ms', 's', or 'm'")]
+    Unit,
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
     use crate::processing::code::SnippetAttributes;
+    use rstest::rstest;
+    use std::fs;
+
+    const TERMINAL_SIZE: TerminalSize = TerminalSize { columns: 40, rows: 24 };
 
     #[test]
     fn shell_code_execution() {
@@ -299,7 +857,7 @@ echo 'bye'"
             language: SnippetLanguage::Shell,
             attributes: SnippetAttributes { execute: true, ..Default::default() },
         };
-        let handle = SnippetExecutor::default().execute_async(&code).expect("execution failed");
+        let handle = SnippetExecutor::default().execute_async(&code, TERMINAL_SIZE).expect("execution failed");
         let state = loop {
             let state = handle.state.lock().unwrap();
             if state.status.is_finished() {
@@ -307,8 +865,7 @@ echo 'bye'"
             }
         };
 
-        let expected_lines = vec!["hello world", "bye"];
-        assert_eq!(state.output, expected_lines);
+        assert_eq!(String::from_utf8_lossy(&state.output), "hello world\r\nbye\r\n");
     }
 
     #[test]
@@ -319,7 +876,7 @@ echo 'bye'"
             language: SnippetLanguage::Shell,
             attributes: SnippetAttributes { execute: false, ..Default::default() },
         };
-        let result = SnippetExecutor::default().execute_async(&code);
+        let result = SnippetExecutor::default().execute_async(&code, TERMINAL_SIZE);
         assert!(result.is_err());
     }
 
@@ -335,7 +892,7 @@ echo 'hello world'
             language: SnippetLanguage::Shell,
             attributes: SnippetAttributes { execute: true, ..Default::default() },
         };
-        let handle = SnippetExecutor::default().execute_async(&code).expect("execution failed");
+        let handle = SnippetExecutor::default().execute_async(&code, TERMINAL_SIZE).expect("execution failed");
         let state = loop {
             let state = handle.state.lock().unwrap();
             if state.status.is_finished() {
@@ -343,8 +900,7 @@ echo 'hello world'
             }
         };
 
-        let expected_lines = vec!["This message redirects to stderr", "hello world"];
-        assert_eq!(state.output, expected_lines);
+        assert_eq!(String::from_utf8_lossy(&state.output), "This message redirects to stderr\r\nhello world\r\n");
     }
 
     #[test]
@@ -360,17 +916,291 @@ echo 'hello world'
             language: SnippetLanguage::Shell,
             attributes: SnippetAttributes { execute: true, ..Default::default() },
         };
-        let handle = SnippetExecutor::default().execute_async(&code).expect("execution failed");
+        let handle = SnippetExecutor::default().execute_async(&code, TERMINAL_SIZE).expect("execution failed");
+        let state = loop {
+            let state = handle.state.lock().unwrap();
+            if state.status.is_finished() {
+                break state;
+            }
+        };
+
+        let expected =
+            "this line was hidden\r\nthis line was hidden and contains another prefix /// \r\nhello world\r\n";
+        assert_eq!(String::from_utf8_lossy(&state.output), expected);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn shell_code_execution_on_terminal() {
+        let contents = r"
+test -t 1 && echo 'on a terminal'
+stty size < /dev/tty
+"
+        .into();
+        let code = Snippet {
+            contents,
+            language: SnippetLanguage::Shell,
+            attributes: SnippetAttributes { execute: true, ..Default::default() },
+        };
+        let handle = SnippetExecutor::default().execute_async(&code, TERMINAL_SIZE).expect("execution failed");
+        let state = loop {
+            let state = handle.state.lock().unwrap();
+            if state.status.is_finished() {
+                break state;
+            }
+        };
+        assert_eq!(String::from_utf8_lossy(&state.output), "on a terminal\r\n24 40\r\n");
+    }
+
+    #[test]
+    fn shell_code_execution_captured() {
+        let contents = r"
+echo 'hello world'
+echo 'oops' >&2
+exit 1
+"
+        .into();
+        let code = Snippet {
+            contents,
+            language: SnippetLanguage::Shell,
+            attributes: SnippetAttributes { execute: true, ..Default::default() },
+        };
+        let output = SnippetExecutor::default().execute_captured(&code).expect("execution failed");
+        assert_eq!(output.output, "hello world\n");
+        assert_eq!(output.errors, "oops\n");
+        assert!(matches!(output.status, ProcessStatus::Failure));
+    }
+
+    #[test]
+    fn shell_code_execution_times_out() {
+        let contents = r"
+echo 'hello world'
+sleep 10 &
+sleep 10
+"
+        .into();
+        let code = Snippet {
+            contents,
+            language: SnippetLanguage::Shell,
+            attributes: SnippetAttributes {
+                execute: true,
+                timeout: Some(ExecutionTimeout(Duration::from_millis(200))),
+                ..Default::default()
+            },
+        };
+        let started = Instant::now();
+        let handle = SnippetExecutor::default().execute_async(&code, TERMINAL_SIZE).expect("execution failed");
         let state = loop {
             let state = handle.state.lock().unwrap();
             if state.status.is_finished() {
                 break state;
             }
         };
+        assert!(matches!(state.status, ProcessStatus::TimedOut), "{:?}", state.status);
+        assert_eq!(String::from_utf8_lossy(&state.output), "hello world\r\n");
+        // The background process must have been killed too or we'd still be waiting on its output.
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn shell_code_execution_times_out_in_background() {
+        let code = Snippet {
+            // Ignoring SIGHUP keeps the background process alive after the session leader exits.
+            contents: "trap '' HUP\nsleep 10 &\necho 'hello world'".into(),
+            language: SnippetLanguage::Shell,
+            attributes: SnippetAttributes {
+                execute: true,
+                timeout: Some(ExecutionTimeout(Duration::from_millis(200))),
+                ..Default::default()
+            },
+        };
+        let started = Instant::now();
+        let handle = SnippetExecutor::default().execute_async(&code, TERMINAL_SIZE).expect("execution failed");
+        let state = wait_for_output(&handle);
+        assert!(matches!(state.status, ProcessStatus::TimedOut), "{:?}", state.status);
+        assert_eq!(String::from_utf8_lossy(&state.output), "hello world\r\n");
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn shell_code_execution_cancelled_in_background() {
+        let code = Snippet {
+            contents: "trap '' HUP\nsleep 10 &".into(),
+            language: SnippetLanguage::Shell,
+            attributes: SnippetAttributes { execute: true, ..Default::default() },
+        };
+        let started = Instant::now();
+        let handle = SnippetExecutor::default().execute_async(&code, TERMINAL_SIZE).expect("execution failed");
+        thread::sleep(Duration::from_millis(200));
+        handle.cancel();
+        let state = wait_for_output(&handle);
+        assert!(matches!(state.status, ProcessStatus::Cancelled), "{:?}", state.status);
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn shell_code_execution_cancelled() {
+        let code = Snippet {
+            contents: "sleep 10".into(),
+            language: SnippetLanguage::Shell,
+            attributes: SnippetAttributes { execute: true, ..Default::default() },
+        };
+        let handle = SnippetExecutor::default().execute_async(&code, TERMINAL_SIZE).expect("execution failed");
+        handle.cancel();
+        let status = loop {
+            let state = handle.state.lock().unwrap();
+            if state.status.is_finished() {
+                break state.status.clone();
+            }
+        };
+        assert!(matches!(status, ProcessStatus::Cancelled), "{status:?}");
+    }
+
+    #[test]
+    fn shell_code_execution_with_stdin_and_args() {
+        let code = Snippet {
+            contents: r#"echo "$# $1 $2"; cat"#.into(),
+            language: SnippetLanguage::Shell,
+            attributes: SnippetAttributes {
+                execute: true,
+                stdin: Some(SnippetStdin::Contents("hello\nworld\n".into())),
+                args: vec!["a".into(), "b c".into()],
+                ..Default::default()
+            },
+        };
+        let handle = SnippetExecutor::default().execute_async(&code, TERMINAL_SIZE).expect("execution failed");
+        let state = wait_for_output(&handle);
+        assert!(matches!(state.status, ProcessStatus::Success), "{:?}", state.status);
+        assert_eq!(String::from_utf8_lossy(&state.output), "2 a b c\r\nhello\r\nworld\r\n");
+    }
+
+    #[test]
+    fn shell_code_execution_captured_with_stdin_file() {
+        let directory = tempfile::tempdir().expect("creating directory failed");
+        fs::write(directory.path().join("input.txt"), "hello\n").expect("writing file failed");
+        let code = Snippet {
+            contents: "cat".into(),
+            language: SnippetLanguage::Shell,
+            attributes: SnippetAttributes {
+                execute: true,
+                stdin: Some(SnippetStdin::File("input.txt".into())),
+                ..Default::default()
+            },
+        };
+        let executor = SnippetExecutor::new(Default::default(), directory.path().into()).expect("invalid executor");
+        let output = executor.execute_captured(&code).expect("execution failed");
+        assert_eq!(output.output, "hello\n");
+
+        let code = Snippet {
+            attributes: SnippetAttributes { stdin: Some(SnippetStdin::File("missing.txt".into())), ..code.attributes },
+            ..code
+        };
+        let output = executor.execute_captured(&code).expect("execution failed");
+        assert!(matches!(output.status, ProcessStatus::Failure), "{:?}", output.status);
+        assert!(output.errors.starts_with("error opening stdin file 'missing.txt'"), "{}", output.errors);
+    }
+
+    fn session_snippet(contents: &str, session: &str) -> Snippet {
+        Snippet {
+            contents: contents.into(),
+            language: SnippetLanguage::Bash,
+            attributes: SnippetAttributes { execute: true, session: Some(session.into()), ..Default::default() },
+        }
+    }
+
+    fn wait_for_output(handle: &ExecutionHandle) -> ExecutionState {
+        loop {
+            let state = handle.state.lock().unwrap();
+            if state.status.is_finished() {
+                break state.clone();
+            }
+        }
+    }
+
+    #[test]
+    fn session_execution() {
+        let executor = SnippetExecutor::default();
+        let first = executor.execute_async(&session_snippet("x=42", "demo"), TERMINAL_SIZE).expect("execution failed");
+        let second =
+            executor.execute_async(&session_snippet("echo \"x=$x\"", "demo"), TERMINAL_SIZE).expect("execution failed");
+        let other = executor
+            .execute_async(&session_snippet("echo \"x=$x\"", "other"), TERMINAL_SIZE)
+            .expect("execution failed");
+
+        let first = wait_for_output(&first);
+        assert!(matches!(first.status, ProcessStatus::Success), "{:?}", first.status);
+        assert_eq!(String::from_utf8_lossy(&first.output), "");
+        let second = wait_for_output(&second);
+        assert_eq!(String::from_utf8_lossy(&second.output), "x=42\r\n");
+        let other = wait_for_output(&other);
+        assert_eq!(String::from_utf8_lossy(&other.output), "x=\r\n");
+    }
+
+    #[test]
+    fn session_execution_failed() {
+        let executor = SnippetExecutor::default();
+        let output = executor.execute_captured(&session_snippet("x=42; false", "demo")).expect("execution failed");
+        assert!(matches!(output.status, ProcessStatus::Failure), "{:?}", output.status);
+
+        // The session keeps going after a snippet fails.
+        let output = executor.execute_captured(&session_snippet("echo \"x=$x\"", "demo")).expect("execution failed");
+        assert!(matches!(output.status, ProcessStatus::Success), "{:?}", output.status);
+        assert_eq!(output.output, "x=42\n");
+    }
+
+    #[rstest]
+    #[case::empty("", true)]
+    #[case::zero(" 0", true)]
+    #[case::padded_zero(" 00000\r", true)]
+    #[case::non_zero(" 1", false)]
+    #[case::not_a_number(" 42P01", false)]
+    fn session_status(#[case] input: &str, #[case] success: bool) {
+        let status = SessionRunner::parse_status(input.as_bytes());
+        assert_eq!(matches!(status, ProcessStatus::Success), success, "{status:?}");
+    }
+
+    #[test]
+    fn session_restarted_after_timeout() {
+        let executor = SnippetExecutor::default();
+        let mut snippet = session_snippet("x=42; sleep 10", "demo");
+        snippet.attributes.timeout = Some(ExecutionTimeout(Duration::from_millis(200)));
+        let handle = executor.execute_async(&snippet, TERMINAL_SIZE).expect("execution failed");
+        let state = wait_for_output(&handle);
+        assert!(matches!(state.status, ProcessStatus::TimedOut), "{:?}", state.status);
+
+        let output = executor.execute_captured(&session_snippet("echo \"x=$x\"", "demo")).expect("execution failed");
+        assert_eq!(output.output, "x=\n");
+        assert!(matches!(output.status, ProcessStatus::Success), "{:?}", output.status);
+    }
+
+    #[test]
+    fn session_execution_captured() {
+        let executor = SnippetExecutor::default();
+        let output = executor.execute_captured(&session_snippet("x=42", "demo")).expect("execution failed");
+        assert!(matches!(output.status, ProcessStatus::Success), "{:?}", output.status);
+
+        let snippet = session_snippet("echo \"x=$x\"; echo oops >&2", "demo");
+        let output = executor.execute_captured(&snippet).expect("execution failed");
+        assert!(matches!(output.status, ProcessStatus::Success), "{:?}", output.status);
+        assert_eq!(output.output, "x=42\n");
+        assert_eq!(output.errors, "oops\n");
+    }
+
+    #[rstest]
+    #[case::millis("500ms", Duration::from_millis(500))]
+    #[case::seconds("5s", Duration::from_secs(5))]
+    #[case::minutes("2m", Duration::from_secs(120))]
+    fn parse_timeout(#[case] input: &str, #[case] expected: Duration) {
+        let timeout: ExecutionTimeout = input.parse().expect("parse failed");
+        assert_eq!(timeout.0, expected);
+    }
 
-        let expected_lines =
-            vec!["this line was hidden", "this line was hidden and contains another prefix /// ", "hello world"];
-        assert_eq!(state.output, expected_lines);
+    #[rstest]
+    #[case::no_unit("5")]
+    #[case::no_value("s")]
+    #[case::unknown_unit("5h")]
+    fn invalid_timeout(#[case] input: &str) {
+        input.parse::<ExecutionTimeout>().expect_err("parse succeeded");
     }
 
     #[test]
@@ -0,0 +1,271 @@
+use super::{ExportError, encode_png};
+use crate::{
+    render::virt::{PrintedImage, StyledChar, TerminalGrid},
+    style::{Color, Colors, TextStyle},
+};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use std::fmt::Write;
+
+const STYLES: &str = r#"
+body {
+  margin: 0;
+  overflow: hidden;
+  background-color: var(--background);
+  color: var(--foreground);
+}
+
+.slide {
+  display: none;
+  position: absolute;
+  top: 50%;
+  left: 50%;
+  width: calc(var(--columns) * 1ch);
+  height: calc(var(--rows) * var(--row-height));
+  font-family: monospace;
+  font-size: 16px;
+  line-height: var(--row-height);
+  white-space: pre;
+  overflow: hidden;
+}
+
+.slide.current {
+  display: block;
+}
+
+.row {
+  height: var(--row-height);
+}
+
+.image {
+  position: absolute;
+}
+"#;
+
+const SCRIPT: &str = r#"
+const slides = document.querySelectorAll(".slide");
+let current = 0;
+
+function show(index) {
+  current = Math.max(0, Math.min(index, slides.length - 1));
+  slides.forEach((slide, i) => slide.classList.toggle("current", i == current));
+  window.location.hash = current + 1;
+  resize();
+}
+
+function resize() {
+  const slide = slides[current];
+  const scale = Math.min(window.innerWidth / slide.offsetWidth, window.innerHeight / slide.offsetHeight);
+  slide.style.transform = `translate(-50%, -50%) scale(${scale})`;
+}
+
+document.addEventListener("keydown", (event) => {
+  switch (event.key) {
+    case "ArrowRight":
+    case "ArrowDown":
+    case "PageDown":
+    case " ":
+    case "l":
+    case "j":
+      show(current + 1);
+      break;
+    case "ArrowLeft":
+    case "ArrowUp":
+    case "PageUp":
+    case "h":
+    case "k":
+      show(current - 1);
+      break;
+    case "Home":
+    case "g":
+      show(0);
+      break;
+    case "End":
+    case "G":
+      show(slides.length - 1);
+      break;
+  }
+});
+window.addEventListener("resize", resize);
+show((parseInt(window.location.hash.substring(1)) || 1) - 1);
+"#;
+
+/// A presentation exported as a single, self contained, HTML file.
+pub(crate) struct HtmlPresentation {
+    title: String,
+    colors: Colors,
+    columns: u16,
+    rows: u16,
+    slides: Vec<String>,
+}
+
+impl HtmlPresentation {
+    pub(crate) fn new(title: String, colors: Colors, columns: u16, rows: u16) -> Self {
+        Self { title, colors, columns, rows, slides: Vec::new() }
+    }
+
+    /// Add a slide that has been rendered into the given grid.
+    pub(crate) fn add_slide(&mut self, grid: &TerminalGrid) -> Result<(), ExportError> {
+        let mut html = String::new();
+        match grid.background_color.or(self.colors.background) {
+            Some(color) => {
+                let _ = writeln!(html, r#"<div class="slide" style="background-color: {}">"#, css_color(color));
+            }
+            None => html.push_str("<div class=\"slide\">\n"),
+        };
+        for row in &grid.rows {
+            html.push_str(r#"<div class="row">"#);
+            Self::push_row(&mut html, row);
+            html.push_str("</div>\n");
+        }
+        for image in &grid.images {
+            Self::push_image(&mut html, image)?;
+        }
+        html.push_str("</div>\n");
+        self.slides.push(html);
+        Ok(())
+    }
+
+    /// Generate the HTML document.
+    pub(crate) fn generate(self) -> String {
+        let mut variables = format!(
+            "--columns: {}; --rows: {}; --row-height: 1.25em; --background: {}; --foreground: {};",
+            self.columns,
+            self.rows,
+            self.colors.background.map(css_color).unwrap_or_else(|| "black".into()),
+            self.colors.foreground.map(css_color).unwrap_or_else(|| "white".into()),
+        );
+        variables.insert_str(0, ":root { ");
+        variables.push_str(" }");
+
+        let title = escape(&self.title);
+        let slides = self.slides.concat();
+        format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n<style>\n{variables}\n{STYLES}</style>\n</head>\n<body>\n{slides}<script>{SCRIPT}</script>\n</body>\n</html>\n"
+        )
+    }
+
+    fn push_row(html: &mut String, row: &[StyledChar]) {
+        // Merge consecutive characters that use the same style into a single span.
+        let mut current: Option<(&TextStyle, String)> = None;
+        for cell in row {
+            if cell.character == StyledChar::WIDE_CHAR_PADDING {
+                continue;
+            }
+            match &mut current {
+                Some((style, text)) if *style == &cell.style => text.push(cell.character),
+                _ => {
+                    if let Some((style, text)) = current.take() {
+                        Self::push_span(html, style, &text);
+                    }
+                    current = Some((&cell.style, cell.character.to_string()));
+                }
+            }
+        }
+        if let Some((style, text)) = current {
+            Self::push_span(html, style, &text);
+        }
+    }
+
+    fn push_span(html: &mut String, style: &TextStyle, text: &str) {
+        let mut css = String::new();
+        if let Some(color) = style.colors.foreground {
+            let _ = write!(css, "color: {};", css_color(color));
+        }
+        if let Some(color) = style.colors.background {
+            let _ = write!(css, "background-color: {};", css_color(color));
+        }
+        if style.is_bold() {
+            css.push_str("font-weight: bold;");
+        }
+        if style.is_italics() {
+            css.push_str("font-style: italic;");
+        }
+        match (style.is_underlined(), style.is_strikethrough()) {
+            (true, true) => css.push_str("text-decoration: underline line-through;"),
+            (true, false) => css.push_str("text-decoration: underline;"),
+            (false, true) => css.push_str("text-decoration: line-through;"),
+            (false, false) => (),
+        };
+        let text = escape(text);
+        if css.is_empty() {
+            html.push_str(&text);
+        } else {
+            let _ = write!(html, r#"<span style="{css}">{text}</span>"#);
+        }
+    }
+
+    fn push_image(html: &mut String, image: &PrintedImage) -> Result<(), ExportError> {
+        let contents = STANDARD.encode(encode_png(&image.image)?);
+        let PrintedImage { row, column, rows, columns, z_index, .. } = image;
+        let _ = writeln!(
+            html,
+            r#"<img class="image" src="data:image/png;base64,{contents}" style="top: calc({row} * var(--row-height)); left: {column}ch; width: {columns}ch; height: calc({rows} * var(--row-height)); z-index: {z_index}">"#
+        );
+        Ok(())
+    }
+}
+
+fn css_color(color: Color) -> String {
+    let (r, g, b) = color.to_palette_rgb();
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+fn escape(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => output.push_str("&amp;"),
+            '<' => output.push_str("&lt;"),
+            '>' => output.push_str("&gt;"),
+            '"' => output.push_str("&quot;"),
+            _ => output.push(c),
+        };
+    }
+    output
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        markdown::{elements::Text, text::WeightedLine},
+        presentation::RenderOperation,
+        render::{
+            engine::{RenderEngine, RenderEngineOptions},
+            properties::WindowSize,
+            virt::VirtualTerminal,
+        },
+        theme::Alignment,
+    };
+
+    fn render(operations: &[RenderOperation]) -> TerminalGrid {
+        let dimensions = WindowSize { rows: 2, columns: 6, width: 60, height: 40 };
+        let mut terminal = VirtualTerminal::new(&dimensions);
+        let engine = RenderEngine::new(&mut terminal, dimensions, RenderEngineOptions::default());
+        engine.render(operations.iter()).expect("render failed");
+        terminal.into_contents()
+    }
+
+    #[test]
+    fn styled_spans() {
+        let grid = render(&[RenderOperation::RenderText {
+            line: WeightedLine::from(vec![Text::new("<a>", TextStyle::default().bold()), Text::from("b")]),
+            alignment: Alignment::default(),
+        }]);
+        let mut html = String::new();
+        HtmlPresentation::push_row(&mut html, &grid.rows[0]);
+        assert_eq!(html, r#"<span style="font-weight: bold;">&lt;a&gt;</span>b  "#);
+    }
+
+    #[test]
+    fn colored_slide() {
+        let colors = Colors { background: Some(Color::new(1, 2, 3)), foreground: Some(Color::Red) };
+        let grid = render(&[RenderOperation::SetColors(colors), RenderOperation::ClearScreen]);
+        let mut presentation = HtmlPresentation::new("<deck>".into(), colors, 6, 2);
+        presentation.add_slide(&grid).expect("adding slide failed");
+        let html = presentation.generate();
+        assert!(html.contains("<title>&lt;deck&gt;</title>"));
+        assert!(html.contains(r#"<div class="slide" style="background-color: #010203">"#));
+        assert!(html.contains("--foreground: #ff0000;"));
+    }
+}
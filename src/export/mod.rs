@@ -0,0 +1,383 @@
+pub use self::snapshot::{SnapshotFormat, SnapshotSize};
+use self::{html::HtmlPresentation, pdf::PdfPresentation, snapshot::SlideSnapshot};
+use crate::{
+    MarkdownParser, PresentationTheme, Resources,
+    custom::KeyBindingsConfig,
+    execute::SnippetExecutor,
+    markdown::parse::ParseError,
+    media::{
+        image::Image,
+        printer::{ImageResource, ResourceProperties},
+    },
+    presentation::{Presentation, RenderOperation},
+    processing::{
+        builder::{BuildError, PresentationBuilder, PresentationBuilderOptions, Themes},
+        lint::{Lint, LintReport, PresentationLinter},
+        snippets::{SnippetReport, SnippetRunner},
+    },
+    render::{
+        draw::RenderError,
+        engine::RenderEngine,
+        properties::WindowSize,
+        validate::{OverflowReport, OverflowValidator},
+        virt::{TerminalGrid, VirtualTerminal},
+    },
+    third_party::ThirdPartyRender,
+};
+use image::{DynamicImage, ImageEncoder, ImageError, codecs::png::PngEncoder};
+use std::{
+    fs, io,
+    path::Path,
+    rc::Rc,
+    thread::sleep,
+    time::{Duration, Instant},
+};
+
+mod html;
+mod pdf;
+mod snapshot;
+
+const ASYNC_RENDER_TIMEOUT: Duration = Duration::from_secs(120);
+const ASYNC_RENDER_POLL_INTERVAL: Duration = Duration::from_millis(200);
+const DEFAULT_EXPORT_DIMENSIONS: WindowSize = WindowSize { rows: 40, columns: 120, width: 1200, height: 800 };
+const DEFAULT_FONT_SIZE: u8 = 10;
+
+/// Allows exporting presentations into PDF and HTML.
+pub struct Exporter<'a> {
+    parser: MarkdownParser<'a>,
+    default_theme: &'a PresentationTheme,
+    resources: Resources,
+    third_party: ThirdPartyRender,
+    code_executor: Rc<SnippetExecutor>,
+    themes: Themes,
+    options: PresentationBuilderOptions,
+}
+
+impl<'a> Exporter<'a> {
+    /// Construct a new exporter.
+    pub fn new(
+        parser: MarkdownParser<'a>,
+        default_theme: &'a PresentationTheme,
+        resources: Resources,
+        third_party: ThirdPartyRender,
+        code_executor: Rc<SnippetExecutor>,
+        themes: Themes,
+        options: PresentationBuilderOptions,
+    ) -> Self {
+        Self { parser, default_theme, resources, third_party, code_executor, themes, options }
+    }
+
+    /// Export the given presentation into PDF.
+    ///
+    /// The generated file is placed next to the presentation file.
+    pub fn export_pdf(&mut self, presentation_path: &Path) -> Result<(), ExportError> {
+        println!("Rendering slides...");
+        let content = fs::read_to_string(presentation_path).map_err(ExportError::ReadPresentation)?;
+        let output_path = presentation_path.with_extension("pdf");
+        let pdf = self.generate_pdf(&content, presentation_path)?;
+        fs::write(&output_path, pdf).map_err(ExportError::Io)?;
+        println!("Presentation exported to {}", output_path.display());
+        Ok(())
+    }
+
+    /// Export the given presentation into a single HTML file.
+    ///
+    /// The generated file is self contained and is placed next to the presentation file.
+    pub fn export_html(&mut self, presentation_path: &Path) -> Result<(), ExportError> {
+        println!("Rendering slides...");
+        let content = fs::read_to_string(presentation_path).map_err(ExportError::ReadPresentation)?;
+        let output_path = presentation_path.with_extension("html");
+        let html = self.generate_html(&content, presentation_path)?;
+        fs::write(&output_path, html).map_err(ExportError::Io)?;
+        println!("Presentation exported to {}", output_path.display());
+        Ok(())
+    }
+
+    /// Render a single slide, with all of its chunks visible, and format it.
+    ///
+    /// Slides are numbered starting at 1. If no size is provided, the current terminal's size is used.
+    pub fn render_slide(
+        &mut self,
+        presentation_path: &Path,
+        slide: usize,
+        size: Option<&SnapshotSize>,
+        format: &SnapshotFormat,
+    ) -> Result<String, ExportError> {
+        let content = fs::read_to_string(presentation_path).map_err(ExportError::ReadPresentation)?;
+        let mut presentation = self.build_presentation(&content)?;
+        let total_slides = presentation.iter_slides().count();
+        if slide == 0 || slide > total_slides {
+            return Err(ExportError::InvalidSlide { slide, total_slides });
+        }
+        let dimensions = match size {
+            Some(size) => size.window_size(DEFAULT_FONT_SIZE),
+            None => Self::export_dimensions(),
+        };
+        let grid = Self::render_slide_contents(&mut presentation, slide - 1, &dimensions)?;
+        Ok(SlideSnapshot::new(slide, &grid).format(format))
+    }
+
+    /// Check that every slide, with all of its chunks visible, fits on the screen when using each of
+    /// the given sizes.
+    pub fn check_overflows(
+        &mut self,
+        presentation_path: &Path,
+        sizes: &[SnapshotSize],
+    ) -> Result<OverflowReport, ExportError> {
+        let content = fs::read_to_string(presentation_path).map_err(ExportError::ReadPresentation)?;
+        let mut presentation = self.build_presentation(&content)?;
+        let sizes: Vec<_> = sizes.iter().map(|size| size.window_size(DEFAULT_FONT_SIZE)).collect();
+        let report = OverflowValidator::find_all(&mut presentation, &sizes)?;
+        Ok(report)
+    }
+
+    /// Look for problems in the given presentation without rendering it.
+    ///
+    /// The presentation is only built if no problems are found up front, as building it would
+    /// otherwise report most of them a second time.
+    pub fn lint(&mut self, presentation_path: &Path) -> Result<LintReport, ExportError> {
+        let content = fs::read_to_string(presentation_path).map_err(ExportError::ReadPresentation)?;
+        let path = presentation_path.to_path_buf();
+        let (elements, parse_errors) = self.parser.parse_all(&content);
+        let mut lints: Vec<_> = parse_errors
+            .into_iter()
+            .map(|e| Lint { file: None, position: Some(e.sourcepos), error: e.into() })
+            .collect();
+        let linter = PresentationLinter::new(&mut self.resources, &self.code_executor, self.options.clone());
+        lints.extend(linter.lint(&elements));
+        if lints.is_empty() {
+            let result = PresentationBuilder::new(
+                self.default_theme,
+                &mut self.resources,
+                &mut self.third_party,
+                self.code_executor.clone(),
+                &self.themes,
+                Default::default(),
+                KeyBindingsConfig::default(),
+                self.options.clone(),
+            )
+            .build_all(elements, Vec::new());
+            match result {
+                Ok((_, errors)) => lints.extend(errors.into_iter().map(|e| Lint {
+                    file: None,
+                    position: Some(e.source_position),
+                    error: e.error.into(),
+                })),
+                Err(e) => lints.push(Lint { file: None, position: None, error: e.into() }),
+            };
+        }
+        Ok(LintReport::new(path, lints))
+    }
+
+    /// Run every executable snippet in the given presentation without rendering it.
+    pub fn run_snippets(&mut self, presentation_path: &Path) -> Result<SnippetReport, ExportError> {
+        let content = fs::read_to_string(presentation_path).map_err(ExportError::ReadPresentation)?;
+        let elements = self.parser.parse(&content)?;
+        let runs = SnippetRunner::new(&mut self.resources, &self.code_executor, self.options.clone()).run(&elements);
+        Ok(SnippetReport::new(presentation_path.to_path_buf(), runs))
+    }
+
+    fn generate_html(&mut self, content: &str, path: &Path) -> Result<String, ExportError> {
+        let dimensions = Self::export_dimensions();
+        let grids = self.render_presentation(content, &dimensions)?;
+        let colors = self.default_theme.default_style.colors;
+        let mut html = HtmlPresentation::new(Self::title(path), colors, dimensions.columns, dimensions.rows);
+        for grid in grids {
+            html.add_slide(&grid)?;
+        }
+        Ok(html.generate())
+    }
+
+    fn generate_pdf(&mut self, content: &str, path: &Path) -> Result<Vec<u8>, ExportError> {
+        let dimensions = Self::export_dimensions();
+        let grids = self.render_presentation(content, &dimensions)?;
+        let colors = self.default_theme.default_style.colors;
+        let mut pdf = PdfPresentation::new(&Self::title(path), colors, dimensions.columns, dimensions.rows);
+        for grid in grids {
+            pdf.add_slide(&grid)?;
+        }
+        Ok(pdf.generate())
+    }
+
+    fn title(path: &Path) -> String {
+        path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default()
+    }
+
+    fn render_presentation(
+        &mut self,
+        content: &str,
+        dimensions: &WindowSize,
+    ) -> Result<Vec<TerminalGrid>, ExportError> {
+        let presentation = self.build_presentation(content)?;
+        Self::validate_theme_colors(&presentation)?;
+        Self::render_slides(presentation, dimensions)
+    }
+
+    fn build_presentation(&mut self, content: &str) -> Result<Presentation, ExportError> {
+        let elements = self.parser.parse(content)?;
+        let mut presentation = PresentationBuilder::new(
+            self.default_theme,
+            &mut self.resources,
+            &mut self.third_party,
+            self.code_executor.clone(),
+            &self.themes,
+            Default::default(),
+            KeyBindingsConfig::default(),
+            self.options.clone(),
+        )
+        .build(elements)?;
+        Self::wait_for_async_renders(&mut presentation, ASYNC_RENDER_TIMEOUT)?;
+        Ok(presentation)
+    }
+
+    // Snippet executions and third party renders all need to be finished before we can export.
+    fn wait_for_async_renders(presentation: &mut Presentation, timeout: Duration) -> Result<(), ExportError> {
+        presentation.trigger_all_async_renders();
+        let deadline = Instant::now() + timeout;
+        loop {
+            let pending = presentation.slides_with_async_renders();
+            if pending.is_empty() {
+                return Ok(());
+            }
+            if Instant::now() >= deadline {
+                let mut slides: Vec<_> = pending.into_iter().map(|index| index + 1).collect();
+                slides.sort();
+                return Err(ExportError::AsyncRenderTimeout(slides));
+            }
+            sleep(ASYNC_RENDER_POLL_INTERVAL);
+        }
+    }
+
+    fn export_dimensions() -> WindowSize {
+        WindowSize::current(DEFAULT_FONT_SIZE).unwrap_or(DEFAULT_EXPORT_DIMENSIONS)
+    }
+
+    /// Render every slide, with all of its chunks visible, into a virtual terminal.
+    fn render_slides(
+        mut presentation: Presentation,
+        dimensions: &WindowSize,
+    ) -> Result<Vec<TerminalGrid>, ExportError> {
+        let total_slides = presentation.iter_slides().count();
+        let mut grids = Vec::new();
+        for index in 0..total_slides {
+            grids.push(Self::render_slide_contents(&mut presentation, index, dimensions)?);
+        }
+        Ok(grids)
+    }
+
+    fn render_slide_contents(
+        presentation: &mut Presentation,
+        index: usize,
+        dimensions: &WindowSize,
+    ) -> Result<TerminalGrid, ExportError> {
+        presentation.go_to_slide(index);
+        let total_chunks = presentation.current_slide().iter_chunks().count();
+        presentation.jump_chunk(total_chunks.saturating_sub(1));
+
+        let mut terminal = VirtualTerminal::new(dimensions);
+        let engine = RenderEngine::new(&mut terminal, dimensions.clone(), Default::default());
+        engine.render(presentation.current_slide().iter_visible_operations())?;
+        Ok(terminal.into_contents())
+    }
+
+    fn validate_theme_colors(presentation: &Presentation) -> Result<(), ExportError> {
+        for slide in presentation.iter_slides() {
+            for operation in slide.iter_visible_operations() {
+                let RenderOperation::SetColors(colors) = operation else {
+                    continue;
+                };
+                // The PDF requires a specific theme to be set, as "no background" means "what the
+                // browser uses" which is likely white and it will probably look terrible. It's
+                // better to err early and let you choose a theme that contains _some_ color.
+                if colors.background.is_none() {
+                    return Err(ExportError::UnsupportedColor("background"));
+                }
+                if colors.foreground.is_none() {
+                    return Err(ExportError::UnsupportedColor("foreground"));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ExportError {
+    #[error("failed to read presentation: {0}")]
+    ReadPresentation(io::Error),
+
+    #[error("failed to parse presentation: {0}")]
+    ParsePresentation(#[from] ParseError),
+
+    #[error("failed to build presentation: {0}")]
+    BuildPresentation(#[from] BuildError),
+
+    #[error("unsupported {0} color in theme")]
+    UnsupportedColor(&'static str),
+
+    #[error("generating images: {0}")]
+    GeneratingImages(#[from] ImageError),
+
+    #[error("slide {slide} does not exist, presentation has {total_slides} slides")]
+    InvalidSlide { slide: usize, total_slides: usize },
+
+    #[error("failed to render presentation: {0}")]
+    Render(#[from] RenderError),
+
+    #[error("timed out waiting for slides {} to finish rendering", .0.iter().map(ToString::to_string).collect::<Vec<_>>().join(", "))]
+    AsyncRenderTimeout(Vec<usize>),
+
+    #[error("io: {0}")]
+    Io(#[from] io::Error),
+}
+
+/// Get the decoded contents of an image.
+fn image_contents(image: &Image) -> &DynamicImage {
+    let ImageResource::Ascii(resource) = image.resource.as_ref() else { panic!("not in ascii mode") };
+    resource
+}
+
+/// Encode an image as PNG.
+fn encode_png(image: &Image) -> Result<Vec<u8>, ExportError> {
+    let mut buffer = Vec::new();
+    let contents = image_contents(image);
+    let (width, height) = image.dimensions();
+    PngEncoder::new(&mut buffer).write_image(contents.as_bytes(), width, height, contents.color().into())?;
+    Ok(buffer)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::presentation::{AsRenderOperations, RenderAsync, RenderAsyncState, Slide};
+
+    #[derive(Debug)]
+    struct NeverRendered;
+
+    impl AsRenderOperations for NeverRendered {
+        fn as_render_operations(&self, _: &WindowSize) -> Vec<RenderOperation> {
+            Vec::new()
+        }
+    }
+
+    impl RenderAsync for NeverRendered {
+        fn start_render(&self) -> bool {
+            true
+        }
+
+        fn poll_state(&self) -> RenderAsyncState {
+            RenderAsyncState::Rendering { modified: false }
+        }
+    }
+
+    #[test]
+    fn async_render_timeout() {
+        let mut presentation = Presentation::from(vec![
+            Slide::from(vec![RenderOperation::ClearScreen]),
+            Slide::from(vec![RenderOperation::RenderAsync(Rc::new(NeverRendered))]),
+        ]);
+        let result = Exporter::wait_for_async_renders(&mut presentation, Duration::from_millis(10));
+        let Err(ExportError::AsyncRenderTimeout(slides)) = result else { panic!("unexpected result: {result:?}") };
+        assert_eq!(slides, &[2]);
+    }
+}
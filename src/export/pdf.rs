@@ -0,0 +1,682 @@
+use super::{ExportError, image_contents};
+use crate::{
+    media::printer::ImageResource,
+    render::virt::{PrintedImage, StyledChar, TerminalGrid},
+    style::{Color, Colors, TextStyle},
+};
+use flate2::{Compression, write::ZlibEncoder};
+use image::GenericImageView;
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+    sync::Arc,
+};
+
+// Courier glyphs are 0.6 ems wide so this makes every character fall exactly on its cell.
+const FONT_SIZE: f32 = 10.0;
+const CELL_WIDTH: f32 = FONT_SIZE * 0.6;
+const CELL_HEIGHT: f32 = CELL_WIDTH * 2.0;
+const BASELINE_OFFSET: f32 = CELL_HEIGHT * 0.28;
+const LIGHT_LINE_WIDTH: f32 = CELL_WIDTH * 0.12;
+const HEAVY_LINE_WIDTH: f32 = CELL_WIDTH * 0.3;
+const FONTS: &[&str] = &["Courier", "Courier-Bold", "Courier-Oblique", "Courier-BoldOblique"];
+
+/// A presentation exported as a PDF file.
+///
+/// Every slide is laid out as a grid of monospace characters using PDF's built in Courier fonts,
+/// so the output doesn't need any fonts to be embedded in it.
+pub(crate) struct PdfPresentation {
+    writer: PdfWriter,
+    colors: Colors,
+    width: f32,
+    height: f32,
+    pages_id: usize,
+    font_ids: Vec<usize>,
+    page_ids: Vec<usize>,
+    image_ids: HashMap<*const ImageResource, usize>,
+    info_id: usize,
+}
+
+impl PdfPresentation {
+    pub(crate) fn new(title: &str, colors: Colors, columns: u16, rows: u16) -> Self {
+        let mut writer = PdfWriter::default();
+        let pages_id = writer.reserve();
+        let font_ids = FONTS
+            .iter()
+            .map(|font| {
+                let font = format!("<< /Type /Font /Subtype /Type1 /BaseFont /{font} /Encoding /WinAnsiEncoding >>");
+                writer.add(font.into_bytes())
+            })
+            .collect();
+        let info_id = writer.add(format!("<< /Title {} /Producer (presenterm) >>", pdf_string(title)).into_bytes());
+        Self {
+            writer,
+            colors,
+            width: columns as f32 * CELL_WIDTH,
+            height: rows as f32 * CELL_HEIGHT,
+            pages_id,
+            font_ids,
+            page_ids: Vec::new(),
+            image_ids: HashMap::new(),
+            info_id,
+        }
+    }
+
+    /// Add a page that contains the slide rendered in the given grid.
+    pub(crate) fn add_slide(&mut self, grid: &TerminalGrid) -> Result<(), ExportError> {
+        let background = grid.background_color.or(self.colors.background).unwrap_or(Color::Black);
+        let foreground = self.colors.foreground.unwrap_or(Color::White);
+        let mut canvas = PageCanvas { content: Vec::new(), height: self.height };
+        canvas.fill_rect(0.0, 0.0, self.width, self.height, background)?;
+        for (row_index, row) in grid.rows.iter().enumerate() {
+            canvas.draw_row_background(row_index, row, background)?;
+        }
+
+        let mut images = Vec::new();
+        let mut sorted_images: Vec<_> = grid.images.iter().collect();
+        sorted_images.sort_by_key(|image| image.z_index);
+        for image in sorted_images {
+            let id = self.register_image(image)?;
+            let name = format!("Im{}", images.len());
+            canvas.draw_image(&name, image)?;
+            images.push((name, id));
+        }
+        for (row_index, row) in grid.rows.iter().enumerate() {
+            canvas.draw_row_text(row_index, row, foreground)?;
+        }
+
+        let content_id = self.writer.add_stream("", &canvas.content)?;
+        let fonts: String =
+            self.font_ids.iter().enumerate().map(|(index, id)| format!("/F{index} {id} 0 R ")).collect();
+        let images: String = images.iter().map(|(name, id)| format!("/{name} {id} 0 R ")).collect();
+        let page = format!(
+            "<< /Type /Page /Parent {} 0 R /MediaBox [0 0 {} {}] /Contents {content_id} 0 R /Resources << /Font << {fonts}>> /XObject << {images}>> >> >>",
+            self.pages_id,
+            format_number(self.width),
+            format_number(self.height),
+        );
+        let page_id = self.writer.add(page.into_bytes());
+        self.page_ids.push(page_id);
+        Ok(())
+    }
+
+    /// Generate the PDF document.
+    pub(crate) fn generate(mut self) -> Vec<u8> {
+        let kids: String = self.page_ids.iter().map(|id| format!("{id} 0 R ")).collect();
+        let pages = format!("<< /Type /Pages /Kids [{kids}] /Count {} >>", self.page_ids.len());
+        self.writer.set(self.pages_id, pages.into_bytes());
+        let catalog_id = self.writer.add(format!("<< /Type /Catalog /Pages {} 0 R >>", self.pages_id).into_bytes());
+        self.writer.finish(catalog_id, self.info_id)
+    }
+
+    fn register_image(&mut self, image: &PrintedImage) -> Result<usize, ExportError> {
+        let key = Arc::as_ptr(&image.image.resource);
+        if let Some(id) = self.image_ids.get(&key) {
+            return Ok(*id);
+        }
+        let contents = image_contents(&image.image);
+        let (width, height) = contents.dimensions();
+        let contents = contents.to_rgba8();
+        let mut rgb = Vec::with_capacity(contents.len() / 4 * 3);
+        let mut alpha = Vec::with_capacity(contents.len() / 4);
+        for pixel in contents.pixels() {
+            rgb.extend_from_slice(&pixel.0[0..3]);
+            alpha.push(pixel.0[3]);
+        }
+        let properties = format!("/Width {width} /Height {height} /BitsPerComponent 8");
+        let mask = if alpha.iter().any(|value| *value != 255) {
+            let dictionary = format!("/Type /XObject /Subtype /Image {properties} /ColorSpace /DeviceGray");
+            let id = self.writer.add_stream(&dictionary, &alpha)?;
+            format!("/SMask {id} 0 R")
+        } else {
+            String::new()
+        };
+        let dictionary = format!("/Type /XObject /Subtype /Image {properties} /ColorSpace /DeviceRGB {mask}");
+        let id = self.writer.add_stream(&dictionary, &rgb)?;
+        self.image_ids.insert(key, id);
+        Ok(id)
+    }
+}
+
+/// The content of a single page.
+struct PageCanvas {
+    content: Vec<u8>,
+    height: f32,
+}
+
+impl PageCanvas {
+    fn draw_row_background(&mut self, row_index: usize, row: &[StyledChar], page_background: Color) -> io::Result<()> {
+        let mut column = 0;
+        for (color, cells) in group_by(row, |cell| cell.style.colors.background) {
+            let length = cells.len();
+            if let Some(color) = color.filter(|color| *color != page_background) {
+                let (x, y) = self.cell_origin(column, row_index);
+                self.fill_rect(x, y, length as f32 * CELL_WIDTH, CELL_HEIGHT, color)?;
+            }
+            column += length;
+        }
+        Ok(())
+    }
+
+    fn draw_row_text(&mut self, row_index: usize, row: &[StyledChar], default_foreground: Color) -> io::Result<()> {
+        let mut column = 0;
+        for (style, cells) in group_by(row, |cell| TextKey::new(&cell.style, default_foreground)) {
+            let mut text = Vec::new();
+            for (offset, cell) in cells.iter().enumerate() {
+                match Glyph::from(cell.character) {
+                    Glyph::Text(byte) => text.push(byte),
+                    Glyph::Shape(shape) => {
+                        self.draw_shape(column + offset, row_index, shape, style.color)?;
+                        text.push(b' ');
+                    }
+                }
+            }
+            if text.iter().any(|byte| *byte != b' ') {
+                self.draw_text(column, row_index, &text, &style)?;
+            }
+            column += cells.len();
+        }
+        Ok(())
+    }
+
+    fn draw_text(&mut self, column: usize, row: usize, text: &[u8], style: &TextKey) -> io::Result<()> {
+        let font = match (style.bold, style.italics) {
+            (false, false) => 0,
+            (true, false) => 1,
+            (false, true) => 2,
+            (true, true) => 3,
+        };
+        let (x, y) = self.cell_origin(column, row);
+        self.set_fill_color(style.color)?;
+        write!(
+            self.content,
+            "BT /F{font} {} Tf {} {} Td (",
+            format_number(FONT_SIZE),
+            format_number(x),
+            format_number(y + BASELINE_OFFSET)
+        )?;
+        for byte in text {
+            if matches!(byte, b'(' | b')' | b'\\') {
+                self.content.push(b'\\');
+            }
+            self.content.push(*byte);
+        }
+        self.content.extend_from_slice(b") Tj ET\n");
+
+        let width = text.len() as f32 * CELL_WIDTH;
+        if style.underlined {
+            self.fill_rect(x, y + BASELINE_OFFSET * 0.5, width, LIGHT_LINE_WIDTH, style.color)?;
+        }
+        if style.strikethrough {
+            self.fill_rect(x, y + CELL_HEIGHT * 0.5, width, LIGHT_LINE_WIDTH, style.color)?;
+        }
+        Ok(())
+    }
+
+    fn draw_shape(&mut self, column: usize, row: usize, shape: Shape, color: Color) -> io::Result<()> {
+        let (x, y) = self.cell_origin(column, row);
+        let (center_x, center_y) = (x + CELL_WIDTH / 2.0, y + CELL_HEIGHT / 2.0);
+        match shape {
+            Shape::Lines { up, down, left, right } => {
+                let segments = [
+                    (up, center_x, center_y, center_x, y + CELL_HEIGHT),
+                    (down, center_x, center_y, center_x, y),
+                    (left, center_x, center_y, x, center_y),
+                    (right, center_x, center_y, x + CELL_WIDTH, center_y),
+                ];
+                self.set_stroke_color(color)?;
+                for (line, from_x, from_y, to_x, to_y) in segments {
+                    let width = match line {
+                        Line::None => continue,
+                        Line::Light => LIGHT_LINE_WIDTH,
+                        Line::Heavy => HEAVY_LINE_WIDTH,
+                    };
+                    // Extend every segment by half the line width so joints don't leave gaps.
+                    let (from_x, from_y) = match (to_x - from_x, to_y - from_y) {
+                        (dx, _) if dx > 0.0 => (from_x - width / 2.0, from_y),
+                        (dx, _) if dx < 0.0 => (from_x + width / 2.0, from_y),
+                        (_, dy) if dy > 0.0 => (from_x, from_y - width / 2.0),
+                        _ => (from_x, from_y + width / 2.0),
+                    };
+                    writeln!(
+                        self.content,
+                        "{} w {} {} m {} {} l S",
+                        format_number(width),
+                        format_number(from_x),
+                        format_number(from_y),
+                        format_number(to_x),
+                        format_number(to_y)
+                    )?;
+                }
+            }
+            Shape::Block { left, bottom, right, top } => {
+                let block_x = x + CELL_WIDTH * left;
+                let block_y = y + CELL_HEIGHT * bottom;
+                let width = CELL_WIDTH * (right - left);
+                let height = CELL_HEIGHT * (top - bottom);
+                self.fill_rect(block_x, block_y, width, height, color)?;
+            }
+            Shape::Square { filled } => {
+                let size = CELL_WIDTH * 0.5;
+                let (x, y) = (center_x - size / 2.0, center_y - size / 2.0);
+                if filled {
+                    self.fill_rect(x, y, size, size, color)?;
+                } else {
+                    self.set_stroke_color(color)?;
+                    let (x, y, size) = (format_number(x), format_number(y), format_number(size));
+                    writeln!(self.content, "{} w {x} {y} {size} {size} re S", format_number(LIGHT_LINE_WIDTH))?;
+                }
+            }
+            Shape::Circle { filled } => {
+                self.draw_circle(center_x, center_y, CELL_WIDTH * 0.25, filled, color)?;
+            }
+        };
+        Ok(())
+    }
+
+    fn draw_circle(&mut self, x: f32, y: f32, radius: f32, filled: bool, color: Color) -> io::Result<()> {
+        // Approximate the circle using 4 bezier curves.
+        let k = radius * 0.5523;
+        let points = [
+            (x + radius, y, x + radius, y + k, x + k, y + radius, x, y + radius),
+            (x, y + radius, x - k, y + radius, x - radius, y + k, x - radius, y),
+            (x - radius, y, x - radius, y - k, x - k, y - radius, x, y - radius),
+            (x, y - radius, x + k, y - radius, x + radius, y - k, x + radius, y),
+        ];
+        if filled {
+            self.set_fill_color(color)?;
+        } else {
+            self.set_stroke_color(color)?;
+            writeln!(self.content, "{} w", format_number(LIGHT_LINE_WIDTH))?;
+        }
+        writeln!(self.content, "{} {} m", format_number(points[0].0), format_number(points[0].1))?;
+        for (_, _, x1, y1, x2, y2, x3, y3) in points {
+            let coordinates = [x1, y1, x2, y2, x3, y3].map(format_number).join(" ");
+            writeln!(self.content, "{coordinates} c")?;
+        }
+        let operator = if filled { "f" } else { "S" };
+        writeln!(self.content, "{operator}")
+    }
+
+    fn draw_image(&mut self, name: &str, image: &PrintedImage) -> io::Result<()> {
+        let (x, _) = self.cell_origin(image.column as usize, 0);
+        let width = image.columns as f32 * CELL_WIDTH;
+        let height = image.rows as f32 * CELL_HEIGHT;
+        let y = self.height - image.row as f32 * CELL_HEIGHT - height;
+        writeln!(
+            self.content,
+            "q {} 0 0 {} {} {} cm /{name} Do Q",
+            format_number(width),
+            format_number(height),
+            format_number(x),
+            format_number(y)
+        )
+    }
+
+    fn fill_rect(&mut self, x: f32, y: f32, width: f32, height: f32, color: Color) -> io::Result<()> {
+        self.set_fill_color(color)?;
+        let [x, y, width, height] = [x, y, width, height].map(format_number);
+        writeln!(self.content, "{x} {y} {width} {height} re f")
+    }
+
+    fn set_fill_color(&mut self, color: Color) -> io::Result<()> {
+        writeln!(self.content, "{} rg", pdf_color(color))
+    }
+
+    fn set_stroke_color(&mut self, color: Color) -> io::Result<()> {
+        writeln!(self.content, "{} RG", pdf_color(color))
+    }
+
+    // Get the bottom left corner of the given cell, in PDF coordinates.
+    fn cell_origin(&self, column: usize, row: usize) -> (f32, f32) {
+        (column as f32 * CELL_WIDTH, self.height - (row + 1) as f32 * CELL_HEIGHT)
+    }
+}
+
+/// The properties that need to match for consecutive characters to be printed in a single run.
+#[derive(Clone, Debug, PartialEq)]
+struct TextKey {
+    color: Color,
+    bold: bool,
+    italics: bool,
+    underlined: bool,
+    strikethrough: bool,
+}
+
+impl TextKey {
+    fn new(style: &TextStyle, default_foreground: Color) -> Self {
+        Self {
+            color: style.colors.foreground.unwrap_or(default_foreground),
+            bold: style.is_bold(),
+            italics: style.is_italics(),
+            underlined: style.is_underlined(),
+            strikethrough: style.is_strikethrough(),
+        }
+    }
+}
+
+/// The way a character is drawn into the page.
+#[derive(Clone, Debug, PartialEq)]
+enum Glyph {
+    /// A character in the font's encoding.
+    Text(u8),
+
+    /// A character that isn't supported by the font and is instead drawn by hand.
+    Shape(Shape),
+}
+
+impl From<char> for Glyph {
+    fn from(c: char) -> Self {
+        if c == StyledChar::WIDE_CHAR_PADDING {
+            return Self::Text(b' ');
+        }
+        if let Some(shape) = Shape::from_char(c) {
+            return Self::Shape(shape);
+        }
+        Self::Text(win_ansi_byte(c).unwrap_or(b'?'))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Line {
+    None,
+    Light,
+    Heavy,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Shape {
+    /// Lines that start at the center of the cell and go towards its edges.
+    Lines {
+        up: Line,
+        down: Line,
+        left: Line,
+        right: Line,
+    },
+
+    /// A filled rectangle, expressed as fractions of the cell.
+    Block {
+        left: f32,
+        bottom: f32,
+        right: f32,
+        top: f32,
+    },
+
+    Square {
+        filled: bool,
+    },
+
+    Circle {
+        filled: bool,
+    },
+}
+
+impl Shape {
+    fn from_char(c: char) -> Option<Self> {
+        use Line::{Heavy as H, Light as L, None as N};
+
+        let lines = |up, down, left, right| Some(Self::Lines { up, down, left, right });
+        let block = |left, bottom, right, top| Some(Self::Block { left, bottom, right, top });
+        match c {
+            '─' | '┄' | '┈' | '╌' | '═' => lines(N, N, L, L),
+            '━' | '┅' | '┉' | '╍' => lines(N, N, H, H),
+            '│' | '┆' | '┊' | '╎' | '║' => lines(L, L, N, N),
+            '┃' | '┇' | '┋' | '╏' => lines(H, H, N, N),
+            '┌' | '╭' | '╔' => lines(N, L, N, L),
+            '┐' | '╮' | '╗' => lines(N, L, L, N),
+            '└' | '╰' | '╚' => lines(L, N, N, L),
+            '┘' | '╯' | '╝' => lines(L, N, L, N),
+            '├' | '╠' => lines(L, L, N, L),
+            '┤' | '╣' => lines(L, L, L, N),
+            '┬' | '╦' => lines(N, L, L, L),
+            '┴' | '╩' => lines(L, N, L, L),
+            '┼' | '╬' => lines(L, L, L, L),
+            '┏' => lines(N, H, N, H),
+            '┓' => lines(N, H, H, N),
+            '┗' => lines(H, N, N, H),
+            '┛' => lines(H, N, H, N),
+            '┣' => lines(H, H, N, H),
+            '┫' => lines(H, H, H, N),
+            '┳' => lines(N, H, H, H),
+            '┻' => lines(H, N, H, H),
+            '╋' => lines(H, H, H, H),
+            '╴' => lines(N, N, L, N),
+            '╵' => lines(L, N, N, N),
+            '╶' => lines(N, N, N, L),
+            '╷' => lines(N, L, N, N),
+            '█' | '▓' => block(0.0, 0.0, 1.0, 1.0),
+            '▀' => block(0.0, 0.5, 1.0, 1.0),
+            '▄' => block(0.0, 0.0, 1.0, 0.5),
+            '▌' => block(0.0, 0.0, 0.5, 1.0),
+            '▐' => block(0.5, 0.0, 1.0, 1.0),
+            '▁' => block(0.0, 0.0, 1.0, 0.125),
+            '▂' => block(0.0, 0.0, 1.0, 0.25),
+            '▃' => block(0.0, 0.0, 1.0, 0.375),
+            '▅' => block(0.0, 0.0, 1.0, 0.625),
+            '▆' => block(0.0, 0.0, 1.0, 0.75),
+            '▇' => block(0.0, 0.0, 1.0, 0.875),
+            '▔' => block(0.0, 0.875, 1.0, 1.0),
+            '▏' => block(0.0, 0.0, 0.125, 1.0),
+            '▕' => block(0.875, 0.0, 1.0, 1.0),
+            '▪' | '■' => Some(Self::Square { filled: true }),
+            '▫' | '□' => Some(Self::Square { filled: false }),
+            '●' => Some(Self::Circle { filled: true }),
+            '◦' | '○' => Some(Self::Circle { filled: false }),
+            _ => None,
+        }
+    }
+}
+
+/// A minimal PDF file writer.
+#[derive(Default)]
+struct PdfWriter {
+    objects: Vec<Vec<u8>>,
+}
+
+impl PdfWriter {
+    /// Reserve an object id so it can be referenced before its contents are known.
+    fn reserve(&mut self) -> usize {
+        self.add(Vec::new())
+    }
+
+    fn set(&mut self, id: usize, contents: Vec<u8>) {
+        self.objects[id - 1] = contents;
+    }
+
+    fn add(&mut self, contents: Vec<u8>) -> usize {
+        self.objects.push(contents);
+        self.objects.len()
+    }
+
+    fn add_stream(&mut self, dictionary: &str, data: &[u8]) -> io::Result<usize> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data)?;
+        let data = encoder.finish()?;
+        let mut contents =
+            format!("<< {dictionary} /Filter /FlateDecode /Length {} >>\nstream\n", data.len()).into_bytes();
+        contents.extend(data);
+        contents.extend_from_slice(b"\nendstream");
+        Ok(self.add(contents))
+    }
+
+    fn finish(self, root_id: usize, info_id: usize) -> Vec<u8> {
+        let mut output = b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n".to_vec();
+        let mut offsets = Vec::new();
+        for (index, object) in self.objects.iter().enumerate() {
+            offsets.push(output.len());
+            output.extend(format!("{} 0 obj\n", index + 1).into_bytes());
+            output.extend(object);
+            output.extend_from_slice(b"\nendobj\n");
+        }
+        let xref_offset = output.len();
+        output.extend(format!("xref\n0 {}\n0000000000 65535 f \n", offsets.len() + 1).into_bytes());
+        for offset in offsets {
+            output.extend(format!("{offset:010} 00000 n \n").into_bytes());
+        }
+        let trailer = format!(
+            "trailer\n<< /Size {} /Root {root_id} 0 R /Info {info_id} 0 R >>\nstartxref\n{xref_offset}\n%%EOF\n",
+            self.objects.len() + 1
+        );
+        output.extend(trailer.into_bytes());
+        output
+    }
+}
+
+/// Split a slice into groups of consecutive elements that share the same key.
+fn group_by<T, K, F>(items: &[T], key: F) -> Vec<(K, &[T])>
+where
+    K: PartialEq,
+    F: Fn(&T) -> K,
+{
+    let mut groups = Vec::new();
+    let mut start = 0;
+    while start < items.len() {
+        let current = key(&items[start]);
+        let length = items[start..].iter().take_while(|item| key(item) == current).count();
+        groups.push((current, &items[start..start + length]));
+        start += length;
+    }
+    groups
+}
+
+/// Get the byte that represents a character in the `WinAnsiEncoding` encoding.
+fn win_ansi_byte(c: char) -> Option<u8> {
+    let byte = match c {
+        ' '..='~' => c as u8,
+        '\u{a0}'..='\u{ff}' => c as u32 as u8,
+        '€' => 0x80,
+        '‚' => 0x82,
+        'ƒ' => 0x83,
+        '„' => 0x84,
+        '…' => 0x85,
+        '†' => 0x86,
+        '‡' => 0x87,
+        'ˆ' => 0x88,
+        '‰' => 0x89,
+        'Š' => 0x8a,
+        '‹' => 0x8b,
+        'Œ' => 0x8c,
+        'Ž' => 0x8e,
+        '‘' => 0x91,
+        '’' => 0x92,
+        '“' => 0x93,
+        '”' => 0x94,
+        '•' => 0x95,
+        '–' => 0x96,
+        '—' => 0x97,
+        '˜' => 0x98,
+        '™' => 0x99,
+        'š' => 0x9a,
+        '›' => 0x9b,
+        'œ' => 0x9c,
+        'ž' => 0x9e,
+        'Ÿ' => 0x9f,
+        _ => return None,
+    };
+    Some(byte)
+}
+
+fn pdf_color(color: Color) -> String {
+    let (r, g, b) = color.to_palette_rgb();
+    [r, g, b].map(|c| format_number(c as f32 / 255.0)).join(" ")
+}
+
+fn pdf_string(text: &str) -> String {
+    let mut output = String::from("(");
+    for c in text.chars() {
+        match win_ansi_byte(c) {
+            Some(b'(') => output.push_str("\\("),
+            Some(b')') => output.push_str("\\)"),
+            Some(b'\\') => output.push_str("\\\\"),
+            Some(byte) if byte.is_ascii() => output.push(byte as char),
+            Some(byte) => output.push_str(&format!("\\{byte:03o}")),
+            None => output.push('?'),
+        };
+    }
+    output.push(')');
+    output
+}
+
+fn format_number(value: f32) -> String {
+    let value = format!("{value:.3}");
+    value.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        markdown::text::WeightedLine,
+        presentation::RenderOperation,
+        render::{
+            engine::{RenderEngine, RenderEngineOptions},
+            properties::WindowSize,
+            virt::VirtualTerminal,
+        },
+        theme::Alignment,
+    };
+    use rstest::rstest;
+
+    fn render(operations: &[RenderOperation]) -> TerminalGrid {
+        let dimensions = WindowSize { rows: 2, columns: 6, width: 60, height: 40 };
+        let mut terminal = VirtualTerminal::new(&dimensions);
+        let engine = RenderEngine::new(&mut terminal, dimensions, RenderEngineOptions::default());
+        engine.render(operations.iter()).expect("render failed");
+        terminal.into_contents()
+    }
+
+    #[rstest]
+    #[case::ascii('a', Glyph::Text(b'a'))]
+    #[case::latin1('ñ', Glyph::Text(0xf1))]
+    #[case::bullet('•', Glyph::Text(0x95))]
+    #[case::unsupported('漢', Glyph::Text(b'?'))]
+    #[case::box_drawing('─', Glyph::Shape(Shape::Lines { up: Line::None, down: Line::None, left: Line::Light, right: Line::Light }))]
+    #[case::block('▀', Glyph::Shape(Shape::Block { left: 0.0, bottom: 0.5, right: 1.0, top: 1.0 }))]
+    fn glyphs(#[case] input: char, #[case] expected: Glyph) {
+        assert_eq!(Glyph::from(input), expected);
+    }
+
+    #[rstest]
+    #[case::integer(12.0, "12")]
+    #[case::decimal(1.5, "1.5")]
+    #[case::rounded(0.33333, "0.333")]
+    #[case::zero(0.0, "0")]
+    fn number_formatting(#[case] input: f32, #[case] expected: &str) {
+        assert_eq!(format_number(input), expected);
+    }
+
+    #[test]
+    fn text_content() {
+        let grid = render(&[RenderOperation::RenderText {
+            line: WeightedLine::from("(hi)"),
+            alignment: Alignment::default(),
+        }]);
+        let mut canvas = PageCanvas { content: Vec::new(), height: 24.0 };
+        canvas.draw_row_text(0, &grid.rows[0], Color::White).expect("drawing failed");
+        let content = String::from_utf8(canvas.content).expect("not utf8");
+        assert_eq!(content, "1 1 1 rg\nBT /F0 10 Tf 0 15.36 Td (\\(hi\\)  ) Tj ET\n");
+    }
+
+    #[test]
+    fn document_structure() {
+        let colors = Colors { background: Some(Color::Black), foreground: Some(Color::White) };
+        let grid = render(&[RenderOperation::SetColors(colors), RenderOperation::ClearScreen]);
+        let mut presentation = PdfPresentation::new("deck", colors, 6, 2);
+        presentation.add_slide(&grid).expect("adding slide failed");
+        presentation.add_slide(&grid).expect("adding slide failed");
+        let output = presentation.generate();
+
+        let text = String::from_utf8_lossy(&output);
+        assert!(text.starts_with("%PDF-1.4"));
+        assert!(text.contains("/Type /Pages /Kids [8 0 R 10 0 R ] /Count 2"));
+        assert!(text.contains("/MediaBox [0 0 36 24]"));
+
+        // Make sure every entry in the cross reference table points to its object.
+        let xref = text.split("xref\n").nth(1).expect("no xref");
+        for (index, line) in xref.lines().skip(2).take_while(|line| line.ends_with(" n ")).enumerate() {
+            let offset: usize = line[0..10].parse().expect("invalid offset");
+            let expected = format!("{} 0 obj", index + 1);
+            assert_eq!(&output[offset..offset + expected.len()], expected.as_bytes());
+        }
+    }
+}
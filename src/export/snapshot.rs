@@ -0,0 +1,265 @@
+use crate::{
+    render::{
+        properties::WindowSize,
+        virt::{PrintedImage, StyledChar, TerminalGrid},
+    },
+    style::{Color, TextStyle},
+};
+use clap::ValueEnum;
+use serde::Serialize;
+use std::{fmt, str::FromStr};
+
+/// The format used when printing a slide snapshot.
+#[derive(Clone, Debug, Default, ValueEnum)]
+pub enum SnapshotFormat {
+    /// Print the slide using ANSI escape codes.
+    #[default]
+    Ansi,
+
+    /// Print every row in the slide as a list of styled spans in JSON format.
+    Json,
+}
+
+/// The size of the terminal a slide snapshot is rendered into, e.g. `120x40`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SnapshotSize {
+    columns: u16,
+    rows: u16,
+}
+
+impl SnapshotSize {
+    pub(crate) fn window_size(&self, font_size: u8) -> WindowSize {
+        let font_size = font_size as u16;
+        WindowSize {
+            rows: self.rows,
+            columns: self.columns,
+            width: self.columns.saturating_mul(font_size),
+            height: self.rows.saturating_mul(font_size * 2),
+        }
+    }
+}
+
+impl FromStr for SnapshotSize {
+    type Err = ParseSnapshotSizeError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let (columns, rows) = input.split_once('x').ok_or(ParseSnapshotSizeError)?;
+        let columns = columns.parse().map_err(|_| ParseSnapshotSizeError)?;
+        let rows = rows.parse().map_err(|_| ParseSnapshotSizeError)?;
+        if columns == 0 || rows == 0 {
+            return Err(ParseSnapshotSizeError);
+        }
+        Ok(Self { columns, rows })
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("size must be in <columns>x<rows> format, e.g. 120x40")]
+pub struct ParseSnapshotSizeError;
+
+/// A slide that was rendered into a virtual terminal.
+#[derive(Serialize)]
+pub(crate) struct SlideSnapshot {
+    slide: usize,
+    columns: u16,
+    rows: u16,
+    background: Option<Color>,
+    lines: Vec<Vec<Span>>,
+    images: Vec<ImagePosition>,
+}
+
+impl SlideSnapshot {
+    pub(crate) fn new(slide: usize, grid: &TerminalGrid) -> Self {
+        let columns = grid.rows.first().map(|row| row.len()).unwrap_or_default() as u16;
+        let lines = grid.rows.iter().map(|row| Span::build_line(row)).collect();
+        let images = grid.images.iter().map(ImagePosition::from).collect();
+        Self { slide, columns, rows: grid.rows.len() as u16, background: grid.background_color, lines, images }
+    }
+
+    pub(crate) fn format(&self, format: &SnapshotFormat) -> String {
+        match format {
+            SnapshotFormat::Ansi => self.to_string(),
+            SnapshotFormat::Json => serde_json::to_string_pretty(self).expect("serialization failed"),
+        }
+    }
+}
+
+impl fmt::Display for SlideSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for line in &self.lines {
+            for span in line {
+                write!(f, "{}", span.style().apply(span.text.as_str()))?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// A sequence of characters in a row that share the same style.
+#[derive(Debug, PartialEq, Serialize)]
+struct Span {
+    text: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    foreground: Option<Color>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    background: Option<Color>,
+
+    #[serde(skip_serializing_if = "is_false")]
+    bold: bool,
+
+    #[serde(skip_serializing_if = "is_false")]
+    italics: bool,
+
+    #[serde(skip_serializing_if = "is_false")]
+    underlined: bool,
+
+    #[serde(skip_serializing_if = "is_false")]
+    strikethrough: bool,
+}
+
+impl Span {
+    fn new(text: String, style: &TextStyle) -> Self {
+        Self {
+            text,
+            foreground: style.colors.foreground,
+            background: style.colors.background,
+            bold: style.is_bold(),
+            italics: style.is_italics(),
+            underlined: style.is_underlined(),
+            strikethrough: style.is_strikethrough(),
+        }
+    }
+
+    fn build_line(row: &[StyledChar]) -> Vec<Span> {
+        // Merge consecutive characters that use the same style into a single span.
+        let mut spans: Vec<(TextStyle, String)> = Vec::new();
+        for cell in row.iter().filter(|cell| cell.character != StyledChar::WIDE_CHAR_PADDING) {
+            match spans.last_mut() {
+                Some((style, text)) if *style == cell.style => text.push(cell.character),
+                _ => spans.push((cell.style, cell.character.to_string())),
+            };
+        }
+        spans.into_iter().map(|(style, text)| Span::new(text, &style)).collect()
+    }
+
+    fn style(&self) -> TextStyle {
+        let mut style = TextStyle::default();
+        if self.bold {
+            style = style.bold();
+        }
+        if self.italics {
+            style = style.italics();
+        }
+        if self.underlined {
+            style = style.underlined();
+        }
+        if self.strikethrough {
+            style = style.strikethrough();
+        }
+        style.colors.foreground = self.foreground;
+        style.colors.background = self.background;
+        style
+    }
+}
+
+/// The position of an image in the slide, in cells.
+#[derive(Serialize)]
+struct ImagePosition {
+    row: u16,
+    column: u16,
+    rows: u16,
+    columns: u16,
+}
+
+impl From<&PrintedImage> for ImagePosition {
+    fn from(image: &PrintedImage) -> Self {
+        let PrintedImage { row, column, rows, columns, .. } = image;
+        Self { row: *row, column: *column, rows: *rows, columns: *columns }
+    }
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        markdown::{elements::Text, text::WeightedLine},
+        presentation::RenderOperation,
+        render::{
+            engine::{RenderEngine, RenderEngineOptions},
+            virt::VirtualTerminal,
+        },
+        style::Colors,
+        theme::Alignment,
+    };
+    use rstest::rstest;
+
+    fn render(operations: &[RenderOperation]) -> TerminalGrid {
+        let dimensions = WindowSize { rows: 2, columns: 6, width: 60, height: 40 };
+        let mut terminal = VirtualTerminal::new(&dimensions);
+        let engine = RenderEngine::new(&mut terminal, dimensions, RenderEngineOptions::default());
+        engine.render(operations.iter()).expect("render failed");
+        terminal.into_contents()
+    }
+
+    #[rstest]
+    #[case::valid("120x40", Some(SnapshotSize { columns: 120, rows: 40 }))]
+    #[case::no_separator("120", None)]
+    #[case::zero_rows("120x0", None)]
+    #[case::negative("-1x40", None)]
+    #[case::not_a_number("ax40", None)]
+    fn parse_size(#[case] input: &str, #[case] expected: Option<SnapshotSize>) {
+        assert_eq!(input.parse().ok(), expected);
+    }
+
+    #[test]
+    fn spans() {
+        let grid = render(&[RenderOperation::RenderText {
+            line: WeightedLine::from(vec![Text::new("ab", TextStyle::default().bold()), Text::from("漢")]),
+            alignment: Alignment::default(),
+        }]);
+        let snapshot = SlideSnapshot::new(1, &grid);
+        let expected =
+            vec![Span::new("ab".into(), &TextStyle::default().bold()), Span::new("漢  ".into(), &TextStyle::default())];
+        assert_eq!(snapshot.lines[0], expected);
+        assert_eq!(snapshot.columns, 6);
+        assert_eq!(snapshot.rows, 2);
+    }
+
+    #[test]
+    fn json_format() {
+        let colors = Colors { background: Some(Color::Black), foreground: Some(Color::Red) };
+        let grid = render(&[RenderOperation::SetColors(colors), RenderOperation::ClearScreen]);
+        let snapshot = SlideSnapshot::new(3, &grid);
+        let json: serde_json::Value =
+            serde_json::from_str(&snapshot.format(&SnapshotFormat::Json)).expect("invalid json");
+        let expected = serde_json::json!({
+            "slide": 3,
+            "columns": 6,
+            "rows": 2,
+            "background": "black",
+            "lines": [
+                [{ "text": "      ", "background": "black" }],
+                [{ "text": "      ", "background": "black" }],
+            ],
+            "images": [],
+        });
+        assert_eq!(json, expected);
+    }
+
+    #[test]
+    fn ansi_format() {
+        let grid = render(&[RenderOperation::RenderText {
+            line: WeightedLine::from(vec![Text::new("hi", TextStyle::default().bold())]),
+            alignment: Alignment::default(),
+        }]);
+        let output = SlideSnapshot::new(1, &grid).format(&SnapshotFormat::Ansi);
+        assert_eq!(output, "\u{1b}[1mhi\u{1b}[0m    \n      \n");
+    }
+}
@@ -1,2 +1,3 @@
+pub(crate) mod remote;
 pub(crate) mod source;
 pub(crate) mod user;
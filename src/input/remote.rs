@@ -0,0 +1,357 @@
+use super::source::Command;
+use crate::speaker::PresentationStatus;
+use serde::{Deserialize, Serialize};
+use std::{
+    env,
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{Ipv4Addr, SocketAddr, TcpListener},
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{
+        Arc,
+        mpsc::{self, Receiver, Sender},
+    },
+    thread,
+};
+
+/// The environment variable that holds the token clients need to send when using a TCP address.
+const TOKEN_ENV_VAR: &str = "PRESENTERM_REMOTE_CONTROL_TOKEN";
+
+/// The address a remote control listens on.
+///
+/// This can either be a localhost address like `127.0.0.1:8123`, a port number, which will be
+/// bound on `127.0.0.1`, or a path to a unix socket.
+#[derive(Clone, Debug, PartialEq)]
+pub enum RemoteControlAddress {
+    /// A TCP address.
+    Tcp(SocketAddr),
+
+    /// A path to a unix socket.
+    Unix(PathBuf),
+}
+
+impl FromStr for RemoteControlAddress {
+    type Err = ParseRemoteControlAddressError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        if let Ok(port) = input.parse::<u16>() {
+            return Ok(Self::Tcp(SocketAddr::from((Ipv4Addr::LOCALHOST, port))));
+        }
+        let address = match input.strip_prefix("localhost:") {
+            Some(port) => port.parse::<u16>().ok().map(|port| SocketAddr::from((Ipv4Addr::LOCALHOST, port))),
+            None => input.parse::<SocketAddr>().ok(),
+        };
+        match address {
+            // Anyone who can reach this can control the presentation so don't listen on the network.
+            Some(address) if !address.ip().is_loopback() => Err(ParseRemoteControlAddressError),
+            Some(address) => Ok(Self::Tcp(address)),
+            None if input.is_empty() => Err(ParseRemoteControlAddressError),
+            None => Ok(Self::Unix(input.into())),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("remote control address must be a localhost address, a port, or a unix socket path")]
+pub struct ParseRemoteControlAddressError;
+
+/// A socket that lets other processes control a presentation.
+///
+/// Clients send one JSON command per line, e.g. `{"command": "go_to_slide", "slide": 3}`, and get
+/// the status of the presentation back as a JSON line once the command is applied.
+///
+/// Any local process, including web pages running in a browser, can connect to a TCP address so
+/// clients connecting to one must first send a line containing a token that's shared with them
+/// via [TOKEN_ENV_VAR].
+pub(crate) struct RemoteControl {
+    requests: Receiver<Request>,
+    pending_replies: Vec<Sender<String>>,
+    socket_path: Option<PathBuf>,
+}
+
+impl RemoteControl {
+    pub(crate) fn new(address: &RemoteControlAddress) -> io::Result<Self> {
+        let (sender, requests) = mpsc::channel();
+        let socket_path = match address {
+            RemoteControlAddress::Tcp(address) => {
+                let token = env::var(TOKEN_ENV_VAR).ok().filter(|token| !token.is_empty()).ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("{TOKEN_ENV_VAR} must be set to listen for remote control commands on a TCP address"),
+                    )
+                })?;
+                Self::listen_tcp(address, token, sender)?;
+                None
+            }
+            RemoteControlAddress::Unix(path) => {
+                Self::listen_unix(path, sender)?;
+                Some(path.clone())
+            }
+        };
+        Ok(Self { requests, pending_replies: Vec::new(), socket_path })
+    }
+
+    fn listen_tcp(address: &SocketAddr, token: String, sender: Sender<Request>) -> io::Result<()> {
+        let listener = TcpListener::bind(address)?;
+        let token = Some(Arc::from(token));
+        thread::spawn(move || accept(listener.incoming(), sender, token, |stream| stream.try_clone()));
+        Ok(())
+    }
+
+    // The socket is only accessible by the current user so there's no need for a token.
+    #[cfg(unix)]
+    fn listen_unix(path: &Path, sender: Sender<Request>) -> io::Result<()> {
+        let listener = crate::socket::bind_unix_socket(path)?;
+        thread::spawn(move || accept(listener.incoming(), sender, None, |stream| stream.try_clone()));
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    fn listen_unix(_path: &Path, _sender: Sender<Request>) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::Unsupported, "unix sockets are not supported in this platform"))
+    }
+
+    /// Get the next command sent by a client, if any.
+    ///
+    /// Every client that sent a request is waiting for a reply that must be sent via
+    /// [RemoteControl::reply] once the command is applied.
+    pub(crate) fn try_next_command(&mut self) -> Option<Command> {
+        while let Ok(Request { command, reply }) = self.requests.try_recv() {
+            self.pending_replies.push(reply);
+            if let Some(command) = command.into_command() {
+                return Some(command);
+            }
+        }
+        None
+    }
+
+    /// Reply to every client that is waiting for one.
+    pub(crate) fn reply(&mut self, status: Option<&PresentationStatus>) {
+        if self.pending_replies.is_empty() {
+            return;
+        }
+        let reply = match status {
+            Some(status) => serde_json::to_string(status),
+            None => serde_json::to_string(&ErrorReply { error: "no presentation loaded".into() }),
+        }
+        .expect("serialization failed");
+        for sender in self.pending_replies.drain(..) {
+            // The client may have gone away in the meantime, which is fine.
+            let _ = sender.send(reply.clone());
+        }
+    }
+}
+
+impl Drop for RemoteControl {
+    fn drop(&mut self) {
+        if let Some(path) = &self.socket_path {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// A command sent by a remote control client.
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(tag = "command", rename_all = "snake_case", deny_unknown_fields)]
+enum RemoteCommand {
+    Next,
+    NextFast,
+    Previous,
+    PreviousFast,
+    FirstSlide,
+    LastSlide,
+    GoToSlide {
+        slide: u32,
+    },
+    Reload,
+    HardReload,
+    Exit,
+
+    /// Don't do anything, just reply with the status of the presentation.
+    Status,
+}
+
+impl RemoteCommand {
+    fn into_command(self) -> Option<Command> {
+        let command = match self {
+            Self::Next => Command::Next,
+            Self::NextFast => Command::NextFast,
+            Self::Previous => Command::Previous,
+            Self::PreviousFast => Command::PreviousFast,
+            Self::FirstSlide => Command::FirstSlide,
+            Self::LastSlide => Command::LastSlide,
+            Self::GoToSlide { slide } => Command::GoToSlide(slide),
+            Self::Reload => Command::Reload,
+            Self::HardReload => Command::HardReload,
+            Self::Exit => Command::Exit,
+            Self::Status => return None,
+        };
+        Some(command)
+    }
+}
+
+struct Request {
+    command: RemoteCommand,
+    reply: Sender<String>,
+}
+
+#[derive(Serialize)]
+struct ErrorReply {
+    error: String,
+}
+
+fn accept<S, I, F>(incoming: I, sender: Sender<Request>, token: Option<Arc<str>>, try_clone: F)
+where
+    S: Read + Write + Send + 'static,
+    I: Iterator<Item = io::Result<S>>,
+    F: Fn(&S) -> io::Result<S>,
+{
+    for stream in incoming {
+        let Ok(stream) = stream else { continue };
+        let Ok(reader) = try_clone(&stream) else { continue };
+        let sender = sender.clone();
+        let token = token.clone();
+        thread::spawn(move || serve(reader, stream, sender, token.as_deref()));
+    }
+}
+
+fn serve<R: Read, W: Write>(reader: R, mut writer: W, sender: Sender<Request>, token: Option<&str>) {
+    let mut lines = BufReader::new(reader).lines();
+    if let Some(token) = token {
+        let authenticated = matches!(lines.next(), Some(Ok(line)) if line.trim() == token);
+        if !authenticated {
+            let reply =
+                serde_json::to_string(&ErrorReply { error: "invalid token".into() }).expect("serialization failed");
+            let _ = writeln!(writer, "{reply}");
+            return;
+        }
+    }
+    for line in lines {
+        let Ok(line) = line else { return };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let reply = match serde_json::from_str(&line) {
+            Ok(command) => {
+                let (reply, receiver) = mpsc::channel();
+                // If either of these fail then the presentation is gone.
+                if sender.send(Request { command, reply }).is_err() {
+                    return;
+                }
+                let Ok(reply) = receiver.recv() else { return };
+                reply
+            }
+            Err(e) => serde_json::to_string(&ErrorReply { error: format!("invalid command: {e}") })
+                .expect("serialization failed"),
+        };
+        if writeln!(writer, "{reply}").and_then(|_| writer.flush()).is_err() {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+    use std::net::TcpStream;
+
+    #[rstest]
+    #[case::port("8123", RemoteControlAddress::Tcp("127.0.0.1:8123".parse().unwrap()))]
+    #[case::localhost("localhost:8123", RemoteControlAddress::Tcp("127.0.0.1:8123".parse().unwrap()))]
+    #[case::ipv4("127.0.0.1:8123", RemoteControlAddress::Tcp("127.0.0.1:8123".parse().unwrap()))]
+    #[case::ipv6("[::1]:8123", RemoteControlAddress::Tcp("[::1]:8123".parse().unwrap()))]
+    #[case::path("/tmp/presenterm.sock", RemoteControlAddress::Unix("/tmp/presenterm.sock".into()))]
+    fn parse_address(#[case] input: &str, #[case] expected: RemoteControlAddress) {
+        let address: RemoteControlAddress = input.parse().expect("parse failed");
+        assert_eq!(address, expected);
+    }
+
+    #[rstest]
+    #[case::any("0.0.0.0:8123")]
+    #[case::remote("10.0.0.1:8123")]
+    #[case::empty("")]
+    fn invalid_address(#[case] input: &str) {
+        assert!(input.parse::<RemoteControlAddress>().is_err());
+    }
+
+    #[rstest]
+    #[case::next(r#"{"command":"next"}"#, Some(Command::Next))]
+    #[case::previous(r#"{"command":"previous"}"#, Some(Command::Previous))]
+    #[case::go_to_slide(r#"{"command":"go_to_slide","slide":3}"#, Some(Command::GoToSlide(3)))]
+    #[case::reload(r#"{"command":"reload"}"#, Some(Command::Reload))]
+    #[case::exit(r#"{"command":"exit"}"#, Some(Command::Exit))]
+    #[case::status(r#"{"command":"status"}"#, None)]
+    fn parse_command(#[case] input: &str, #[case] expected: Option<Command>) {
+        let command: RemoteCommand = serde_json::from_str(input).expect("parse failed");
+        assert_eq!(command.into_command(), expected);
+    }
+
+    #[rstest]
+    #[case::unknown(r#"{"command":"jump"}"#)]
+    #[case::missing_slide(r#"{"command":"go_to_slide"}"#)]
+    #[case::unknown_field(r#"{"command":"go_to_slide","slide":3,"chunk":1}"#)]
+    fn invalid_command(#[case] input: &str) {
+        assert!(serde_json::from_str::<RemoteCommand>(input).is_err());
+    }
+
+    fn listen_tcp(token: &str) -> (RemoteControl, SocketAddr) {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind failed");
+        let address = listener.local_addr().unwrap();
+        drop(listener);
+        let (sender, requests) = mpsc::channel();
+        RemoteControl::listen_tcp(&address, token.into(), sender).expect("listen failed");
+        let remote = RemoteControl { requests, pending_replies: Vec::new(), socket_path: None };
+        (remote, address)
+    }
+
+    #[test]
+    fn invalid_token() {
+        let (mut remote, address) = listen_tcp("secret");
+        let mut client = TcpStream::connect(address).expect("connect failed");
+        writeln!(client, "POST / HTTP/1.1\n{{\"command\":\"exit\"}}").unwrap();
+
+        let mut reply = String::new();
+        client.read_to_string(&mut reply).unwrap();
+        assert!(reply.contains("invalid token"), "{reply}");
+        assert!(remote.try_next_command().is_none());
+    }
+
+    #[test]
+    fn round_trip() {
+        let (mut remote, address) = listen_tcp("secret");
+
+        let client = TcpStream::connect(address).expect("connect failed");
+        let mut reader = BufReader::new(client.try_clone().unwrap());
+        let mut client = client;
+        writeln!(client, "secret\n{{\"command\":\"bogus\"}}\n{{\"command\":\"go_to_slide\",\"slide\":2}}").unwrap();
+
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        assert!(line.contains("invalid command"), "{line}");
+
+        let command = loop {
+            if let Some(command) = remote.try_next_command() {
+                break command;
+            }
+            thread::yield_now();
+        };
+        assert_eq!(command, Command::GoToSlide(2));
+
+        let status = PresentationStatus {
+            slide: 2,
+            total_slides: 3,
+            chunk: 1,
+            total_chunks: 1,
+            title: "Intro".into(),
+            next_slide_title: None,
+            speaker_notes: vec![],
+            started_at: 0,
+        };
+        remote.reply(Some(&status));
+        line.clear();
+        reader.read_line(&mut line).unwrap();
+        let reply: PresentationStatus = serde_json::from_str(&line).expect("invalid reply");
+        assert_eq!(reply.slide, 2);
+    }
+}
@@ -1,5 +1,8 @@
-use super::user::{CommandKeyBindings, KeyBindingsValidationError, UserInput};
-use crate::custom::KeyBindingsConfig;
+use super::{
+    remote::{RemoteControl, RemoteControlAddress},
+    user::{CommandKeyBindings, InputMode, KeyBindingsValidationError, UserInput},
+};
+use crate::{custom::KeyBindingsConfig, presentation::CursorDirection, speaker::PresentationStatus};
 use serde::Deserialize;
 use std::{io, time::Duration};
 use strum::EnumDiscriminants;
@@ -10,24 +13,53 @@ use strum::EnumDiscriminants;
 /// happens.
 pub struct CommandSource {
     user_input: UserInput,
+    remote: Option<RemoteControl>,
 }
 
 impl CommandSource {
     /// Create a new command source over the given presentation path.
     pub fn new(config: KeyBindingsConfig) -> Result<Self, KeyBindingsValidationError> {
         let bindings = CommandKeyBindings::try_from(config)?;
-        Ok(Self { user_input: UserInput::new(bindings) })
+        Ok(Self { user_input: UserInput::new(bindings), remote: None })
+    }
+
+    /// Also accept commands from a remote control listening on the given address.
+    pub fn listen_remote(&mut self, address: &RemoteControlAddress) -> io::Result<()> {
+        self.remote = Some(RemoteControl::new(address)?);
+        Ok(())
     }
 
     /// Try to get the next command.
     ///
     /// This attempts to get a command and returns `Ok(None)` on timeout.
     pub(crate) fn try_next_command(&mut self) -> io::Result<Option<Command>> {
-        match self.user_input.poll_next_command(Duration::from_millis(250))? {
+        // Poll more often when there's a remote control so its commands are applied quickly.
+        let timeout = match &mut self.remote {
+            Some(remote) => {
+                if let Some(command) = remote.try_next_command() {
+                    return Ok(Some(command));
+                }
+                Duration::from_millis(50)
+            }
+            None => Duration::from_millis(250),
+        };
+        match self.user_input.poll_next_command(timeout)? {
             Some(command) => Ok(Some(command)),
             None => Ok(None),
         }
     }
+
+    /// Set the mode used to interpret keyboard input.
+    pub(crate) fn set_input_mode(&mut self, mode: InputMode) {
+        self.user_input.set_mode(mode);
+    }
+
+    /// Let any remote clients waiting on a command know the status of the presentation.
+    pub(crate) fn reply(&mut self, status: Option<&PresentationStatus>) {
+        if let Some(remote) = &mut self.remote {
+            remote.reply(status);
+        }
+    }
 }
 
 /// A command.
@@ -63,6 +95,9 @@ pub(crate) enum Command {
     /// Render any async render operations in the current slide.
     RenderAsyncOperations,
 
+    /// Cancel any async render operations running in the current slide.
+    CancelAsyncOperations,
+
     /// Exit the presentation.
     Exit,
 
@@ -85,4 +120,54 @@ pub(crate) enum Command {
 
     /// Hide the currently open modal, if any.
     CloseModal,
+
+    /// Interact with the slide index modal.
+    SlideIndex(SlideIndexAction),
+
+    /// Start searching the presentation.
+    StartSearch,
+
+    /// Scroll the current slide up.
+    ScrollUp,
+
+    /// Scroll the current slide down.
+    ScrollDown,
+
+    /// Interact with the ongoing search.
+    Search(SearchAction),
+}
+
+/// An action on the slide index modal.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum SlideIndexAction {
+    /// Move the cursor.
+    MoveCursor(CursorDirection),
+
+    /// Jump to the slide the cursor points to.
+    Select,
+
+    /// Add a character to the filter.
+    Type(char),
+
+    /// Remove the last character in the filter.
+    Erase,
+}
+
+/// An action on the ongoing search.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum SearchAction {
+    /// Add a character to the query.
+    Type(char),
+
+    /// Remove the last character in the query.
+    Erase,
+
+    /// Finish typing the query and jump to the first match.
+    Confirm,
+
+    /// Jump to the next match.
+    NextMatch,
+
+    /// Jump to the previous match.
+    PreviousMatch,
 }
@@ -1,6 +1,6 @@
-use super::source::{Command, CommandDiscriminants};
-use crate::custom::KeyBindingsConfig;
-use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, poll, read};
+use super::source::{Command, CommandDiscriminants, SearchAction, SlideIndexAction};
+use crate::{custom::KeyBindingsConfig, presentation::CursorDirection};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseEventKind, poll, read};
 use schemars::JsonSchema;
 use serde_with::DeserializeFromStr;
 use std::{fmt, io, iter, mem, str::FromStr, time::Duration};
@@ -9,11 +9,20 @@ use std::{fmt, io, iter, mem, str::FromStr, time::Duration};
 pub struct UserInput {
     bindings: CommandKeyBindings,
     events: Vec<KeyEvent>,
+    mode: InputMode,
 }
 
 impl UserInput {
     pub fn new(bindings: CommandKeyBindings) -> Self {
-        Self { bindings, events: Vec::new() }
+        Self { bindings, events: Vec::new(), mode: InputMode::Presentation }
+    }
+
+    /// Set the mode used to interpret key events.
+    pub(crate) fn set_mode(&mut self, mode: InputMode) {
+        if self.mode != mode {
+            self.mode = mode;
+            self.events.clear();
+        }
     }
 
     /// Polls for the next input command coming from the keyboard.
@@ -27,11 +36,20 @@ impl UserInput {
         let (command, events) = match read()? {
             // Ignore release events
             Event::Key(event) if event.kind == KeyEventKind::Release => (None, events),
-            Event::Key(event) => {
-                events.push(event);
-                self.match_events(events)
-            }
+            Event::Key(event) => match self.mode.action(&event, &events) {
+                Some(command) => (Some(command), Vec::new()),
+                None => {
+                    events.push(event);
+                    self.match_events(events)
+                }
+            },
             Event::Resize(..) => (Some(Command::Redraw), events),
+            Event::Mouse(event) => match event.kind {
+                MouseEventKind::ScrollUp => (Some(Command::ScrollUp), events),
+                MouseEventKind::ScrollDown => (Some(Command::ScrollDown), events),
+                // Don't let mouse movement get in the way of a key binding being typed.
+                _ => (None, events),
+            },
             _ => (None, vec![]),
         };
         self.events = events;
@@ -47,6 +65,54 @@ impl UserInput {
     }
 }
 
+/// The way key events are interpreted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum InputMode {
+    /// Key events are matched against the key bindings.
+    Presentation,
+
+    /// Key events navigate and filter the slide index, falling back to the key bindings.
+    SlideIndex,
+
+    /// Key events are used to type in a search query, falling back to the key bindings.
+    SearchQuery,
+
+    /// Key events jump between search matches, falling back to the key bindings.
+    SearchResults,
+}
+
+impl InputMode {
+    fn action(&self, event: &KeyEvent, buffered: &[KeyEvent]) -> Option<Command> {
+        // Don't get in the way of a key binding that's halfway through being typed.
+        if !buffered.is_empty() {
+            return None;
+        }
+        let typed = match event.code {
+            KeyCode::Char(c) if !event.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) => Some(c),
+            _ => None,
+        };
+        let command = match (self, event.code, typed) {
+            (Self::Presentation, _, _) => return None,
+            (Self::SlideIndex, KeyCode::Up, _) => {
+                Command::SlideIndex(SlideIndexAction::MoveCursor(CursorDirection::Up))
+            }
+            (Self::SlideIndex, KeyCode::Down, _) => {
+                Command::SlideIndex(SlideIndexAction::MoveCursor(CursorDirection::Down))
+            }
+            (Self::SlideIndex, KeyCode::Enter, _) => Command::SlideIndex(SlideIndexAction::Select),
+            (Self::SlideIndex, KeyCode::Backspace, _) => Command::SlideIndex(SlideIndexAction::Erase),
+            (Self::SlideIndex, _, Some(c)) => Command::SlideIndex(SlideIndexAction::Type(c)),
+            (Self::SearchQuery, KeyCode::Enter, _) => Command::Search(SearchAction::Confirm),
+            (Self::SearchQuery, KeyCode::Backspace, _) => Command::Search(SearchAction::Erase),
+            (Self::SearchQuery, _, Some(c)) => Command::Search(SearchAction::Type(c)),
+            (Self::SearchResults, _, Some('n')) => Command::Search(SearchAction::NextMatch),
+            (Self::SearchResults, _, Some('N')) => Command::Search(SearchAction::PreviousMatch),
+            _ => return None,
+        };
+        Some(command)
+    }
+}
+
 enum InputAction {
     Buffer,
     Reset,
@@ -89,6 +155,7 @@ impl CommandKeyBindings {
                 }
             }
             RenderAsyncOperations => Command::RenderAsyncOperations,
+            CancelAsyncOperations => Command::CancelAsyncOperations,
             Exit => Command::Exit,
             Suspend => Command::Suspend,
             Reload => Command::Reload,
@@ -96,6 +163,11 @@ impl CommandKeyBindings {
             ToggleSlideIndex => Command::ToggleSlideIndex,
             ToggleKeyBindingsConfig => Command::ToggleKeyBindingsConfig,
             CloseModal => Command::CloseModal,
+            StartSearch => Command::StartSearch,
+            ScrollUp => Command::ScrollUp,
+            ScrollDown => Command::ScrollDown,
+            // These are generated by the input mode rather than bound to keys.
+            SlideIndex | Search => return InputAction::Reset,
         };
         InputAction::Emit(command)
     }
@@ -139,7 +211,11 @@ impl TryFrom<KeyBindingsConfig> for CommandKeyBindings {
             .chain(zip(CommandDiscriminants::ToggleSlideIndex, config.toggle_slide_index))
             .chain(zip(CommandDiscriminants::ToggleKeyBindingsConfig, config.toggle_bindings))
             .chain(zip(CommandDiscriminants::RenderAsyncOperations, config.execute_code))
+            .chain(zip(CommandDiscriminants::CancelAsyncOperations, config.cancel_execution))
             .chain(zip(CommandDiscriminants::CloseModal, config.close_modal))
+            .chain(zip(CommandDiscriminants::StartSearch, config.search))
+            .chain(zip(CommandDiscriminants::ScrollUp, config.scroll_up))
+            .chain(zip(CommandDiscriminants::ScrollDown, config.scroll_down))
             .collect();
         Self::validate_conflicts(bindings.iter().map(|binding| &binding.0))?;
         Ok(Self { bindings })
@@ -568,6 +644,32 @@ mod test {
         assert!(result.is_ok(), "got error: {result:?}");
     }
 
+    #[rstest]
+    #[case::up(KeyCode::Up.into_event(), Some(SlideIndexAction::MoveCursor(CursorDirection::Up)))]
+    #[case::down(KeyCode::Down.into_event(), Some(SlideIndexAction::MoveCursor(CursorDirection::Down)))]
+    #[case::enter(KeyCode::Enter.into_event(), Some(SlideIndexAction::Select))]
+    #[case::backspace(KeyCode::Backspace.into_event(), Some(SlideIndexAction::Erase))]
+    #[case::char('q'.into_event(), Some(SlideIndexAction::Type('q')))]
+    #[case::control('p'.into_event().with_control(), None)]
+    #[case::esc(KeyCode::Esc.into_event(), None)]
+    fn slide_index_mode(#[case] event: KeyEvent, #[case] expected: Option<SlideIndexAction>) {
+        let command = InputMode::SlideIndex.action(&event, &[]);
+        assert_eq!(command, expected.map(Command::SlideIndex));
+        assert_eq!(InputMode::Presentation.action(&event, &[]), None);
+    }
+
+    #[rstest]
+    #[case::query_char(InputMode::SearchQuery, 'n'.into_event(), Some(SearchAction::Type('n')))]
+    #[case::query_enter(InputMode::SearchQuery, KeyCode::Enter.into_event(), Some(SearchAction::Confirm))]
+    #[case::query_backspace(InputMode::SearchQuery, KeyCode::Backspace.into_event(), Some(SearchAction::Erase))]
+    #[case::query_esc(InputMode::SearchQuery, KeyCode::Esc.into_event(), None)]
+    #[case::results_next(InputMode::SearchResults, 'n'.into_event(), Some(SearchAction::NextMatch))]
+    #[case::results_previous(InputMode::SearchResults, 'N'.into_event(), Some(SearchAction::PreviousMatch))]
+    #[case::results_other(InputMode::SearchResults, 'j'.into_event(), None)]
+    fn search_mode(#[case] mode: InputMode, #[case] event: KeyEvent, #[case] expected: Option<SearchAction>) {
+        assert_eq!(mode.action(&event, &[]), expected.map(Command::Search));
+    }
+
     #[rstest]
     #[case("<number>G")]
     #[case("<PageUp>potato")]
@@ -16,6 +16,9 @@ pub(crate) mod presenter;
 pub(crate) mod processing;
 pub(crate) mod render;
 pub(crate) mod resource;
+pub(crate) mod search;
+pub(crate) mod socket;
+pub(crate) mod speaker;
 pub(crate) mod style;
 pub(crate) mod theme;
 pub(crate) mod third_party;
@@ -25,14 +28,22 @@ pub use crate::{
     custom::{Config, ImageProtocol, ValidateOverflows},
     demo::ThemesDemo,
     execute::SnippetExecutor,
-    export::{ExportError, Exporter},
-    input::source::CommandSource,
+    export::{ExportError, Exporter, SnapshotFormat, SnapshotSize},
+    input::{remote::RemoteControlAddress, source::CommandSource},
     markdown::parse::MarkdownParser,
     media::{graphics::GraphicsMode, printer::ImagePrinter, register::ImageRegistry},
     presenter::{PresentMode, Presenter, PresenterOptions},
-    processing::builder::{PresentationBuilderOptions, Themes},
-    render::highlighting::{CodeHighlighter, HighlightThemeSet},
+    processing::{
+        builder::{PresentationBuilderOptions, Themes},
+        lint::LintReport,
+        snippets::SnippetReport,
+    },
+    render::{
+        highlighting::{CodeHighlighter, HighlightThemeSet},
+        validate::OverflowReport,
+    },
     resource::Resources,
+    speaker::view::PresenterView,
     theme::{LoadThemeError, PresentationTheme, PresentationThemeSet},
     third_party::{ThirdPartyConfigs, ThirdPartyRender},
 };
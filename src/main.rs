@@ -4,8 +4,8 @@ use directories::ProjectDirs;
 use presenterm::{
     CommandSource, Config, Exporter, GraphicsMode, HighlightThemeSet, ImagePrinter, ImageProtocol, ImageRegistry,
     MarkdownParser, PresentMode, PresentationBuilderOptions, PresentationTheme, PresentationThemeSet, Presenter,
-    PresenterOptions, Resources, SnippetExecutor, Themes, ThemesDemo, ThirdPartyConfigs, ThirdPartyRender,
-    ValidateOverflows,
+    PresenterOptions, PresenterView, RemoteControlAddress, Resources, SnapshotFormat, SnapshotSize, SnippetExecutor,
+    Themes, ThemesDemo, ThirdPartyConfigs, ThirdPartyRender, ValidateOverflows,
 };
 use std::{
     env::{self, current_dir},
@@ -30,17 +30,51 @@ struct Cli {
     #[clap(short, long)]
     export_pdf: bool,
 
-    /// Generate the PDF metadata without generating the PDF itself.
-    #[clap(long, hide = true)]
-    generate_pdf_metadata: bool,
+    /// Export the presentation as a single, self contained, HTML file rather than displaying it.
+    #[clap(long)]
+    export_html: bool,
+
+    /// Render a single slide, with all of its pauses shown, and print it rather than displaying the presentation.
+    #[clap(long, value_name = "SLIDE")]
+    render_slide: Option<usize>,
+
+    /// The size of the terminal to render the slide into, e.g. `120x40`.
+    #[clap(long, requires = "render_slide")]
+    size: Option<SnapshotSize>,
+
+    /// The format to print the rendered slide in.
+    #[clap(long, requires = "render_slide", default_value = "ansi")]
+    render_format: SnapshotFormat,
+
+    /// Check that every slide fits on the screen using each of the given sizes, e.g. `80x24,120x40`.
+    ///
+    /// Every overflow is reported and the process exits with an error if there's any.
+    #[clap(long, value_name = "SIZES", value_delimiter = ',')]
+    check_overflows: Option<Vec<SnapshotSize>>,
+
+    /// Look for problems in the presentation, like missing images or invalid commands, and report them.
+    #[clap(long)]
+    lint: bool,
+
+    /// Run every executable snippet in the presentation and report whether each of them succeeded.
+    ///
+    /// A snippet followed by an `expected-output` code block fails if its output doesn't match it.
+    #[clap(long)]
+    run_snippets: bool,
 
     /// Generate a JSON schema for the configuration file.
     #[clap(long)]
     generate_config_file_schema: bool,
 
-    /// Run in export mode.
-    #[clap(long, hide = true)]
-    enable_export_mode: bool,
+    /// Display the speaker notes of the presentation running in another terminal.
+    #[clap(long)]
+    presenter_view: bool,
+
+    /// Accept commands from a remote control on the given localhost address, port, or unix socket path.
+    ///
+    /// Listening on a localhost address or port requires setting the PRESENTERM_REMOTE_CONTROL_TOKEN environment variable.
+    #[clap(long, value_name = "ADDRESS")]
+    remote_control: Option<RemoteControlAddress>,
 
     /// Use presentation mode.
     #[clap(short, long, default_value_t = false)]
@@ -79,6 +113,18 @@ struct Cli {
     config_file: Option<String>,
 }
 
+impl Cli {
+    /// Whether the presentation is processed without being displayed.
+    fn is_headless(&self) -> bool {
+        self.export_pdf
+            || self.export_html
+            || self.render_slide.is_some()
+            || self.check_overflows.is_some()
+            || self.lint
+            || self.run_snippets
+    }
+}
+
 fn create_splash() -> String {
     let crate_version = env!("CARGO_PKG_VERSION");
 
@@ -141,6 +187,7 @@ fn display_acknowledgements() {
 fn make_builder_options(config: &Config, mode: &PresentMode, force_default_theme: bool) -> PresentationBuilderOptions {
     PresentationBuilderOptions {
         allow_mutations: !matches!(mode, PresentMode::Export),
+        mode: *mode,
         implicit_slide_ends: config.options.implicit_slide_ends.unwrap_or_default(),
         command_prefix: config.options.command_prefix.clone().unwrap_or_default(),
         image_attribute_prefix: config.options.image_attributes_prefix.clone().unwrap_or_else(|| "image:".to_string()),
@@ -151,6 +198,7 @@ fn make_builder_options(config: &Config, mode: &PresentMode, force_default_theme
         strict_front_matter_parsing: config.options.strict_front_matter_parsing.unwrap_or(true),
         enable_snippet_execution: config.snippet.exec.enable,
         enable_snippet_execution_replace: config.snippet.exec_replace.enable,
+        math: config.options.math.unwrap_or_default(),
     }
 }
 
@@ -166,7 +214,7 @@ fn load_default_theme(config: &Config, themes: &Themes, cli: &Cli) -> Presentati
 }
 
 fn select_graphics_mode(cli: &Cli, config: &Config) -> GraphicsMode {
-    if cli.enable_export_mode || cli.export_pdf || cli.generate_pdf_metadata {
+    if cli.is_headless() {
         GraphicsMode::AsciiBlocks
     } else {
         let protocol = cli.image_protocol.as_ref().unwrap_or(&config.defaults.image_protocol);
@@ -219,14 +267,22 @@ fn run(mut cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
         load_customizations(cli.config_file.clone().map(PathBuf::from), &resources_path)?;
 
     let default_theme = load_default_theme(&config, &themes, &cli);
+    if cli.presenter_view {
+        let bindings = config.bindings.try_into()?;
+        let font_size = config.defaults.terminal_font_size;
+        let view = PresenterView::new(&path, &default_theme, bindings, io::stdout(), font_size)?;
+        view.run()?;
+        return Ok(());
+    }
     let force_default_theme = cli.theme.is_some();
-    let mode = match (cli.present, cli.enable_export_mode) {
+    let export = cli.is_headless();
+    let mode = match (cli.present, export) {
         (true, _) => PresentMode::Presentation,
         (false, true) => PresentMode::Export,
         (false, false) => PresentMode::Development,
     };
     let arena = Arena::new();
-    let parser = MarkdownParser::new(&arena);
+    let parser = MarkdownParser::new(&arena).with_math(config.options.math.unwrap_or_default());
 
     let validate_overflows = overflow_validation(&mode, &config.defaults.validate_overflows) || cli.validate_overflows;
     let mut options = make_builder_options(&config, &mode, force_default_theme);
@@ -247,30 +303,40 @@ fn run(mut cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
     };
     let third_party = ThirdPartyRender::new(third_party_config, registry, &resources_path);
     let code_executor = Rc::new(code_executor);
-    if cli.export_pdf || cli.generate_pdf_metadata {
+    if cli.is_headless() {
         let mut exporter =
             Exporter::new(parser, &default_theme, resources, third_party, code_executor, themes, options);
-        let mut args = Vec::new();
-        if let Some(theme) = cli.theme.as_ref() {
-            args.extend(["--theme", theme]);
-        }
-        if let Some(path) = cli.config_file.as_ref() {
-            args.extend(["--config-file", path]);
-        }
-        if cli.enable_snippet_execution {
-            args.push("-x");
-        }
-        if cli.enable_snippet_execution_replace {
-            args.push("-X");
-        }
         if cli.export_pdf {
-            exporter.export_pdf(&path, &args)?;
+            exporter.export_pdf(&path)?;
+        } else if cli.export_html {
+            exporter.export_html(&path)?;
+        } else if let Some(slide) = cli.render_slide {
+            let output = exporter.render_slide(&path, slide, cli.size.as_ref(), &cli.render_format)?;
+            print!("{output}");
+        } else if let Some(sizes) = &cli.check_overflows {
+            let report = exporter.check_overflows(&path, sizes)?;
+            print!("{report}");
+            if !report.is_empty() {
+                return Err("presentation overflows the screen".into());
+            }
+        } else if cli.lint {
+            let report = exporter.lint(&path)?;
+            print!("{report}");
+            if !report.is_empty() {
+                return Err("presentation has problems".into());
+            }
         } else {
-            let meta = exporter.generate_metadata(&path)?;
-            println!("{}", serde_json::to_string_pretty(&meta)?);
+            let report = exporter.run_snippets(&path)?;
+            print!("{report}");
+            if !report.is_success() {
+                return Err("some snippets failed".into());
+            }
         }
     } else {
-        let commands = CommandSource::new(config.bindings.clone())?;
+        let mut commands = CommandSource::new(config.bindings.clone())?;
+        if let Some(address) = &cli.remote_control {
+            commands.listen_remote(address)?;
+        }
         options.print_modal_background = matches!(graphics_mode, GraphicsMode::Kitty { .. });
 
         let options = PresenterOptions {
@@ -279,6 +345,7 @@ fn run(mut cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
             font_size_fallback: config.defaults.terminal_font_size,
             bindings: config.bindings,
             validate_overflows,
+            mouse_scroll: config.defaults.mouse_scroll,
         };
         let presenter = Presenter::new(
             &default_theme,
@@ -1,5 +1,5 @@
 use crate::style::TextStyle;
-use std::{fmt, iter, path::PathBuf, str::FromStr};
+use std::{fmt, path::PathBuf, str::FromStr};
 use unicode_width::UnicodeWidthStr;
 
 /// A markdown element.
@@ -51,6 +51,20 @@ pub(crate) enum MarkdownElement {
 
     /// A block quote containing a list of lines.
     BlockQuote(Vec<Line>),
+
+    /// The numbers of the footnotes referenced by the element that precedes this one.
+    FootnoteReferences(Vec<u32>),
+
+    /// A footnote definition.
+    ///
+    /// These are always placed at the end of the document, sorted by their number.
+    FootnoteDefinition {
+        /// The number this footnote is referenced by.
+        number: u32,
+
+        /// The contents of this footnote, starting with its superscript marker.
+        contents: Vec<Line>,
+    },
 }
 
 #[derive(Clone, Copy, Debug, Default)]
@@ -102,6 +116,11 @@ impl Line {
         self.0.iter().map(|text| text.content.width()).sum()
     }
 
+    /// Get the contents of this line without any styling.
+    pub(crate) fn text(&self) -> String {
+        self.0.iter().map(|text| text.content.as_str()).collect()
+    }
+
     /// Applies the given style to this text.
     pub(crate) fn apply_style(&mut self, style: &TextStyle) {
         for text in &mut self.0 {
@@ -170,6 +189,9 @@ pub(crate) enum ListItemType {
 
     /// A list item for an ordered list that uses a period after the list item number.
     OrderedPeriod,
+
+    /// A task list item, e.g. `- [x] done`.
+    Task { checked: bool },
 }
 
 /// A table.
@@ -180,28 +202,29 @@ pub(crate) struct Table {
 
     /// All of the rows in this table, excluding the header.
     pub(crate) rows: Vec<TableRow>,
-}
 
-impl Table {
-    /// gets the number of columns in this table.
-    pub(crate) fn columns(&self) -> usize {
-        self.header.0.len()
-    }
-
-    /// Iterates all the text entries in a column.
-    ///
-    /// This includes the header.
-    pub(crate) fn iter_column(&self, column: usize) -> impl Iterator<Item = &Line> {
-        let header_element = &self.header.0[column];
-        let row_elements = self.rows.iter().map(move |row| &row.0[column]);
-        iter::once(header_element).chain(row_elements)
-    }
+    /// The alignment of each of the columns in this table.
+    pub(crate) alignments: Vec<ColumnAlignment>,
 }
 
 /// A table row.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub(crate) struct TableRow(pub(crate) Vec<Line>);
 
+/// The alignment of a table column.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum ColumnAlignment {
+    /// Align the column's contents to the left.
+    #[default]
+    Left,
+
+    /// Center the column's contents.
+    Center,
+
+    /// Align the column's contents to the right.
+    Right,
+}
+
 /// A percentage.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub(crate) struct Percent(pub(crate) u8);
@@ -1,9 +1,9 @@
 use super::{
-    elements::SourcePosition,
+    elements::{LineColumn, SourcePosition},
     html::{HtmlInline, HtmlParser, ParseHtmlError},
 };
 use crate::{
-    markdown::elements::{Line, ListItem, ListItemType, MarkdownElement, Table, TableRow, Text},
+    markdown::elements::{ColumnAlignment, Line, ListItem, ListItemType, MarkdownElement, Table, TableRow, Text},
     style::TextStyle,
 };
 use comrak::{
@@ -11,11 +11,12 @@ use comrak::{
     arena_tree::Node,
     format_commonmark,
     nodes::{
-        Ast, AstNode, ListDelimType, ListType, NodeCodeBlock, NodeHeading, NodeHtmlBlock, NodeList, NodeValue,
-        Sourcepos,
+        Ast, AstNode, ListDelimType, ListType, NodeCodeBlock, NodeHeading, NodeHtmlBlock, NodeList, NodeTable,
+        NodeValue, Sourcepos, TableAlignment,
     },
     parse_document,
 };
+use serde::Deserialize;
 use std::{
     cell::RefCell,
     fmt::{self, Debug, Display},
@@ -34,6 +35,8 @@ impl Default for ParserOptions {
         options.extension.table = true;
         options.extension.strikethrough = true;
         options.extension.multiline_block_quotes = true;
+        options.extension.footnotes = true;
+        options.extension.tasklist = true;
         Self(options)
     }
 }
@@ -44,30 +47,128 @@ impl Default for ParserOptions {
 pub struct MarkdownParser<'a> {
     arena: &'a Arena<AstNode<'a>>,
     options: ComrakOptions<'static>,
+    footnote_offset: u32,
+    math: bool,
 }
 
 impl<'a> MarkdownParser<'a> {
     /// Construct a new markdown parser.
     pub fn new(arena: &'a Arena<AstNode<'a>>) -> Self {
-        Self { arena, options: ParserOptions::default().0 }
+        Self { arena, options: ParserOptions::default().0, footnote_offset: 0, math: false }
+    }
+
+    /// Treat text surrounded by dollar signs as math formulas.
+    ///
+    /// This can still be overridden by the front matter of the file being parsed.
+    pub fn with_math(mut self, enabled: bool) -> Self {
+        self.math = enabled;
+        self
+    }
+
+    /// Start numbering footnotes after the given number.
+    ///
+    /// This is used when parsing a file that's included into one whose footnotes have already
+    /// been numbered.
+    pub(crate) fn with_footnote_offset(mut self, offset: u32) -> Self {
+        self.footnote_offset = offset;
+        self
     }
 
     /// Parse the contents of a markdown file.
-    pub(crate) fn parse(&self, contents: &str) -> ParseResult<Vec<MarkdownElement>> {
-        let node = parse_document(self.arena, contents, &self.options);
+    ///
+    /// Every element is paired with the position in the file it starts at.
+    pub(crate) fn parse(&self, contents: &str) -> ParseResult<Vec<(MarkdownElement, SourcePosition)>> {
+        let (elements, mut errors) = self.parse_all(contents);
+        match errors.is_empty() {
+            true => Ok(elements),
+            false => Err(errors.remove(0)),
+        }
+    }
+
+    /// Parse the contents of a markdown file without stopping at the first error.
+    ///
+    /// Any top level element that fails to parse is left out, and the errors for all of them are
+    /// returned in the order they appear in the file.
+    pub(crate) fn parse_all(&self, contents: &str) -> (Vec<(MarkdownElement, SourcePosition)>, Vec<ParseError>) {
+        let mut options = self.options.clone();
+        options.extension.math_dollars = Self::front_matter_math(contents).unwrap_or(self.math);
+        let node = parse_document(self.arena, contents, &options);
         let mut elements = Vec::new();
+        let mut errors = Vec::new();
         let mut lines_offset = 0;
+        let mut footnotes = self.footnote_offset;
         for node in node.children() {
-            let mut parsed_elements =
-                self.parse_node(node).map_err(|e| ParseError::new(e.kind, e.sourcepos.offset_lines(lines_offset)))?;
+            let footnote_references = self.find_footnote_references(node);
+            let parsed_elements = match &node.data.borrow().value {
+                // comrak moves these to the end of the document, sorted by the order in which
+                // they're first referenced, so we can number them as we go.
+                NodeValue::FootnoteDefinition(_) => {
+                    footnotes += 1;
+                    self.parse_footnote_definition(node, footnotes).map(|element| vec![element])
+                }
+                _ => self.parse_node(node),
+            };
+            let mut parsed_elements = match parsed_elements {
+                Ok(elements) => elements,
+                Err(e) => {
+                    errors.push(ParseError::new(e.kind, e.sourcepos.offset_lines(lines_offset)));
+                    continue;
+                }
+            };
             if let NodeValue::FrontMatter(contents) = &node.data.borrow().value {
                 lines_offset += contents.lines().count();
             }
             // comrak ignores the lines in the front matter so we need to offset this ourselves.
             Self::adjust_source_positions(parsed_elements.iter_mut(), lines_offset);
-            elements.extend(parsed_elements);
+            let position = match &node.data.borrow().value {
+                // comrak doesn't track this one, but it can only be at the beginning of the file.
+                NodeValue::FrontMatter(_) => SourcePosition { start: LineColumn { line: 1, column: 1 } },
+                _ => SourcePosition::from(node.data.borrow().sourcepos).offset_lines(lines_offset),
+            };
+            elements.extend(parsed_elements.into_iter().map(|element| (element, position)));
+            if !footnote_references.is_empty() {
+                elements.push((MarkdownElement::FootnoteReferences(footnote_references), position));
+            }
         }
-        Ok(elements)
+        (elements, errors)
+    }
+
+    // Whether math is enabled needs to be known before parsing, so the front matter is looked at on its own first.
+    fn front_matter_math(contents: &str) -> Option<bool> {
+        #[derive(Deserialize)]
+        struct FrontMatter {
+            options: Option<MathOptions>,
+        }
+
+        #[derive(Deserialize)]
+        struct MathOptions {
+            math: Option<bool>,
+        }
+
+        let mut lines = contents.lines();
+        if lines.next()?.trim_end() != "---" {
+            return None;
+        }
+        let front_matter: Vec<_> = lines.take_while(|line| line.trim_end() != "---").collect();
+        let front_matter: FrontMatter = serde_yaml::from_str(&front_matter.join("\n")).ok()?;
+        front_matter.options?.math
+    }
+
+    fn find_footnote_references(&self, node: &'a AstNode<'a>) -> Vec<u32> {
+        let mut references = Vec::new();
+        // References within a footnote definition don't belong to the slide it's in.
+        if matches!(node.data.borrow().value, NodeValue::FootnoteDefinition(_)) {
+            return references;
+        }
+        for node in node.descendants() {
+            if let NodeValue::FootnoteReference(reference) = &node.data.borrow().value {
+                let number = reference.ix + self.footnote_offset;
+                if !references.contains(&number) {
+                    references.push(number);
+                }
+            }
+        }
+        references
     }
 
     fn adjust_source_positions<'b>(elements: impl Iterator<Item = &'b mut MarkdownElement>, lines_offset: usize) {
@@ -80,7 +181,9 @@ impl<'a> MarkdownParser<'a> {
                 | MarkdownElement::List(_)
                 | MarkdownElement::Table(_)
                 | MarkdownElement::ThematicBreak
-                | MarkdownElement::BlockQuote(_) => continue,
+                | MarkdownElement::BlockQuote(_)
+                | MarkdownElement::FootnoteReferences(_)
+                | MarkdownElement::FootnoteDefinition { .. } => continue,
                 MarkdownElement::Comment { source_position, .. }
                 | MarkdownElement::Snippet { source_position, .. }
                 | MarkdownElement::Image { source_position, .. } => source_position,
@@ -100,7 +203,7 @@ impl<'a> MarkdownParser<'a> {
                 let items = self.parse_list(node, list.marker_offset as u8 / 2)?;
                 MarkdownElement::List(items)
             }
-            NodeValue::Table(_) => self.parse_table(node)?,
+            NodeValue::Table(table) => self.parse_table(node, table)?,
             NodeValue::CodeBlock(block) => Self::parse_code_block(block, data.sourcepos)?,
             NodeValue::ThematicBreak => MarkdownElement::ThematicBreak,
             NodeValue::HtmlBlock(block) => self.parse_html_block(block, data.sourcepos)?,
@@ -136,7 +239,8 @@ impl<'a> MarkdownParser<'a> {
 
     fn parse_block_quote(&self, node: &'a AstNode<'a>) -> ParseResult<MarkdownElement> {
         let mut elements = Vec::new();
-        let inlines = InlinesParser::new(self.arena, SoftBreak::Newline, StringifyImages::Yes).parse(node)?;
+        let inlines = InlinesParser::new(self.arena, SoftBreak::Newline, StringifyImages::Yes, self.footnote_offset)
+            .parse(node)?;
         for inline in inlines {
             match inline {
                 Inline::Text(text) => elements.push(text),
@@ -150,6 +254,27 @@ impl<'a> MarkdownParser<'a> {
         Ok(MarkdownElement::BlockQuote(elements))
     }
 
+    fn parse_footnote_definition(&self, node: &'a AstNode<'a>, number: u32) -> ParseResult<MarkdownElement> {
+        let mut contents = Vec::new();
+        let inlines =
+            InlinesParser::new(self.arena, SoftBreak::Space, StringifyImages::Yes, self.footnote_offset).parse(node)?;
+        for inline in inlines {
+            if let Inline::Text(text) = inline {
+                contents.push(text);
+            }
+        }
+        let mut first_line =
+            vec![Text::new(superscript(number), TextStyle::default().footnote_reference()), Text::from(" ")];
+        match contents.first_mut() {
+            Some(line) => {
+                first_line.append(&mut line.0);
+                line.0 = first_line;
+            }
+            None => contents.push(Line(first_line)),
+        };
+        Ok(MarkdownElement::FootnoteDefinition { number, contents })
+    }
+
     fn parse_code_block(block: &NodeCodeBlock, sourcepos: Sourcepos) -> ParseResult<MarkdownElement> {
         if !block.fenced {
             return Err(ParseErrorKind::UnfencedCodeBlock.with_sourcepos(sourcepos));
@@ -172,7 +297,8 @@ impl<'a> MarkdownParser<'a> {
 
     fn parse_paragraph(&self, node: &'a AstNode<'a>) -> ParseResult<Vec<MarkdownElement>> {
         let mut elements = Vec::new();
-        let inlines = InlinesParser::new(self.arena, SoftBreak::Space, StringifyImages::No).parse(node)?;
+        let inlines =
+            InlinesParser::new(self.arena, SoftBreak::Space, StringifyImages::No, self.footnote_offset).parse(node)?;
         let mut paragraph_elements = Vec::new();
         for inline in inlines {
             match inline {
@@ -197,7 +323,8 @@ impl<'a> MarkdownParser<'a> {
     }
 
     fn parse_text(&self, node: &'a AstNode<'a>) -> ParseResult<Line> {
-        let inlines = InlinesParser::new(self.arena, SoftBreak::Space, StringifyImages::No).parse(node)?;
+        let inlines =
+            InlinesParser::new(self.arena, SoftBreak::Space, StringifyImages::No, self.footnote_offset).parse(node)?;
         let mut chunks = Vec::new();
         for inline in inlines {
             match inline {
@@ -217,7 +344,16 @@ impl<'a> MarkdownParser<'a> {
             let data = node.data.borrow();
             match &data.value {
                 NodeValue::Item(item) => {
-                    elements.extend(self.parse_list_item(item, node, depth)?);
+                    let item_type = match (item.list_type, item.delimiter) {
+                        (ListType::Bullet, _) => ListItemType::Unordered,
+                        (ListType::Ordered, ListDelimType::Paren) => ListItemType::OrderedParens,
+                        (ListType::Ordered, ListDelimType::Period) => ListItemType::OrderedPeriod,
+                    };
+                    elements.extend(self.parse_list_item(item_type, node, depth)?);
+                }
+                NodeValue::TaskItem(symbol) => {
+                    let item_type = ListItemType::Task { checked: symbol.is_some() };
+                    elements.extend(self.parse_list_item(item_type, node, depth)?);
                 }
                 other => {
                     return Err(ParseErrorKind::UnsupportedStructure {
@@ -231,12 +367,7 @@ impl<'a> MarkdownParser<'a> {
         Ok(elements)
     }
 
-    fn parse_list_item(&self, item: &NodeList, root: &'a AstNode<'a>, depth: u8) -> ParseResult<Vec<ListItem>> {
-        let item_type = match (item.list_type, item.delimiter) {
-            (ListType::Bullet, _) => ListItemType::Unordered,
-            (ListType::Ordered, ListDelimType::Paren) => ListItemType::OrderedParens,
-            (ListType::Ordered, ListDelimType::Period) => ListItemType::OrderedPeriod,
-        };
+    fn parse_list_item(&self, item_type: ListItemType, root: &'a AstNode<'a>, depth: u8) -> ParseResult<Vec<ListItem>> {
         let mut elements = Vec::new();
         for node in root.children() {
             let data = node.data.borrow();
@@ -260,7 +391,7 @@ impl<'a> MarkdownParser<'a> {
         Ok(elements)
     }
 
-    fn parse_table(&self, node: &'a AstNode<'a>) -> ParseResult<MarkdownElement> {
+    fn parse_table(&self, node: &'a AstNode<'a>, table: &NodeTable) -> ParseResult<MarkdownElement> {
         let mut header = TableRow(Vec::new());
         let mut rows = Vec::new();
         for node in node.children() {
@@ -279,7 +410,16 @@ impl<'a> MarkdownParser<'a> {
                 rows.push(row)
             }
         }
-        Ok(MarkdownElement::Table(Table { header, rows }))
+        let alignments = table
+            .alignments
+            .iter()
+            .map(|alignment| match alignment {
+                TableAlignment::None | TableAlignment::Left => ColumnAlignment::Left,
+                TableAlignment::Center => ColumnAlignment::Center,
+                TableAlignment::Right => ColumnAlignment::Right,
+            })
+            .collect();
+        Ok(MarkdownElement::Table(Table { header, rows, alignments }))
     }
 
     fn parse_table_row(&self, node: &'a AstNode<'a>) -> ParseResult<TableRow> {
@@ -316,11 +456,17 @@ struct InlinesParser<'a> {
     arena: &'a Arena<AstNode<'a>>,
     soft_break: SoftBreak,
     stringify_images: StringifyImages,
+    footnote_offset: u32,
 }
 
 impl<'a> InlinesParser<'a> {
-    fn new(arena: &'a Arena<AstNode<'a>>, soft_break: SoftBreak, stringify_images: StringifyImages) -> Self {
-        Self { inlines: Vec::new(), pending_text: Vec::new(), arena, soft_break, stringify_images }
+    fn new(
+        arena: &'a Arena<AstNode<'a>>,
+        soft_break: SoftBreak,
+        stringify_images: StringifyImages,
+        footnote_offset: u32,
+    ) -> Self {
+        Self { inlines: Vec::new(), pending_text: Vec::new(), arena, soft_break, stringify_images, footnote_offset }
     }
 
     fn parse(mut self, node: &'a AstNode<'a>) -> ParseResult<Vec<Inline>> {
@@ -416,17 +562,29 @@ impl<'a> InlinesParser<'a> {
                 self.inlines.push(Inline::LineBreak);
             }
             NodeValue::Item(item) => {
-                match (item.list_type, item.delimiter) {
-                    (ListType::Bullet, _) => self.pending_text.push(Text::from("* ")),
-                    (ListType::Ordered, ListDelimType::Period) => {
-                        self.pending_text.push(Text::from(format!("{}. ", item.start)))
-                    }
-                    (ListType::Ordered, ListDelimType::Paren) => {
-                        self.pending_text.push(Text::from(format!("{}) ", item.start)))
-                    }
-                };
+                self.pending_text.push(Text::from(inline_list_item_prefix(item, item.start)));
+                self.process_children(node, style)?;
+            }
+            NodeValue::TaskItem(symbol) => {
+                // Task items don't carry their list's type so it has to be taken from the list itself.
+                if let NodeValue::List(list) = &parent.data.borrow().value {
+                    let index = node.preceding_siblings().skip(1).count();
+                    self.pending_text.push(Text::from(inline_list_item_prefix(list, list.start + index)));
+                }
+                let checkbox = if symbol.is_some() { "[x] " } else { "[ ] " };
+                self.pending_text.push(Text::from(checkbox));
                 self.process_children(node, style)?;
             }
+            NodeValue::Math(math) => {
+                // Keep the delimiters so this can still be displayed as is if it can't be rendered.
+                let delimiter = if math.display_math { "$$" } else { "$" };
+                let formula = format!("{delimiter}{}{delimiter}", math.literal);
+                self.pending_text.push(Text::new(formula, style.math()));
+            }
+            NodeValue::FootnoteReference(reference) => {
+                let number = reference.ix + self.footnote_offset;
+                self.pending_text.push(Text::new(superscript(number), style.footnote_reference()));
+            }
             NodeValue::HtmlInline(html) => {
                 let html_inline = HtmlParser::default()
                     .parse(html)
@@ -465,6 +623,19 @@ impl<'a> InlinesParser<'a> {
     }
 }
 
+fn inline_list_item_prefix(list: &NodeList, number: usize) -> String {
+    match (list.list_type, list.delimiter) {
+        (ListType::Bullet, _) => "* ".into(),
+        (ListType::Ordered, ListDelimType::Period) => format!("{number}. "),
+        (ListType::Ordered, ListDelimType::Paren) => format!("{number}) "),
+    }
+}
+
+fn superscript(number: u32) -> String {
+    const DIGITS: [char; 10] = ['⁰', '¹', '²', '³', '⁴', '⁵', '⁶', '⁷', '⁸', '⁹'];
+    number.to_string().chars().filter_map(|c| c.to_digit(10)).map(|digit| DIGITS[digit as usize]).collect()
+}
+
 enum HtmlStyle {
     Add(TextStyle),
     Remove,
@@ -606,7 +777,8 @@ mod test {
 
     fn try_parse(input: &str) -> Result<Vec<MarkdownElement>, ParseError> {
         let arena = Arena::new();
-        MarkdownParser::new(&arena).parse(input)
+        let elements = MarkdownParser::new(&arena).parse(input)?;
+        Ok(elements.into_iter().map(|(element, _)| element).collect())
     }
 
     fn parse_single(input: &str) -> MarkdownElement {
@@ -849,13 +1021,27 @@ let q = 42;
 | Carrot | Yuck |
 ",
         );
-        let MarkdownElement::Table(Table { header, rows }) = parsed else { panic!("not a table: {parsed:?}") };
+        let MarkdownElement::Table(Table { header, rows, .. }) = parsed else { panic!("not a table: {parsed:?}") };
         assert_eq!(header.0.len(), 2);
         assert_eq!(rows.len(), 2);
         assert_eq!(rows[0].0.len(), 2);
         assert_eq!(rows[1].0.len(), 2);
     }
 
+    #[test]
+    fn table_alignments() {
+        let parsed = parse_single(
+            r"
+| Name | Taste | Price | Origin |
+| ---- | :---- | :---: | -----: |
+| Potato | Great | 1 | Peru |
+",
+        );
+        let MarkdownElement::Table(Table { alignments, .. }) = parsed else { panic!("not a table: {parsed:?}") };
+        let expected = &[ColumnAlignment::Left, ColumnAlignment::Left, ColumnAlignment::Center, ColumnAlignment::Right];
+        assert_eq!(alignments, expected);
+    }
+
     #[test]
     fn comment() {
         let parsed = parse_single(
@@ -881,6 +1067,131 @@ let q = 42;
         assert_eq!(items[0].depth, 1);
     }
 
+    #[test]
+    fn math() {
+        let arena = Arena::new();
+        let parsed = MarkdownParser::new(&arena).with_math(true).parse("energy is $E = mc^2$\n\n$$\\sum x$$");
+        let parsed: Vec<_> = parsed.expect("parse failed").into_iter().map(|(element, _)| element).collect();
+        let [MarkdownElement::Paragraph(inline), MarkdownElement::Paragraph(display)] = parsed.as_slice() else {
+            panic!("unexpected elements: {parsed:?}")
+        };
+        let math = TextStyle::default().math();
+        assert_eq!(inline, &[Line(vec![Text::from("energy is "), Text::new("$E = mc^2$", math)])]);
+        assert_eq!(display, &[Line(vec![Text::new("$$\\sum x$$", math)])]);
+    }
+
+    #[test]
+    fn math_disabled() {
+        let parsed = parse_single("$HOME and $PATH cost $$5");
+        let MarkdownElement::Paragraph(lines) = parsed else { panic!("not a paragraph: {parsed:?}") };
+        assert_eq!(lines, &[Line::from("$HOME and $PATH cost $$5")]);
+    }
+
+    #[rstest]
+    #[case::enabled("---\noptions:\n  math: true\n---\n\n$x$\n", false, true)]
+    #[case::disabled("---\noptions:\n  math: false\n---\n\n$x$\n", true, false)]
+    #[case::not_set("---\ntitle: hi\n---\n\n$x$\n", true, true)]
+    fn math_front_matter(#[case] input: &str, #[case] default: bool, #[case] expected: bool) {
+        let arena = Arena::new();
+        let parsed = MarkdownParser::new(&arena).with_math(default).parse(input).expect("parse failed");
+        let (MarkdownElement::Paragraph(lines), _) = &parsed[1] else { panic!("not a paragraph: {parsed:?}") };
+        assert_eq!(lines[0].0[0].style == TextStyle::default().math(), expected);
+    }
+
+    #[test]
+    fn task_list() {
+        let parsed = parse_single(
+            r"
+* [x] done
+* [ ] pending
+  1. [X] nested
+  2. ordered
+",
+        );
+        let MarkdownElement::List(items) = parsed else { panic!("not a list: {parsed:?}") };
+        let items: Vec<_> = items.into_iter().map(|item| (item.depth, item.item_type)).collect();
+        let expected = &[
+            (0, ListItemType::Task { checked: true }),
+            (0, ListItemType::Task { checked: false }),
+            (1, ListItemType::Task { checked: true }),
+            (1, ListItemType::OrderedPeriod),
+        ];
+        assert_eq!(items, expected);
+    }
+
+    #[test]
+    fn footnotes() {
+        let parsed = parse_all(
+            r"
+hello[^b] world[^a][^b]
+
+unreferenced[^missing]
+
+[^a]: the _first_ one
+[^b]: the second one
+[^c]: not used
+",
+        );
+        let [
+            MarkdownElement::Paragraph(hello),
+            MarkdownElement::FootnoteReferences(references),
+            MarkdownElement::Paragraph(unreferenced),
+            MarkdownElement::FootnoteDefinition { number: 1, contents: first },
+            MarkdownElement::FootnoteDefinition { number: 2, contents: second },
+        ] = parsed.as_slice()
+        else {
+            panic!("unexpected elements: {parsed:?}")
+        };
+        let reference = TextStyle::default().footnote_reference();
+        assert_eq!(hello, &[Line(vec![
+            Text::from("hello"),
+            Text::new("¹", reference),
+            Text::from(" world"),
+            Text::new("²", reference),
+            Text::new("¹", reference)
+        ])]);
+        assert_eq!(references, &[1, 2]);
+        assert_eq!(unreferenced, &[Line::from("unreferenced[^missing]")]);
+        assert_eq!(first, &[Line(vec![Text::new("¹", reference), Text::from(" "), Text::from("the second one")])]);
+        assert_eq!(second, &[Line(vec![
+            Text::new("²", reference),
+            Text::from(" "),
+            Text::from("the "),
+            Text::new("first", TextStyle::default().italics()),
+            Text::from(" one")
+        ])]);
+    }
+
+    #[test]
+    fn footnote_offset() {
+        let arena = Arena::new();
+        let parsed = MarkdownParser::new(&arena)
+            .with_footnote_offset(3)
+            .parse("hello[^a]\n\n[^a]: first\n")
+            .expect("parse failed");
+        let parsed: Vec<_> = parsed.into_iter().map(|(element, _)| element).collect();
+        let [
+            MarkdownElement::Paragraph(hello),
+            MarkdownElement::FootnoteReferences(references),
+            MarkdownElement::FootnoteDefinition { number: 4, .. },
+        ] = parsed.as_slice()
+        else {
+            panic!("unexpected elements: {parsed:?}")
+        };
+        assert_eq!(hello, &[Line(vec![
+            Text::from("hello"),
+            Text::new("⁴", TextStyle::default().footnote_reference())
+        ])]);
+        assert_eq!(references, &[4]);
+    }
+
+    #[rstest]
+    #[case::single(7, "⁷")]
+    #[case::multiple_digits(120, "¹²⁰")]
+    fn superscripts(#[case] number: u32, #[case] expected: &str) {
+        assert_eq!(superscript(number), expected);
+    }
+
     #[test]
     fn block_quote() {
         let parsed = parse_single(
@@ -919,6 +1230,26 @@ let q = 42;
         assert_eq!(lines[10], Line(vec![Text::from("2) "), Text::from("b")]));
     }
 
+    #[test]
+    fn block_quote_task_list() {
+        let parsed = parse_single(
+            r#"
+> * [x] a
+> * [ ] b
+>
+> 3. [ ] a
+> 4. [x] b
+"#,
+        );
+        let MarkdownElement::BlockQuote(lines) = parsed else { panic!("not a block quote: {parsed:?}") };
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines[0], Line(vec![Text::from("* "), Text::from("[x] "), Text::from("a")]));
+        assert_eq!(lines[1], Line(vec![Text::from("* "), Text::from("[ ] "), Text::from("b")]));
+        assert_eq!(lines[2], Line::from(""));
+        assert_eq!(lines[3], Line(vec![Text::from("3. "), Text::from("[ ] "), Text::from("a")]));
+        assert_eq!(lines[4], Line(vec![Text::from("4. "), Text::from("[x] "), Text::from("b")]));
+    }
+
     #[test]
     fn multiline_block_quote() {
         let parsed = parse_single(
@@ -989,6 +1320,46 @@ mom
         assert_eq!(source_position.start.column, 1);
     }
 
+    #[test]
+    fn element_positions() {
+        let input = r"---
+hi
+mom
+---
+
+# Hello
+
+* one
+* two
+
+bye
+";
+        let arena = Arena::new();
+        let parsed = MarkdownParser::new(&arena).parse(input).expect("parse failed");
+        let lines: Vec<_> = parsed.iter().map(|(_, position)| position.start.line).collect();
+        assert_eq!(lines, &[1, 6, 8, 11]);
+    }
+
+    #[test]
+    fn collect_all_errors() {
+        let input = r"
+hi
+
+    unfenced
+
+bye
+
+* ![](potato.png)
+";
+        let arena = Arena::new();
+        let (elements, errors) = MarkdownParser::new(&arena).parse_all(input);
+        let lines: Vec<_> = elements.iter().map(|(_, position)| position.start.line).collect();
+        assert_eq!(lines, &[2, 6]);
+
+        let errors: Vec<_> = errors.iter().map(|e| e.sourcepos.start.line).collect();
+        assert_eq!(errors, &[4, 8]);
+    }
+
     #[rstest]
     #[case::lf("\n")]
     #[case::crlf("\r\n")]
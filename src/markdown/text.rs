@@ -1,5 +1,5 @@
 use super::elements::{Line, Text};
-use crate::style::TextStyle;
+use crate::{search::find_matches, style::TextStyle};
 use std::mem;
 use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
@@ -14,7 +14,7 @@ pub(crate) struct WeightedLine {
 
 impl WeightedLine {
     /// Split this line into chunks of at most `max_length` width.
-    pub(crate) fn split(&self, max_length: usize) -> SplitTextIter {
+    pub(crate) fn split(&self, max_length: usize) -> SplitTextIter<'_> {
         SplitTextIter::new(&self.text, max_length)
     }
 
@@ -23,6 +23,51 @@ impl WeightedLine {
         self.width
     }
 
+    /// Get the text in this line without any styling.
+    pub(crate) fn text(&self) -> String {
+        self.text.iter().map(|text| text.text.content.as_str()).collect()
+    }
+
+    /// Apply a style on top of every case insensitive occurrence of `query` in this line.
+    ///
+    /// Returns `None` if the query doesn't appear in this line.
+    pub(crate) fn highlight(&self, query: &str, style: &TextStyle) -> Option<WeightedLine> {
+        let matches = find_matches(&self.text(), query);
+        if matches.is_empty() {
+            return None;
+        }
+        let mut matches = matches.into_iter().peekable();
+        let mut output = Vec::new();
+        let mut position = 0;
+        for chunk in &self.text {
+            let mut current = String::new();
+            let mut highlighting = false;
+            for c in chunk.text.content.chars() {
+                while matches.next_if(|range| range.end <= position).is_some() {}
+                let highlight = matches.peek().is_some_and(|range| range.contains(&position));
+                if highlight != highlighting && !current.is_empty() {
+                    output.push(Self::highlighted_text(mem::take(&mut current), chunk.text.style, style, highlighting));
+                }
+                highlighting = highlight;
+                current.push(c);
+                position += 1;
+            }
+            if !current.is_empty() {
+                output.push(Self::highlighted_text(current, chunk.text.style, style, highlighting));
+            }
+        }
+        Some(output.into())
+    }
+
+    fn highlighted_text(content: String, style: TextStyle, highlight: &TextStyle, highlighted: bool) -> Text {
+        if !highlighted {
+            return Text::new(content, style);
+        }
+        let mut output = *highlight;
+        output.merge(&style);
+        Text::new(content, output)
+    }
+
     /// Get an iterator to the underlying text chunks.
     #[cfg(test)]
     pub(crate) fn iter_texts(&self) -> impl Iterator<Item = &WeightedText> {
@@ -86,7 +131,7 @@ pub(crate) struct WeightedText {
 }
 
 impl WeightedText {
-    fn to_ref(&self) -> WeightedTextRef {
+    fn to_ref(&self) -> WeightedTextRef<'_> {
         WeightedTextRef { text: &self.text.content, accumulators: &self.accumulators, style: self.text.style }
     }
 
@@ -430,4 +475,22 @@ mod test {
         let block = WeightedLine::from(texts.to_vec());
         assert_eq!(block.text.len(), expected);
     }
+
+    #[test]
+    fn highlight() {
+        let bold = TextStyle::default().bold();
+        let line = WeightedLine::from(vec![Text::new("hello wo", bold), Text::from("rld, World")]);
+        let highlight = TextStyle::default().underlined();
+        let highlighted = line.highlight("world", &highlight).expect("no matches");
+        let texts: Vec<_> = highlighted.iter_texts().map(|text| text.text().clone()).collect();
+        let expected = vec![
+            Text::new("hello ", bold),
+            Text::new("wo", bold.underlined()),
+            Text::new("rld", highlight),
+            Text::from(", "),
+            Text::new("World", highlight),
+        ];
+        assert_eq!(texts, expected);
+        assert!(line.highlight("potato", &highlight).is_none());
+    }
 }
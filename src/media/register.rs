@@ -10,6 +10,11 @@ use std::{path::PathBuf, sync::Arc};
 pub struct ImageRegistry(pub Arc<ImagePrinter>);
 
 impl ImageRegistry {
+    /// Whether images are printed using ascii blocks, which makes small images illegible.
+    pub(crate) fn is_ascii(&self) -> bool {
+        matches!(*self.0, ImagePrinter::Ascii(_))
+    }
+
     pub(crate) fn register_image(&self, image: DynamicImage) -> Result<Image, RegisterImageError> {
         let resource = self.0.register_image(image)?;
         let image = Image::new(resource, ImageSource::Generated);
@@ -1,9 +1,13 @@
 use crate::{
     custom::OptionsConfig,
-    markdown::text::{WeightedLine, WeightedText},
+    markdown::{
+        elements::SourcePosition,
+        text::{WeightedLine, WeightedText},
+    },
     media::image::Image,
-    render::properties::WindowSize,
-    style::{Color, Colors},
+    render::{engine::TextHighlight, properties::WindowSize},
+    search::{SearchMatch, SearchState, find_matches},
+    style::{Color, Colors, TextStyle},
     theme::{Alignment, Margin, PresentationTheme},
 };
 use serde::Deserialize;
@@ -20,6 +24,8 @@ use std::{
 pub(crate) struct Modals {
     pub(crate) slide_index: Vec<RenderOperation>,
     pub(crate) bindings: Vec<RenderOperation>,
+    pub(crate) search_bar: Vec<RenderOperation>,
+    pub(crate) search_match_style: TextStyle,
 }
 
 /// A presentation.
@@ -51,6 +57,11 @@ impl Presentation {
         self.modals.bindings.iter()
     }
 
+    /// Iterate the operations that render the search bar.
+    pub(crate) fn iter_search_bar_operations(&self) -> impl Iterator<Item = &RenderOperation> {
+        self.modals.search_bar.iter()
+    }
+
     /// Consume this presentation and return its slides.
     #[cfg(test)]
     pub(crate) fn into_slides(self) -> Vec<Slide> {
@@ -168,6 +179,19 @@ impl Presentation {
         any_rendered
     }
 
+    /// Cancel any async render operations in the current slide.
+    pub(crate) fn cancel_slide_async_renders(&mut self) -> bool {
+        let slide = self.current_slide_mut();
+        let mut any_cancelled = false;
+        for operation in slide.iter_visible_operations_mut() {
+            if let RenderOperation::RenderAsync(operation) = operation {
+                let is_cancelled = operation.cancel();
+                any_cancelled = any_cancelled || is_cancelled;
+            }
+        }
+        any_cancelled
+    }
+
     // Get all slides that contain async render operations.
     pub(crate) fn slides_with_async_renders(&self) -> HashSet<usize> {
         let mut indexes = HashSet::new();
@@ -209,22 +233,6 @@ impl Presentation {
         slide_state
     }
 
-    /// Run a callback through every operation and let it mutate it in place.
-    ///
-    /// This should be used with care!
-    pub(crate) fn mutate_operations<F>(&mut self, mut callback: F)
-    where
-        F: FnMut(&mut RenderOperation),
-    {
-        for slide in &mut self.slides {
-            for chunk in &mut slide.chunks {
-                for operation in &mut chunk.operations {
-                    callback(operation);
-                }
-            }
-        }
-    }
-
     fn current_slide_mut(&mut self) -> &mut Slide {
         let index = self.current_slide_index();
         &mut self.slides[index]
@@ -253,11 +261,198 @@ impl Presentation {
             false
         }
     }
+
+    /// Reset the slide index so its cursor points to the current slide and nothing is filtered.
+    pub(crate) fn reset_slide_index(&mut self) {
+        let cursor = self.current_slide_index();
+        self.state.set_slide_index(SlideIndexState { cursor, filter: String::new() });
+    }
+
+    /// Move the slide index cursor to the previous or next slide that matches the filter.
+    pub(crate) fn move_slide_index_cursor(&mut self, direction: CursorDirection) -> bool {
+        let mut index = self.state.slide_index();
+        let matches = self.slide_index_matches(&index);
+        let position = matches.iter().position(|slide| *slide == index.cursor);
+        let next = match (direction, position) {
+            (_, None) => matches.first(),
+            (CursorDirection::Up, Some(position)) => matches.get(position.saturating_sub(1)),
+            (CursorDirection::Down, Some(position)) => matches.get(position + 1),
+        };
+        match next {
+            Some(next) if *next != index.cursor => {
+                index.cursor = *next;
+                self.state.set_slide_index(index);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Add or remove a character at the end of the slide index filter.
+    ///
+    /// The cursor is moved to the first matching slide if the slide it points to no longer
+    /// matches.
+    pub(crate) fn update_slide_index_filter(&mut self, character: Option<char>) -> bool {
+        let mut index = self.state.slide_index();
+        let modified = match character {
+            Some(character) => {
+                index.filter.push(character);
+                true
+            }
+            None => index.filter.pop().is_some(),
+        };
+        let matches = self.slide_index_matches(&index);
+        if !matches.contains(&index.cursor) {
+            if let Some(first) = matches.first() {
+                index.cursor = *first;
+            }
+        }
+        self.state.set_slide_index(index);
+        modified
+    }
+
+    /// Get the slide the slide index cursor points to, if it matches the filter.
+    pub(crate) fn slide_index_selection(&self) -> Option<usize> {
+        let index = self.state.slide_index();
+        self.slide_index_matches(&index).contains(&index.cursor).then_some(index.cursor)
+    }
+
+    fn slide_index_matches(&self, index: &SlideIndexState) -> Vec<usize> {
+        self.slides.iter().enumerate().filter(|(_, slide)| index.matches(slide.title())).map(|(i, _)| i).collect()
+    }
+
+    /// Start a new search, discarding any previous one.
+    pub(crate) fn start_search(&mut self) {
+        self.state.set_search(SearchState { editing: true, ..Default::default() });
+    }
+
+    /// Add or remove a character at the end of the search query.
+    pub(crate) fn update_search_query(&mut self, character: Option<char>) -> bool {
+        let mut search = self.state.search();
+        let modified = match character {
+            Some(character) => {
+                search.query.push(character);
+                true
+            }
+            None => search.query.pop().is_some(),
+        };
+        self.state.set_search(search);
+        modified
+    }
+
+    /// Finish typing the search query and jump to the first match at or after the current
+    /// position, wrapping around to the beginning of the presentation if there's none.
+    pub(crate) fn confirm_search(&mut self) -> bool {
+        let mut search = self.state.search();
+        if search.query.is_empty() {
+            return self.clear_search();
+        }
+        search.editing = false;
+        search.matches = self.find_search_matches(&search.query);
+        let position = SearchMatch { slide: self.current_slide_index(), chunk: self.current_chunk() };
+        search.current = match search.matches.iter().position(|found| *found >= position) {
+            Some(index) => Some(index),
+            None if search.matches.is_empty() => None,
+            None => Some(0),
+        };
+        let target = search.current.map(|index| search.matches[index]);
+        self.state.set_search(search);
+        if let Some(target) = target {
+            self.jump_to_search_match(target);
+        }
+        true
+    }
+
+    /// Jump to the next search match, wrapping around at the end of the presentation.
+    pub(crate) fn next_search_match(&mut self) -> bool {
+        self.move_search_match(1)
+    }
+
+    /// Jump to the previous search match, wrapping around at the beginning of the presentation.
+    pub(crate) fn previous_search_match(&mut self) -> bool {
+        self.move_search_match(-1)
+    }
+
+    /// Stop searching.
+    pub(crate) fn clear_search(&mut self) -> bool {
+        let active = self.state.search().is_active();
+        self.state.set_search(SearchState::default());
+        active
+    }
+
+    /// Get the query matches should be highlighted for, if any.
+    pub(crate) fn search_highlight(&self) -> Option<TextHighlight> {
+        let query = self.state.search().query;
+        (!query.is_empty()).then_some(TextHighlight { query, style: self.modals.search_match_style })
+    }
+
+    fn move_search_match(&mut self, offset: isize) -> bool {
+        let mut search = self.state.search();
+        if search.matches.is_empty() {
+            return false;
+        }
+        let total = search.matches.len() as isize;
+        // Without a current match, start right before the first one or right after the last one.
+        let current = match search.current {
+            Some(index) => index as isize,
+            None if offset > 0 => -1,
+            None => 0,
+        };
+        let next = (current + offset).rem_euclid(total) as usize;
+        search.current = Some(next);
+        let target = search.matches[next];
+        self.state.set_search(search);
+        self.jump_to_search_match(target);
+        true
+    }
+
+    fn jump_to_search_match(&mut self, target: SearchMatch) {
+        self.go_to_slide(target.slide);
+        self.jump_chunk(target.chunk);
+    }
+
+    fn find_search_matches(&self, query: &str) -> Vec<SearchMatch> {
+        let mut matches = Vec::new();
+        for (slide_index, slide) in self.slides.iter().enumerate() {
+            for (chunk_index, chunk) in slide.chunks.iter().enumerate() {
+                if chunk.operations.iter().any(|operation| Self::operation_matches(operation, query)) {
+                    matches.push(SearchMatch { slide: slide_index, chunk: chunk_index });
+                }
+            }
+        }
+        matches
+    }
+
+    fn operation_matches(operation: &RenderOperation, query: &str) -> bool {
+        let text = match operation {
+            RenderOperation::RenderText { line, .. } => line.text(),
+            RenderOperation::RenderBlockLine(block) => block.text.text(),
+            // Code blocks expose their contents this way.
+            RenderOperation::RenderDynamic(generator) => match generator.diffable_content() {
+                Some(content) => content.to_string(),
+                None => return false,
+            },
+            _ => return false,
+        };
+        !find_matches(&text, query).is_empty()
+    }
+}
+
+/// The direction in which to move a cursor.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum CursorDirection {
+    Up,
+    Down,
 }
 
 impl From<Vec<Slide>> for Presentation {
     fn from(slides: Vec<Slide>) -> Self {
-        let modals = Modals { slide_index: vec![], bindings: vec![] };
+        let modals = Modals {
+            slide_index: vec![],
+            bindings: vec![],
+            search_bar: vec![],
+            search_match_style: Default::default(),
+        };
         Self::new(slides, modals, Default::default())
     }
 }
@@ -274,6 +469,8 @@ pub(crate) type AsyncPresentationErrorHolder = Arc<Mutex<Option<AsyncPresentatio
 pub(crate) struct PresentationStateInner {
     current_slide_index: usize,
     async_error_holder: AsyncPresentationErrorHolder,
+    slide_index: SlideIndexState,
+    search: SearchState,
 }
 
 #[derive(Clone, Debug, Default)]
@@ -293,6 +490,47 @@ impl PresentationState {
     fn set_current_slide_index(&self, value: usize) {
         self.inner.deref().borrow_mut().current_slide_index = value;
     }
+
+    pub(crate) fn slide_index(&self) -> SlideIndexState {
+        self.inner.deref().borrow().slide_index.clone()
+    }
+
+    fn set_slide_index(&self, value: SlideIndexState) {
+        self.inner.deref().borrow_mut().slide_index = value;
+    }
+
+    pub(crate) fn search(&self) -> SearchState {
+        self.inner.deref().borrow().search.clone()
+    }
+
+    fn set_search(&self, value: SearchState) {
+        self.inner.deref().borrow_mut().search = value;
+    }
+}
+
+/// The state of the slide index modal.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SlideIndexState {
+    /// The index of the slide the cursor points to.
+    pub(crate) cursor: usize,
+
+    /// The text used to filter slides by title.
+    pub(crate) filter: String,
+}
+
+impl SlideIndexState {
+    /// Check whether a slide title matches the filter.
+    ///
+    /// This is a case insensitive fuzzy match: every character in the filter must appear in the
+    /// title in the same order, but not necessarily next to each other.
+    pub(crate) fn matches(&self, title: &str) -> bool {
+        let mut title = title.chars().flat_map(char::to_lowercase);
+        self.filter
+            .chars()
+            .flat_map(char::to_lowercase)
+            .filter(|c| !c.is_whitespace())
+            .all(|expected| title.any(|c| c == expected))
+    }
 }
 
 /// A slide builder.
@@ -300,6 +538,8 @@ impl PresentationState {
 pub(crate) struct SlideBuilder {
     chunks: Vec<SlideChunk>,
     footer: Vec<RenderOperation>,
+    title: String,
+    speaker_notes: Vec<String>,
 }
 
 impl SlideBuilder {
@@ -313,8 +553,21 @@ impl SlideBuilder {
         self
     }
 
+    pub(crate) fn title(mut self, title: String) -> Self {
+        self.title = title;
+        self
+    }
+
+    pub(crate) fn speaker_notes(mut self, notes: Vec<String>) -> Self {
+        self.speaker_notes = notes;
+        self
+    }
+
     pub(crate) fn build(self) -> Slide {
-        Slide::new(self.chunks, self.footer)
+        let mut slide = Slide::new(self.chunks, self.footer);
+        slide.title = self.title;
+        slide.speaker_notes = self.speaker_notes;
+        slide
     }
 }
 
@@ -326,12 +579,24 @@ impl SlideBuilder {
 pub(crate) struct Slide {
     chunks: Vec<SlideChunk>,
     footer: Vec<RenderOperation>,
+    title: String,
+    speaker_notes: Vec<String>,
     visible_chunks: usize,
 }
 
 impl Slide {
     pub(crate) fn new(chunks: Vec<SlideChunk>, footer: Vec<RenderOperation>) -> Self {
-        Self { chunks, footer, visible_chunks: 1 }
+        Self { chunks, footer, title: String::new(), speaker_notes: Vec::new(), visible_chunks: 1 }
+    }
+
+    /// The title of this slide, without any styling.
+    pub(crate) fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// The speaker notes attached to this slide.
+    pub(crate) fn speaker_notes(&self) -> &[String] {
+        &self.speaker_notes
     }
 
     pub(crate) fn iter_operations(&self) -> impl Iterator<Item = &RenderOperation> + Clone {
@@ -358,6 +623,19 @@ impl Slide {
         self.chunks.iter()
     }
 
+    /// The position in the source file of the element that generated an operation.
+    ///
+    /// Operations are indexed in the order [Slide::iter_operations] yields them.
+    pub(crate) fn source_position(&self, mut operation: usize) -> Option<SourcePosition> {
+        for chunk in &self.chunks {
+            if operation < chunk.operations.len() {
+                return chunk.source_position(operation);
+            }
+            operation -= chunk.operations.len();
+        }
+        None
+    }
+
     #[cfg(test)]
     pub(crate) fn into_operations(self) -> Vec<RenderOperation> {
         self.chunks.into_iter().flat_map(|chunk| chunk.operations.into_iter()).chain(self.footer).collect()
@@ -428,11 +706,25 @@ impl From<Vec<RenderOperation>> for Slide {
 pub(crate) struct SlideChunk {
     operations: Vec<RenderOperation>,
     mutators: Vec<Box<dyn ChunkMutator>>,
+    source_positions: Vec<(usize, SourcePosition)>,
 }
 
 impl SlideChunk {
     pub(crate) fn new(operations: Vec<RenderOperation>, mutators: Vec<Box<dyn ChunkMutator>>) -> Self {
-        Self { operations, mutators }
+        Self { operations, mutators, source_positions: Vec::new() }
+    }
+
+    /// Set the position in the source file each of the operations in this chunk came from.
+    ///
+    /// Every entry marks the index of the first operation generated from the element at that
+    /// position, so positions apply until the next entry.
+    pub(crate) fn with_source_positions(mut self, source_positions: Vec<(usize, SourcePosition)>) -> Self {
+        self.source_positions = source_positions;
+        self
+    }
+
+    fn source_position(&self, operation: usize) -> Option<SourcePosition> {
+        self.source_positions.iter().rev().find(|(index, _)| *index <= operation).map(|(_, position)| *position)
     }
 
     pub(crate) fn iter_operations(&self) -> impl Iterator<Item = &RenderOperation> + Clone {
@@ -649,6 +941,12 @@ pub(crate) enum ImageSize {
     WidthScaled {
         ratio: f64,
     },
+    /// A specific size, placed at a column in the current row rather than at the start of it.
+    Positioned {
+        column: u16,
+        columns: u16,
+        rows: u16,
+    },
 }
 
 /// Slide properties, set on initialization.
@@ -682,6 +980,13 @@ pub(crate) trait RenderAsync: AsRenderOperations {
 
     /// Update the internal state and return the updated state.
     fn poll_state(&self) -> RenderAsyncState;
+
+    /// Stop the render for this operation, if it's running and can be stopped.
+    ///
+    /// Should return true if the invocation stopped the rendering.
+    fn cancel(&self) -> bool {
+        false
+    }
 }
 
 /// The state of a [RenderAsync].
@@ -864,4 +1169,82 @@ mod test {
         assert_eq!(presentation.current_slide_index(), expected_slide, "slide differs");
         assert_eq!(presentation.current_slide().visible_chunks - 1, expected_chunk, "chunk differs");
     }
+
+    #[rstest]
+    #[case::empty("", "Introduction", true)]
+    #[case::prefix("intro", "Introduction", true)]
+    #[case::case_insensitive("INTRO", "Introduction", true)]
+    #[case::scattered("itn", "Introduction", true)]
+    #[case::spaces("code exec", "Code execution", true)]
+    #[case::out_of_order("ri", "Intro", false)]
+    #[case::missing("z", "Introduction", false)]
+    fn slide_index_filter(#[case] filter: &str, #[case] title: &str, #[case] expected: bool) {
+        let state = SlideIndexState { cursor: 0, filter: filter.into() };
+        assert_eq!(state.matches(title), expected);
+    }
+
+    #[test]
+    fn slide_index_navigation() {
+        let titles = ["Intro", "Code", "Conclusion", "Questions"];
+        let slides: Vec<_> = titles
+            .into_iter()
+            .map(|title| SlideBuilder::default().chunks(vec![SlideChunk::default()]).title(title.into()).build())
+            .collect();
+        let mut presentation = Presentation::from(slides);
+        presentation.go_to_slide(1);
+        presentation.reset_slide_index();
+        assert_eq!(presentation.slide_index_selection(), Some(1));
+
+        assert!(presentation.move_slide_index_cursor(CursorDirection::Down));
+        assert_eq!(presentation.slide_index_selection(), Some(2));
+        // Moving the cursor doesn't change the current slide.
+        assert_eq!(presentation.current_slide_index(), 1);
+
+        // The cursor stays in place when it still matches, and moves to the first match otherwise.
+        presentation.update_slide_index_filter(Some('c'));
+        assert_eq!(presentation.slide_index_selection(), Some(2));
+        presentation.update_slide_index_filter(Some('o'));
+        presentation.update_slide_index_filter(Some('d'));
+        assert_eq!(presentation.slide_index_selection(), Some(1));
+        assert!(!presentation.move_slide_index_cursor(CursorDirection::Down));
+
+        presentation.update_slide_index_filter(None);
+        presentation.update_slide_index_filter(None);
+        assert!(presentation.move_slide_index_cursor(CursorDirection::Down));
+        assert_eq!(presentation.slide_index_selection(), Some(2));
+
+        presentation.update_slide_index_filter(Some('x'));
+        assert_eq!(presentation.slide_index_selection(), None);
+    }
+
+    #[test]
+    fn search() {
+        let text = |text: &str| RenderOperation::RenderText { line: text.into(), alignment: Default::default() };
+        let mut presentation = Presentation::from(vec![
+            Slide::new(vec![SlideChunk::new(vec![text("Potatoes")], vec![])], vec![]),
+            Slide::new(
+                vec![SlideChunk::new(vec![text("bananas")], vec![]), SlideChunk::new(vec![text("potato")], vec![])],
+                vec![],
+            ),
+            Slide::new(vec![SlideChunk::new(vec![text("tomatoes")], vec![])], vec![]),
+        ]);
+        presentation.go_to_slide(1);
+        presentation.start_search();
+        "POTATO".chars().for_each(|c| {
+            presentation.update_search_query(Some(c));
+        });
+        assert!(presentation.search_highlight().is_some());
+
+        // The first match is the one after the current position.
+        assert!(presentation.confirm_search());
+        assert_eq!((presentation.current_slide_index(), presentation.current_chunk()), (1, 1));
+        assert!(presentation.next_search_match());
+        assert_eq!((presentation.current_slide_index(), presentation.current_chunk()), (0, 0));
+        assert!(presentation.previous_search_match());
+        assert_eq!((presentation.current_slide_index(), presentation.current_chunk()), (1, 1));
+
+        assert!(presentation.clear_search());
+        assert!(presentation.search_highlight().is_none());
+        assert!(!presentation.next_search_match());
+    }
 }
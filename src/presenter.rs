@@ -2,23 +2,27 @@ use crate::{
     custom::KeyBindingsConfig,
     diff::PresentationDiffer,
     execute::SnippetExecutor,
-    export::ImageReplacer,
-    input::source::{Command, CommandSource},
-    markdown::parse::{MarkdownParser, ParseError},
+    input::{
+        source::{Command, CommandSource, SearchAction, SlideIndexAction},
+        user::InputMode,
+    },
+    markdown::parse::MarkdownParser,
     media::{printer::ImagePrinter, register::ImageRegistry},
     presentation::{Presentation, RenderAsyncState},
-    processing::builder::{BuildError, PresentationBuilder, PresentationBuilderOptions, Themes},
+    processing::builder::{BuildError, PresentationBuilder, PresentationBuilderOptions, SlideError, Themes},
     render::{
         draw::{ErrorSource, RenderError, RenderResult, TerminalDrawer},
         properties::WindowSize,
         validate::OverflowValidator,
     },
     resource::Resources,
+    speaker::{PresentationStatus, publisher::StatusPublisher, socket_path, unix_time},
     theme::PresentationTheme,
     third_party::ThirdPartyRender,
 };
+use serde::Deserialize;
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     fmt::Display,
     fs,
     io::{self, Stdout},
@@ -35,6 +39,7 @@ pub struct PresenterOptions {
     pub font_size_fallback: u8,
     pub bindings: KeyBindingsConfig,
     pub validate_overflows: bool,
+    pub mouse_scroll: bool,
 }
 
 /// A slideshow presenter.
@@ -52,6 +57,9 @@ pub struct Presenter<'a> {
     image_printer: Arc<ImagePrinter>,
     themes: Themes,
     options: PresenterOptions,
+    publisher: Option<StatusPublisher>,
+    started_at: u64,
+    scroll: SlideScroll,
 }
 
 impl<'a> Presenter<'a> {
@@ -80,6 +88,9 @@ impl<'a> Presenter<'a> {
             image_printer,
             themes,
             options,
+            publisher: None,
+            started_at: unix_time(),
+            scroll: Default::default(),
         }
     }
 
@@ -88,11 +99,18 @@ impl<'a> Presenter<'a> {
         if matches!(self.options.mode, PresentMode::Development) {
             self.resources.watch_presentation_file(path.to_path_buf());
         }
+        if !matches!(self.options.mode, PresentMode::Export) {
+            // The presenter view is optional so failing to publish the status is not an error.
+            self.publisher = StatusPublisher::new(socket_path(path)).ok();
+        }
         self.state = PresenterState::Presenting(Presentation::from(vec![]));
         self.try_reload(path, true);
 
         let mut drawer =
             TerminalDrawer::new(io::stdout(), self.image_printer.clone(), self.options.font_size_fallback)?;
+        if self.options.mouse_scroll {
+            drawer.terminal.enable_mouse_capture()?;
+        }
         loop {
             if matches!(self.options.mode, PresentMode::Export) {
                 if let PresenterState::Failure { error, .. } = &self.state {
@@ -104,10 +122,26 @@ impl<'a> Presenter<'a> {
             self.render(&mut drawer)?;
 
             loop {
+                self.publish_status();
                 if self.poll_async_renders()? {
                     self.render(&mut drawer)?;
                 }
 
+                let input_mode = match &self.state {
+                    PresenterState::SlideIndex(_) => InputMode::SlideIndex,
+                    PresenterState::Presenting(presentation) => {
+                        let search = presentation.state.search();
+                        if search.editing {
+                            InputMode::SearchQuery
+                        } else if search.is_active() {
+                            InputMode::SearchResults
+                        } else {
+                            InputMode::Presentation
+                        }
+                    }
+                    _ => InputMode::Presentation,
+                };
+                self.commands.set_input_mode(input_mode);
                 let command = match self.commands.try_next_command()? {
                     Some(command) => command,
                     _ => match self.resources.resources_modified() {
@@ -121,7 +155,10 @@ impl<'a> Presenter<'a> {
                     },
                 };
                 match self.apply_command(command) {
-                    CommandSideEffect::Exit => return Ok(()),
+                    CommandSideEffect::Exit => {
+                        self.publish_status();
+                        return Ok(());
+                    }
                     CommandSideEffect::Suspend => {
                         self.suspend(&mut drawer);
                         break;
@@ -157,6 +194,14 @@ impl<'a> Presenter<'a> {
         }
     }
 
+    fn publish_status(&mut self) {
+        let status = PresentationStatus::new(self.state.presentation(), self.started_at);
+        self.commands.reply(status.as_ref());
+        if let (Some(publisher), Some(status)) = (&mut self.publisher, &status) {
+            publisher.publish(status);
+        }
+    }
+
     fn poll_async_renders(&mut self) -> Result<bool, RenderError> {
         if matches!(self.state, PresenterState::Failure { .. }) {
             return Ok(false);
@@ -180,13 +225,19 @@ impl<'a> Presenter<'a> {
 
     fn render(&mut self, drawer: &mut TerminalDrawer<Stdout>) -> RenderResult {
         let result = match &self.state {
-            PresenterState::Presenting(presentation) => drawer.render_slide(presentation),
+            PresenterState::Presenting(presentation) => {
+                Self::render_slide(drawer, presentation, &mut self.scroll)?;
+                if presentation.state.search().is_active() {
+                    drawer.render_search_bar(presentation)?;
+                }
+                Ok(())
+            }
             PresenterState::SlideIndex(presentation) => {
-                drawer.render_slide(presentation)?;
+                Self::render_slide(drawer, presentation, &mut self.scroll)?;
                 drawer.render_slide_index(presentation)
             }
             PresenterState::KeyBindings(presentation) => {
-                drawer.render_slide(presentation)?;
+                Self::render_slide(drawer, presentation, &mut self.scroll)?;
                 drawer.render_key_bindings(presentation)
             }
             PresenterState::Failure { error, source, .. } => drawer.render_error(error, source),
@@ -197,6 +248,20 @@ impl<'a> Presenter<'a> {
         if matches!(result, Err(RenderError::TerminalTooSmall)) { Ok(()) } else { result }
     }
 
+    fn render_slide(
+        drawer: &mut TerminalDrawer<Stdout>,
+        presentation: &Presentation,
+        scroll: &mut SlideScroll,
+    ) -> RenderResult {
+        let slide = presentation.current_slide_index();
+        let max_offset = drawer.render_slide(presentation, scroll.offset(slide))?;
+        // The slide may have shrunk, or the terminal grown, since it was scrolled.
+        if scroll.set_max_offset(slide, max_offset) {
+            drawer.render_slide(presentation, scroll.offset(slide))?;
+        }
+        Ok(())
+    }
+
     fn apply_command(&mut self, command: Command) -> CommandSideEffect {
         // These ones always happens no matter our state.
         match command {
@@ -221,6 +286,22 @@ impl<'a> Presenter<'a> {
             return CommandSideEffect::Redraw;
         }
 
+        if let Command::SlideIndex(action) = command {
+            return self.apply_slide_index_action(action);
+        }
+        // The slides that could be built can still be presented once the errors are dismissed.
+        if let (Command::CloseModal, PresenterState::Failure { mode: FailureMode::BrokenSlides, .. }) =
+            (&command, &self.state)
+        {
+            let presentation = mem::take(&mut self.state).into_presentation();
+            self.state = self.validate_overflows(presentation);
+            return CommandSideEffect::Redraw;
+        }
+        // Without any modal open, closing one means cancelling the search.
+        if let (Command::CloseModal, PresenterState::Presenting(presentation)) = (&command, &mut self.state) {
+            return if presentation.clear_search() { CommandSideEffect::Redraw } else { CommandSideEffect::None };
+        }
+
         // Now apply the commands that require a presentation.
         let presentation = match &mut self.state {
             PresenterState::Presenting(presentation)
@@ -246,6 +327,7 @@ impl<'a> Presenter<'a> {
                     return CommandSideEffect::None;
                 }
             }
+            Command::CancelAsyncOperations => presentation.cancel_slide_async_renders(),
             Command::ToggleSlideIndex => {
                 self.toggle_slide_index();
                 true
@@ -259,14 +341,55 @@ impl<'a> Presenter<'a> {
                 self.state = PresenterState::Presenting(presentation);
                 true
             }
+            Command::StartSearch => {
+                presentation.start_search();
+                let presentation = mem::take(&mut self.state).into_presentation();
+                self.state = PresenterState::Presenting(presentation);
+                true
+            }
+            Command::ScrollUp => self.scroll.scroll_up(presentation.current_slide_index()),
+            Command::ScrollDown => self.scroll.scroll_down(presentation.current_slide_index()),
+            Command::Search(action) => match action {
+                SearchAction::Type(character) => presentation.update_search_query(Some(character)),
+                SearchAction::Erase => presentation.update_search_query(None),
+                SearchAction::Confirm => presentation.confirm_search(),
+                SearchAction::NextMatch => presentation.next_search_match(),
+                SearchAction::PreviousMatch => presentation.previous_search_match(),
+            },
             // These are handled above as they don't require the presentation
-            Command::Reload | Command::HardReload | Command::Exit | Command::Suspend | Command::Redraw => {
+            Command::Reload
+            | Command::HardReload
+            | Command::Exit
+            | Command::Suspend
+            | Command::Redraw
+            | Command::SlideIndex(_) => {
                 panic!("unreachable commands")
             }
         };
         if needs_redraw { CommandSideEffect::Redraw } else { CommandSideEffect::None }
     }
 
+    fn apply_slide_index_action(&mut self, action: SlideIndexAction) -> CommandSideEffect {
+        let PresenterState::SlideIndex(presentation) = &mut self.state else {
+            return CommandSideEffect::None;
+        };
+        let needs_redraw = match action {
+            SlideIndexAction::MoveCursor(direction) => presentation.move_slide_index_cursor(direction),
+            SlideIndexAction::Type(character) => presentation.update_slide_index_filter(Some(character)),
+            SlideIndexAction::Erase => presentation.update_slide_index_filter(None),
+            SlideIndexAction::Select => match presentation.slide_index_selection() {
+                Some(slide) => {
+                    presentation.go_to_slide(slide);
+                    let presentation = mem::take(&mut self.state).into_presentation();
+                    self.state = PresenterState::Presenting(presentation);
+                    true
+                }
+                None => false,
+            },
+        };
+        if needs_redraw { CommandSideEffect::Redraw } else { CommandSideEffect::None }
+    }
+
     fn try_reload(&mut self, path: &Path, force: bool) {
         if matches!(self.options.mode, PresentMode::Presentation) && !force {
             return;
@@ -274,7 +397,7 @@ impl<'a> Presenter<'a> {
         self.slides_with_pending_async_renders.clear();
         self.resources.clear_watches();
         match self.load_presentation(path) {
-            Ok(mut presentation) => {
+            Ok((mut presentation, errors)) => {
                 let current = self.state.presentation();
                 if let Some(modification) = PresentationDiffer::find_first_modification(current, &presentation) {
                     presentation.go_to_slide(modification.slide_index);
@@ -292,7 +415,13 @@ impl<'a> Presenter<'a> {
                     PresentMode::Export => presentation.trigger_all_async_renders(),
                 };
 
-                self.state = self.validate_overflows(presentation);
+                self.state = match errors.is_empty() {
+                    true => self.validate_overflows(presentation),
+                    false => {
+                        let error = Self::describe_slide_errors(path, &errors);
+                        PresenterState::failure(error, presentation, ErrorSource::Slides, FailureMode::BrokenSlides)
+                    }
+                };
             }
             Err(e) => {
                 let presentation = mem::take(&mut self.state).into_presentation();
@@ -302,7 +431,19 @@ impl<'a> Presenter<'a> {
     }
 
     fn is_displaying_other_error(&self) -> bool {
-        matches!(self.state, PresenterState::Failure { mode: FailureMode::Other, .. })
+        matches!(self.state, PresenterState::Failure { mode: FailureMode::Other | FailureMode::BrokenSlides, .. })
+    }
+
+    fn describe_slide_errors(path: &Path, errors: &[SlideError]) -> String {
+        let path = path.display();
+        let lines: Vec<_> = errors
+            .iter()
+            .map(|SlideError { slide, source_position, error }| match slide {
+                Some(slide) => format!("{path}:{source_position}: slide {slide}: {error}"),
+                None => format!("{path}:{source_position}: {error}"),
+            })
+            .collect();
+        lines.join("\n")
     }
 
     fn validate_overflows(&self, presentation: Presentation) -> PresenterState {
@@ -322,11 +463,10 @@ impl<'a> Presenter<'a> {
         }
     }
 
-    fn load_presentation(&mut self, path: &Path) -> Result<Presentation, LoadPresentationError> {
+    fn load_presentation(&mut self, path: &Path) -> Result<(Presentation, Vec<SlideError>), LoadPresentationError> {
         let content = fs::read_to_string(path).map_err(LoadPresentationError::Reading)?;
-        let elements = self.parser.parse(&content)?;
-        let export_mode = matches!(self.options.mode, PresentMode::Export);
-        let mut presentation = PresentationBuilder::new(
+        let (elements, parse_errors) = self.parser.parse_all(&content);
+        let (presentation, errors) = PresentationBuilder::new(
             self.default_theme,
             &mut self.resources,
             &mut self.third_party,
@@ -336,18 +476,15 @@ impl<'a> Presenter<'a> {
             self.options.bindings.clone(),
             self.options.builder_options.clone(),
         )
-        .build(elements)?;
-        if export_mode {
-            ImageReplacer::default().replace_presentation_images(&mut presentation);
-        }
-
-        Ok(presentation)
+        .build_all(elements, parse_errors)?;
+        Ok((presentation, errors))
     }
 
     fn toggle_slide_index(&mut self) {
         let state = mem::take(&mut self.state);
         match state {
-            PresenterState::Presenting(presentation) | PresenterState::KeyBindings(presentation) => {
+            PresenterState::Presenting(mut presentation) | PresenterState::KeyBindings(mut presentation) => {
+                presentation.reset_slide_index();
                 self.state = PresenterState::SlideIndex(presentation)
             }
             PresenterState::SlideIndex(presentation) => self.state = PresenterState::Presenting(presentation),
@@ -442,27 +579,70 @@ impl PresenterState {
 
 enum FailureMode {
     Overflow,
+    BrokenSlides,
     Other,
 }
 
+/// The vertical scroll offset of every slide that doesn't fit on the screen.
+#[derive(Default)]
+struct SlideScroll {
+    offsets: HashMap<usize, u16>,
+    // The maximum offset for the slide that was last rendered.
+    max_offset: u16,
+}
+
+impl SlideScroll {
+    fn offset(&self, slide: usize) -> u16 {
+        self.offsets.get(&slide).copied().unwrap_or_default()
+    }
+
+    fn scroll_up(&mut self, slide: usize) -> bool {
+        let offset = self.offset(slide);
+        self.offsets.insert(slide, offset.saturating_sub(1));
+        offset > 0
+    }
+
+    fn scroll_down(&mut self, slide: usize) -> bool {
+        let offset = self.offset(slide);
+        if offset >= self.max_offset {
+            return false;
+        }
+        self.offsets.insert(slide, offset + 1);
+        true
+    }
+
+    /// Set the maximum offset for a slide that was just rendered, returning whether that caused its
+    /// offset to change.
+    fn set_max_offset(&mut self, slide: usize, max_offset: u16) -> bool {
+        self.max_offset = max_offset;
+        let offset = self.offset(slide);
+        if offset > max_offset {
+            self.offsets.insert(slide, max_offset);
+            true
+        } else {
+            false
+        }
+    }
+}
+
 /// This presentation mode.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum PresentMode {
     /// We are developing the presentation so we want live reloads when the input changes.
+    #[default]
     Development,
 
     /// This is a live presentation so we don't want hot reloading.
     Presentation,
 
-    /// We are running a presentation that's being consumed by `presenterm-export`.
+    /// We are exporting the presentation into a different format.
     Export,
 }
 
 /// An error when loading a presentation.
 #[derive(thiserror::Error, Debug)]
 pub enum LoadPresentationError {
-    #[error(transparent)]
-    Parse(#[from] ParseError),
-
     #[error("reading presentation: {0}")]
     Reading(io::Error),
 
@@ -1,5 +1,7 @@
 use super::{
-    code::{CodeBlockParser, CodeLine, ExternalFile, Highlight, HighlightGroup, Snippet, SnippetLanguage},
+    code::{
+        CodeBlockParser, CodeLine, ExternalFile, Highlight, HighlightGroup, Snippet, SnippetLanguage, SnippetStdin,
+    },
     execution::{DisplaySeparator, RunAcquireTerminalSnippet, SnippetExecutionDisabledOperation},
     modals::KeyBindingsModalBuilder,
 };
@@ -11,6 +13,7 @@ use crate::{
             Line, ListItem, ListItemType, MarkdownElement, Percent, PercentParseError, SourcePosition, Table, TableRow,
             Text,
         },
+        parse::{MarkdownParser, ParseError},
         text::WeightedLine,
     },
     media::{image::Image, printer::RegisterImageError, register::ImageRegistry},
@@ -19,12 +22,17 @@ use crate::{
         Presentation, PresentationMetadata, PresentationState, PresentationThemeMetadata, RenderAsync, RenderOperation,
         Slide, SlideBuilder, SlideChunk,
     },
+    presenter::PresentMode,
     processing::{
         code::{CodePreparer, HighlightContext, HighlightMutator, HighlightedLine},
+        contents::TableOfContentsGenerator,
         execution::RunSnippetOperation,
-        footer::{FooterContext, FooterGenerator},
-        modals::IndexBuilder,
+        footer::{FooterContext, FooterGenerator, FooterSection, FootnotesGenerator},
+        math::{MathSegment, RenderInlineMath},
+        modals::{IndexBuilder, IndexEntry, SearchBarBuilder},
         separator::RenderSeparator,
+        snippets::{is_expected_output, is_stdin},
+        table::TableGenerator,
     },
     render::{
         highlighting::{CodeHighlighter, HighlightThemeSet},
@@ -34,18 +42,31 @@ use crate::{
     style::{Color, Colors, TextStyle},
     theme::{
         Alignment, AuthorPositioning, CodeBlockStyle, ElementType, LoadThemeError, Margin, PresentationTheme,
-        PresentationThemeSet,
+        PresentationThemeSet, TypstStyle,
     },
     third_party::{ThirdPartyRender, ThirdPartyRenderError, ThirdPartyRenderRequest},
 };
+use comrak::Arena;
 use image::DynamicImage;
 use serde::Deserialize;
-use std::{borrow::Cow, cell::RefCell, fmt::Display, iter, mem, path::PathBuf, rc::Rc, str::FromStr};
+use std::{
+    borrow::Cow,
+    cell::RefCell,
+    collections::HashMap,
+    fmt::Display,
+    iter, mem,
+    path::{Path, PathBuf},
+    rc::Rc,
+    str::FromStr,
+};
 use unicode_width::UnicodeWidthStr;
 
 // TODO: move to a theme config.
 static DEFAULT_BOTTOM_SLIDE_MARGIN: u16 = 3;
 pub(crate) static DEFAULT_IMAGE_Z_INDEX: i32 = -2;
+static INLINE_MATH_MARGIN: u16 = 1;
+static DEFAULT_TASK_DONE_SYMBOL: &str = "☑";
+static DEFAULT_TASK_PENDING_SYMBOL: &str = "☐";
 
 #[derive(Default)]
 pub struct Themes {
@@ -66,15 +87,18 @@ pub struct PresentationBuilderOptions {
     pub strict_front_matter_parsing: bool,
     pub enable_snippet_execution: bool,
     pub enable_snippet_execution_replace: bool,
+    pub math: bool,
+    pub mode: PresentMode,
 }
 
 impl PresentationBuilderOptions {
-    fn merge(&mut self, options: OptionsConfig) {
+    pub(crate) fn merge(&mut self, options: OptionsConfig) {
         self.implicit_slide_ends = options.implicit_slide_ends.unwrap_or(self.implicit_slide_ends);
         self.incremental_lists = options.incremental_lists.unwrap_or(self.incremental_lists);
         self.end_slide_shorthand = options.end_slide_shorthand.unwrap_or(self.end_slide_shorthand);
         self.strict_front_matter_parsing =
             options.strict_front_matter_parsing.unwrap_or(self.strict_front_matter_parsing);
+        self.math = options.math.unwrap_or(self.math);
         if let Some(prefix) = options.command_prefix {
             self.command_prefix = prefix;
         }
@@ -98,6 +122,8 @@ impl Default for PresentationBuilderOptions {
             strict_front_matter_parsing: true,
             enable_snippet_execution: false,
             enable_snippet_execution_replace: false,
+            math: false,
+            mode: PresentMode::Development,
         }
     }
 }
@@ -110,13 +136,19 @@ pub(crate) struct PresentationBuilder<'a> {
     slide_chunks: Vec<SlideChunk>,
     chunk_operations: Vec<RenderOperation>,
     chunk_mutators: Vec<Box<dyn ChunkMutator>>,
+    chunk_source_positions: Vec<(usize, SourcePosition)>,
     slides: Vec<Slide>,
+    errors: Vec<SlideError>,
+    parse_errors: Vec<ParseError>,
     highlighter: CodeHighlighter,
     code_executor: Rc<SnippetExecutor>,
     theme: Cow<'a, PresentationTheme>,
     resources: &'a mut Resources,
     third_party: &'a mut ThirdPartyRender,
     slide_state: SlideState,
+    footnotes: HashMap<u32, Vec<Line>>,
+    included_files: Vec<IncludedFile>,
+    current_section: Option<String>,
     presentation_state: PresentationState,
     footer_context: Rc<RefCell<FooterContext>>,
     themes: &'a Themes,
@@ -143,13 +175,19 @@ impl<'a> PresentationBuilder<'a> {
             slide_chunks: Vec::new(),
             chunk_operations: Vec::new(),
             chunk_mutators: Vec::new(),
+            chunk_source_positions: Vec::new(),
             slides: Vec::new(),
+            errors: Vec::new(),
+            parse_errors: Vec::new(),
             highlighter: CodeHighlighter::default(),
             code_executor,
             theme: Cow::Borrowed(default_theme),
             resources,
             third_party,
             slide_state: Default::default(),
+            footnotes: Default::default(),
+            included_files: Default::default(),
+            current_section: None,
             presentation_state: Default::default(),
             footer_context: Default::default(),
             themes,
@@ -161,9 +199,30 @@ impl<'a> PresentationBuilder<'a> {
     }
 
     /// Build a presentation.
-    pub(crate) fn build(mut self, elements: Vec<MarkdownElement>) -> Result<Presentation, BuildError> {
+    ///
+    /// This fails if any of the slides in it can't be built.
+    pub(crate) fn build(self, elements: Vec<(MarkdownElement, SourcePosition)>) -> Result<Presentation, BuildError> {
+        let (presentation, errors) = self.build_all(elements, Vec::new())?;
+        match errors.into_iter().next() {
+            Some(error) => Err(error.error),
+            None => Ok(presentation),
+        }
+    }
+
+    /// Build a presentation, carrying on past the slides that can't be built.
+    ///
+    /// Errors that affect the entire presentation, like an invalid front matter, still make this
+    /// fail. Every other error, including the given ones that were found when parsing it, is
+    /// collected and the slide it's in is replaced by a placeholder that describes it.
+    pub(crate) fn build_all(
+        mut self,
+        elements: Vec<(MarkdownElement, SourcePosition)>,
+        parse_errors: Vec<ParseError>,
+    ) -> Result<(Presentation, Vec<SlideError>), BuildError> {
+        // These are taken from the back as we go through the elements.
+        self.parse_errors = parse_errors.into_iter().rev().collect();
         let mut skip_first = false;
-        if let Some(MarkdownElement::FrontMatter(contents)) = elements.first() {
+        if let Some((MarkdownElement::FrontMatter(contents), _)) = elements.first() {
             self.process_front_matter(contents)?;
             skip_first = true;
         }
@@ -171,23 +230,22 @@ impl<'a> PresentationBuilder<'a> {
         if skip_first {
             elements.next();
         }
+        let elements = self.take_footnote_definitions(elements);
 
         self.set_code_theme()?;
 
         if self.chunk_operations.is_empty() {
             self.push_slide_prelude();
         }
-        for element in elements {
-            self.slide_state.ignore_element_line_break = false;
-            self.process_element(element)?;
-            self.validate_last_operation()?;
-            if !self.slide_state.ignore_element_line_break {
-                self.push_line_break();
-            }
-        }
+        self.process_elements(elements);
+        self.take_parse_errors(usize::MAX);
         if !self.chunk_operations.is_empty() || !self.slide_chunks.is_empty() {
             self.terminate_slide();
         }
+        // Every slide could have been skipped but there always needs to be one to display.
+        if self.slides.is_empty() {
+            self.terminate_slide();
+        }
         self.footer_context.borrow_mut().total_slides = self.slides.len();
 
         let mut bindings_modal_builder = KeyBindingsModalBuilder::default();
@@ -199,9 +257,77 @@ impl<'a> PresentationBuilder<'a> {
 
         let slide_index = self.index_builder.build(&self.theme, self.presentation_state.clone());
         let bindings = bindings_modal_builder.build(&self.theme, &self.bindings_config);
-        let modals = Modals { slide_index, bindings };
+        let search_bar = SearchBarBuilder::build(&self.theme, self.presentation_state.clone());
+        let search_match_style = SearchBarBuilder::match_style(&self.theme);
+        let modals = Modals { slide_index, bindings, search_bar, search_match_style };
         let presentation = Presentation::new(self.slides, modals, self.presentation_state);
-        Ok(presentation)
+        Ok((presentation, self.errors))
+    }
+
+    fn process_elements(&mut self, elements: Vec<(MarkdownElement, SourcePosition)>) {
+        let mut elements = elements.into_iter().peekable();
+        while let Some((element, source_position)) = elements.next() {
+            // Included files are parsed on their own so only the presentation's errors are here.
+            if self.included_files.is_empty() {
+                self.take_parse_errors(source_position.start.line);
+            }
+            // Chunks can only point to lines in the presentation so use the include command for anything included.
+            let chunk_position = self.included_files.first().map(|file| file.source_position);
+            self.chunk_source_positions.push((self.chunk_operations.len(), chunk_position.unwrap_or(source_position)));
+            self.slide_state.ignore_element_line_break = false;
+            // A stdin block right after a snippet is its input rather than something to display.
+            let stdin = match element {
+                MarkdownElement::Snippet { .. } => elements
+                    .next_if(|(next, _)| matches!(next, MarkdownElement::Snippet { info, .. } if is_stdin(info)))
+                    .and_then(|(next, _)| match next {
+                        MarkdownElement::Snippet { code, .. } => Some(code),
+                        _ => None,
+                    }),
+                _ => None,
+            };
+            let result = self.process_element(element, stdin).and_then(|()| self.validate_last_operation());
+            if let Err(e) = result {
+                self.fail_slide(source_position, e);
+            }
+            if !self.slide_state.ignore_element_line_break {
+                self.push_line_break();
+            }
+        }
+    }
+
+    // Any element that failed to parse belongs to the slide that's being built when we get past
+    // the line it was in.
+    fn take_parse_errors(&mut self, line: usize) {
+        while self.parse_errors.last().is_some_and(|error| error.sourcepos.start.line < line) {
+            let error = self.parse_errors.pop().expect("no parse errors");
+            self.fail_slide(error.sourcepos, BuildError::Parse(error));
+        }
+    }
+
+    // Errors in included files point to where they happened in that file, while the slide error itself points
+    // to the include command in the presentation.
+    fn fail_slide(&mut self, mut source_position: SourcePosition, mut error: BuildError) {
+        for file in self.included_files.iter().rev() {
+            error = BuildError::IncludedFile { path: file.path.clone(), source_position, error: Box::new(error) };
+            source_position = file.source_position;
+        }
+        self.slide_state.errors.push((source_position, error));
+    }
+
+    // Footnote definitions are at the end of the document but the slides that reference them
+    // need them, so take them out first.
+    fn take_footnote_definitions<I>(&mut self, elements: I) -> Vec<(MarkdownElement, SourcePosition)>
+    where
+        I: Iterator<Item = (MarkdownElement, SourcePosition)>,
+    {
+        let (definitions, elements): (Vec<_>, Vec<_>) =
+            elements.partition(|(element, _)| matches!(element, MarkdownElement::FootnoteDefinition { .. }));
+        for (definition, _) in definitions {
+            if let MarkdownElement::FootnoteDefinition { number, contents } = definition {
+                self.footnotes.insert(number, contents);
+            }
+        }
+        elements
     }
 
     fn build_modal_background(&self) -> Result<Image, RegisterImageError> {
@@ -253,8 +379,11 @@ impl<'a> PresentationBuilder<'a> {
         self.push_line_break();
     }
 
-    fn process_element(&mut self, element: MarkdownElement) -> Result<(), BuildError> {
-        let should_clear_last = !matches!(element, MarkdownElement::List(_) | MarkdownElement::Comment { .. });
+    fn process_element(&mut self, element: MarkdownElement, stdin: Option<String>) -> Result<(), BuildError> {
+        let should_clear_last = !matches!(
+            element,
+            MarkdownElement::List(_) | MarkdownElement::Comment { .. } | MarkdownElement::FootnoteReferences(_)
+        );
         match element {
             // This one is processed before everything else as it affects how the rest of the
             // elements is rendered.
@@ -263,7 +392,13 @@ impl<'a> PresentationBuilder<'a> {
             MarkdownElement::Heading { level, text } => self.push_heading(level, text),
             MarkdownElement::Paragraph(elements) => self.push_paragraph(elements)?,
             MarkdownElement::List(elements) => self.push_list(elements),
-            MarkdownElement::Snippet { info, code, source_position } => self.push_code(info, code, source_position)?,
+            // These are only used when running snippets outside of a presentation.
+            MarkdownElement::Snippet { info, .. } if is_expected_output(&info) || is_stdin(&info) => {
+                self.slide_state.ignore_element_line_break = true
+            }
+            MarkdownElement::Snippet { info, code, source_position } => {
+                self.push_code(info, code, stdin, source_position)?
+            }
             MarkdownElement::Table(table) => self.push_table(table),
             MarkdownElement::ThematicBreak => self.process_thematic_break(),
             MarkdownElement::Comment { comment, source_position } => self.process_comment(comment, source_position)?,
@@ -271,6 +406,9 @@ impl<'a> PresentationBuilder<'a> {
             MarkdownElement::Image { path, title, source_position } => {
                 self.push_image_from_path(path, title, source_position)?
             }
+            MarkdownElement::FootnoteReferences(numbers) => self.process_footnote_references(numbers),
+            // These are taken out of the elements before processing them.
+            MarkdownElement::FootnoteDefinition { .. } => self.slide_state.ignore_element_line_break = true,
         };
         if should_clear_last {
             self.slide_state.last_element = LastElement::Other;
@@ -407,7 +545,7 @@ impl<'a> PresentationBuilder<'a> {
 
     fn process_comment(&mut self, comment: String, source_position: SourcePosition) -> Result<(), BuildError> {
         let comment = comment.trim();
-        if self.should_ignore_comment(comment) {
+        if Self::should_ignore_comment(comment, &self.options.command_prefix) {
             return Ok(());
         }
         let comment = comment.trim_start_matches(&self.options.command_prefix);
@@ -455,14 +593,83 @@ impl<'a> PresentationBuilder<'a> {
             CommentCommand::NoFooter => {
                 self.slide_state.ignore_footer = true;
             }
+            CommentCommand::SpeakerNote(note) => {
+                self.slide_state.speaker_notes.push(note.trim().to_string());
+            }
+            CommentCommand::Include(path) => self.process_include(path, source_position)?,
+            CommentCommand::Section(name) => {
+                let mut footer_context = self.footer_context.borrow_mut();
+                // Remember where we were so the section can be dropped if this slide ends up skipped.
+                self.slide_state.section_checkpoint.get_or_insert_with(|| SectionCheckpoint {
+                    sections: footer_context.sections.len(),
+                    current_section: self.current_section.clone(),
+                });
+                footer_context.sections.push(FooterSection { name: name.clone(), total_slides: 0 });
+                self.current_section = Some(name);
+            }
+            CommentCommand::TableOfContents => self.push_table_of_contents(None),
+            CommentCommand::SectionContents(section) => self.push_table_of_contents(Some(section)),
+            CommentCommand::SkipSlide => self.slide_state.skip_slide = true,
+            CommentCommand::Only(mode) => self.process_only(mode),
         };
         // Don't push line breaks for any comments.
         self.slide_state.ignore_element_line_break = true;
         Ok(())
     }
 
-    fn should_ignore_comment(&self, comment: &str) -> bool {
-        if comment.contains('\n') || !comment.starts_with(&self.options.command_prefix) {
+    fn process_include(&mut self, path: PathBuf, source_position: SourcePosition) -> Result<(), BuildError> {
+        let path = self.resolve_path(&path);
+        let make_error =
+            |path: &PathBuf, error: String| BuildError::IncludeFile { path: path.clone(), source_position, error };
+        let canonical_path = self.resources.canonicalize(&path).map_err(|e| make_error(&path, e.to_string()))?;
+        if self.included_files.iter().any(|file| file.canonical_path == canonical_path) {
+            return Err(make_error(&path, "file includes itself".into()));
+        }
+        let contents = self.resources.included_file(&path).map_err(|e| make_error(&path, e.to_string()))?;
+        // Keep numbering footnotes where the files that were already parsed left off.
+        let footnote_offset = self.footnotes.keys().max().copied().unwrap_or(0);
+        let arena = Arena::new();
+        let parser = MarkdownParser::new(&arena).with_footnote_offset(footnote_offset).with_math(self.options.math);
+        let (elements, parse_errors) = parser.parse_all(&contents);
+        if let Some((MarkdownElement::FrontMatter(_), position)) = elements.first() {
+            let error = BuildError::InvalidMetadata("front matter is only allowed in the presentation file".into());
+            return Err(BuildError::IncludedFile { path, source_position: *position, error: Box::new(error) });
+        }
+        let elements = self.take_footnote_definitions(elements.into_iter());
+
+        self.included_files.push(IncludedFile { path, canonical_path, source_position });
+        for error in parse_errors {
+            self.fail_slide(error.sourcepos, BuildError::Parse(error));
+        }
+        self.process_elements(elements);
+        self.included_files.pop();
+        Ok(())
+    }
+
+    // Paths in included files are relative to the file that contains them rather than the presentation.
+    fn resolve_path(&self, path: &Path) -> PathBuf {
+        match self.included_files.last().and_then(|file| file.path.parent()) {
+            Some(parent) => parent.join(path),
+            None => path.into(),
+        }
+    }
+
+    fn push_table_of_contents(&mut self, section: Option<String>) {
+        let generator = TableOfContentsGenerator::new(
+            self.index_builder.entries(),
+            section,
+            self.current_section.clone(),
+            self.theme.table_of_contents.clone(),
+        );
+        self.chunk_operations.push(RenderOperation::RenderDynamic(Rc::new(generator)));
+        // Leave some space after it, like we do for lists.
+        self.push_line_break();
+        self.slide_state.has_table_of_contents = true;
+    }
+
+    /// Whether a comment is meant for the reader of the file rather than being a command.
+    pub(crate) fn should_ignore_comment(comment: &str, command_prefix: &str) -> bool {
+        if comment.contains('\n') || !comment.starts_with(command_prefix) {
             // Ignore any multi line comment; those are assumed to be user comments
             // Ignore any line that doesn't start with the selected prefix.
             true
@@ -486,9 +693,39 @@ impl<'a> PresentationBuilder<'a> {
     fn process_pause(&mut self) {
         self.slide_state.last_chunk_ended_in_list = matches!(self.slide_state.last_element, LastElement::List { .. });
 
-        let chunk_operations = mem::take(&mut self.chunk_operations);
-        let mutators = mem::take(&mut self.chunk_mutators);
-        self.slide_chunks.push(SlideChunk::new(chunk_operations, mutators));
+        if let Some(chunk) = self.take_chunk() {
+            self.slide_chunks.push(chunk);
+        }
+    }
+
+    // Take the current chunk, leaving out anything that was meant for a different mode. If that
+    // leaves it empty then there's no chunk at all.
+    fn take_chunk(&mut self) -> Option<SlideChunk> {
+        let mut operations = mem::take(&mut self.chunk_operations);
+        let mut mutators = mem::take(&mut self.chunk_mutators);
+        let mut source_positions = mem::take(&mut self.chunk_source_positions);
+        if let Some(cutoff) = self.slide_state.chunk_cutoff.take() {
+            operations.truncate(cutoff.operations);
+            mutators.truncate(cutoff.mutators);
+            source_positions.retain(|(index, _)| *index < cutoff.operations);
+            if operations.is_empty() && mutators.is_empty() {
+                return None;
+            }
+        }
+        Some(SlideChunk::new(operations, mutators).with_source_positions(source_positions))
+    }
+
+    fn process_only(&mut self, mode: PresentMode) {
+        if mode == self.options.mode {
+            return;
+        }
+        // If nothing came before this in the slide, the entire slide is only meant for that mode.
+        if matches!(self.slide_state.last_element, LastElement::None) && self.slide_chunks.is_empty() {
+            self.slide_state.skip_slide = true;
+        } else if self.slide_state.chunk_cutoff.is_none() {
+            let cutoff = ChunkCutoff { operations: self.chunk_operations.len(), mutators: self.chunk_mutators.len() };
+            self.slide_state.chunk_cutoff = Some(cutoff);
+        }
     }
 
     fn push_slide_title(&mut self, mut text: Line) {
@@ -572,6 +809,7 @@ impl<'a> PresentationBuilder<'a> {
         title: String,
         source_position: SourcePosition,
     ) -> Result<(), BuildError> {
+        let path = self.resolve_path(&path);
         let image = self.resources.image(&path).map_err(|e| BuildError::LoadImage {
             path,
             source_position,
@@ -628,6 +866,7 @@ impl<'a> PresentationBuilder<'a> {
     fn push_list_item(&mut self, index: usize, item: ListItem) {
         let padding_length = (item.depth as usize + 1) * 3;
         let mut prefix: String = " ".repeat(padding_length);
+        let mut colors = Colors::default();
         match item.item_type {
             ListItemType::Unordered => {
                 let delimiter = match item.depth {
@@ -636,6 +875,7 @@ impl<'a> PresentationBuilder<'a> {
                     _ => '▪',
                 };
                 prefix.push(delimiter);
+                prefix.push_str("  ");
             }
             ListItemType::OrderedParens => {
                 prefix.push_str(&(index + 1).to_string());
@@ -645,12 +885,25 @@ impl<'a> PresentationBuilder<'a> {
                 prefix.push_str(&(index + 1).to_string());
                 prefix.push_str(". ");
             }
+            ListItemType::Task { checked } => {
+                let (style, default_symbol) = match checked {
+                    true => (&self.theme.task_list.done, DEFAULT_TASK_DONE_SYMBOL),
+                    false => (&self.theme.task_list.pending, DEFAULT_TASK_PENDING_SYMBOL),
+                };
+                prefix.push_str(style.symbol.as_deref().unwrap_or(default_symbol));
+                prefix.push_str("  ");
+                colors = style.colors;
+            }
         };
 
-        let prefix_length = prefix.len() as u16;
-        self.push_text(prefix.into(), ElementType::List);
+        let prefix_length = prefix.width() as u16;
+        self.push_text(Text::new(prefix, TextStyle::colored(colors)).into(), ElementType::List);
 
-        let text = item.contents;
+        let mut text = item.contents;
+        for chunk in &mut text.0 {
+            chunk.style.colors.foreground = chunk.style.colors.foreground.or(colors.foreground);
+            chunk.style.colors.background = chunk.style.colors.background.or(colors.background);
+        }
         self.push_aligned_text(text, Alignment::Left { margin: Margin::Fixed(prefix_length) });
         self.push_line_break();
         if item.depth == 0 {
@@ -675,6 +928,8 @@ impl<'a> PresentationBuilder<'a> {
                 text.style.colors = self.theme.block_quote.colors.base;
                 if text.style.is_code() {
                     text.style.colors = self.theme.inline_code.colors;
+                } else if text.style.is_footnote_reference() {
+                    text.style.colors = self.theme.footnotes.reference_colors;
                 }
             }
             self.chunk_operations.push(RenderOperation::RenderBlockLine(BlockLine {
@@ -691,6 +946,15 @@ impl<'a> PresentationBuilder<'a> {
         self.chunk_operations.push(RenderOperation::SetColors(self.theme.default_style.colors));
     }
 
+    fn process_footnote_references(&mut self, numbers: Vec<u32>) {
+        self.slide_state.ignore_element_line_break = true;
+        for number in numbers {
+            if !self.slide_state.footnotes.contains(&number) {
+                self.slide_state.footnotes.push(number);
+            }
+        }
+    }
+
     fn push_line(&mut self, text: Text, element_type: ElementType) {
         self.push_text(Line::from(text), element_type);
         self.push_line_break();
@@ -702,32 +966,92 @@ impl<'a> PresentationBuilder<'a> {
     }
 
     fn push_aligned_text(&mut self, mut block: Line, alignment: Alignment) {
-        for chunk in &mut block.0 {
-            if chunk.style.is_code() {
-                chunk.style.colors = self.theme.inline_code.colors;
-            }
+        self.apply_inline_colors(&mut block);
+        if block.0.is_empty() {
+            return;
         }
-        if !block.0.is_empty() {
+        let has_math = block.0.iter().any(|chunk| chunk.style.is_math());
+        if has_math && !self.image_registry.is_ascii() {
+            let operation = self.render_math(block, alignment);
+            self.chunk_operations.push(operation);
+        } else {
             self.chunk_operations
                 .push(RenderOperation::RenderText { line: WeightedLine::from(block), alignment: alignment.clone() });
         }
     }
 
+    fn apply_inline_colors(&self, line: &mut Line) {
+        for chunk in &mut line.0 {
+            // Formulas are displayed like code if they can't be rendered.
+            if chunk.style.is_code() || chunk.style.is_math() {
+                chunk.style.colors = self.theme.inline_code.colors;
+            } else if chunk.style.is_footnote_reference() {
+                chunk.style.colors = self.theme.footnotes.reference_colors;
+            }
+        }
+    }
+
+    fn render_math(&mut self, block: Line, alignment: Alignment) -> RenderOperation {
+        let error_holder = self.presentation_state.async_error_holder();
+        let slide = self.slides.len() + 1;
+        // A line that only contains display math is rendered as a block, just like a latex snippet.
+        if let [chunk] = block.0.as_slice() {
+            if chunk.content.starts_with("$$") {
+                let request = ThirdPartyRenderRequest::Math(chunk.content.clone(), self.theme.typst.clone());
+                let operation = self.third_party.render_async(request, &self.theme, error_holder, slide, None);
+                return RenderOperation::RenderAsync(operation);
+            }
+        }
+
+        // Inline formulas are drawn on top of the text so they can't use the typst block colors.
+        let foreground = self.theme.typst.colors.foreground.or(self.theme.default_style.colors.foreground);
+        let style = TypstStyle {
+            horizontal_margin: Some(INLINE_MATH_MARGIN),
+            vertical_margin: Some(INLINE_MATH_MARGIN),
+            colors: Colors { foreground: foreground.filter(|color| color.as_rgb().is_some()), background: None },
+        };
+        let mut segments = Vec::new();
+        for chunk in block.0 {
+            if chunk.style.is_math() {
+                let request = ThirdPartyRenderRequest::Math(chunk.content.clone(), style.clone());
+                let render = self.third_party.render_async(request, &self.theme, error_holder.clone(), slide, None);
+                segments.push(MathSegment::Formula { source: chunk, render });
+            } else {
+                segments.push(MathSegment::Text(chunk));
+            }
+        }
+        let background = self.theme.default_style.colors.background;
+        RenderOperation::RenderAsync(Rc::new(RenderInlineMath::new(segments, alignment, background)))
+    }
+
     fn push_line_break(&mut self) {
         self.chunk_operations.push(RenderOperation::RenderLineBreak);
     }
 
     fn push_line_breaks(&mut self, count: usize) {
-        self.chunk_operations.extend(iter::repeat(RenderOperation::RenderLineBreak).take(count));
+        self.chunk_operations.extend(iter::repeat_n(RenderOperation::RenderLineBreak, count));
     }
 
     fn push_differ(&mut self, text: String) {
         self.chunk_operations.push(RenderOperation::RenderDynamic(Rc::new(Differ(text))));
     }
 
-    fn push_code(&mut self, info: String, code: String, source_position: SourcePosition) -> Result<(), BuildError> {
+    fn push_code(
+        &mut self,
+        info: String,
+        code: String,
+        stdin: Option<String>,
+        source_position: SourcePosition,
+    ) -> Result<(), BuildError> {
         let mut snippet = CodeBlockParser::parse(info, code)
+            .and_then(|mut snippet| match stdin {
+                Some(stdin) => CodeBlockParser::set_stdin(&mut snippet, stdin).map(|()| snippet),
+                None => Ok(snippet),
+            })
             .map_err(|e| BuildError::InvalidCode { source_position, error: e.to_string() })?;
+        if let Some(SnippetStdin::File(path)) = &mut snippet.attributes.stdin {
+            *path = self.resolve_path(path);
+        }
         if matches!(snippet.language, SnippetLanguage::File) {
             snippet = self.load_external_snippet(snippet, source_position)?;
         }
@@ -779,7 +1103,7 @@ impl<'a> PresentationBuilder<'a> {
     ) -> Result<Snippet, BuildError> {
         let file: ExternalFile = serde_yaml::from_str(&code.contents)
             .map_err(|e| BuildError::InvalidCode { source_position, error: e.to_string() })?;
-        let path = file.path;
+        let path = self.resolve_path(&file.path);
         let path_display = path.display();
         let contents = self.resources.external_snippet(&path).map_err(|e| BuildError::InvalidCode {
             source_position,
@@ -878,6 +1202,9 @@ impl<'a> PresentationBuilder<'a> {
         if !self.code_executor.is_execution_supported(&code.language) {
             return Err(BuildError::UnsupportedExecution(code.language));
         }
+        if code.attributes.session.is_some() && !self.code_executor.is_session_supported(&code.language) {
+            return Err(BuildError::UnsupportedSession(code.language));
+        }
         if code.attributes.acquire_terminal {
             let block_length = block_length as u16;
             let block_length = match self.theme.code.alignment.clone().unwrap_or_default() {
@@ -922,89 +1249,162 @@ impl<'a> PresentationBuilder<'a> {
     }
 
     fn terminate_slide(&mut self) {
+        if self.slide_state.skip_slide {
+            self.discard_slide();
+            return;
+        }
+        if !self.slide_state.errors.is_empty() {
+            self.push_broken_slide_placeholder();
+        }
         let footer = self.generate_footer();
 
-        let operations = mem::take(&mut self.chunk_operations);
-        let mutators = mem::take(&mut self.chunk_mutators);
-        self.slide_chunks.push(SlideChunk::new(operations, mutators));
+        if let Some(chunk) = self.take_chunk() {
+            self.slide_chunks.push(chunk);
+        }
 
         let chunks = mem::take(&mut self.slide_chunks);
-        let slide = SlideBuilder::default().chunks(chunks).footer(footer).build();
-        self.index_builder.add_title(self.slide_state.title.take().unwrap_or_else(|| Text::from("<no title>").into()));
+        let has_title = self.slide_state.title.is_some();
+        let title = self.slide_state.title.take().unwrap_or_else(|| Text::from("<no title>").into());
+        let speaker_notes = mem::take(&mut self.slide_state.speaker_notes);
+        let slide = SlideBuilder::default()
+            .chunks(chunks)
+            .footer(footer)
+            .title(title.text())
+            .speaker_notes(speaker_notes)
+            .build();
+        self.index_builder.add_entry(IndexEntry {
+            title,
+            has_title,
+            section: self.current_section.clone(),
+            has_table_of_contents: self.slide_state.has_table_of_contents,
+        });
+        if self.current_section.is_some() {
+            if let Some(section) = self.footer_context.borrow_mut().sections.last_mut() {
+                section.total_slides += 1;
+            }
+        }
         self.slides.push(slide);
+        self.take_slide_errors(Some(self.slides.len()));
+        self.start_slide();
+    }
+
+    fn discard_slide(&mut self) {
+        if let Some(checkpoint) = self.slide_state.section_checkpoint.take() {
+            self.footer_context.borrow_mut().sections.truncate(checkpoint.sections);
+            self.current_section = checkpoint.current_section;
+        }
+        self.chunk_operations.clear();
+        self.chunk_mutators.clear();
+        self.chunk_source_positions.clear();
+        self.slide_chunks.clear();
+        self.take_slide_errors(None);
+        self.start_slide();
+    }
+
+    fn take_slide_errors(&mut self, slide: Option<usize>) {
+        let errors = mem::take(&mut self.slide_state.errors);
+        self.errors.extend(errors.into_iter().map(|(source_position, error)| SlideError {
+            slide,
+            source_position,
+            error,
+        }));
+    }
+
+    // Anything that was built for a slide that has errors is likely broken so it's replaced by a
+    // list of those errors.
+    fn push_broken_slide_placeholder(&mut self) {
+        self.chunk_operations.clear();
+        self.chunk_mutators.clear();
+        self.chunk_source_positions.clear();
+        self.slide_chunks.clear();
+        self.slide_state.chunk_cutoff = None;
+        self.push_slide_prelude();
+
+        let colors = self.theme.execution_output.status.failure;
+        self.push_line(
+            Text::new("This slide could not be built", TextStyle::colored(colors).bold()),
+            ElementType::Paragraph,
+        );
+        self.push_line_break();
+        let errors: Vec<_> =
+            self.slide_state.errors.iter().map(|(position, error)| (*position, error.to_string())).collect();
+        for (position, error) in errors {
+            self.chunk_source_positions.push((self.chunk_operations.len(), position));
+            let text = format!("line {}: {error}", position.start.line);
+            self.push_line(Text::new(text, TextStyle::colored(colors)), ElementType::Paragraph);
+        }
+    }
 
+    fn start_slide(&mut self) {
         self.push_slide_prelude();
         self.slide_state = Default::default();
         self.slide_state.last_element = LastElement::None;
     }
 
     fn generate_footer(&mut self) -> Vec<RenderOperation> {
+        let mut operations = self.generate_footnotes();
         if self.slide_state.ignore_footer {
-            return Vec::new();
+            return operations;
         }
+        // The current section, if any, is always the last one that was defined.
+        let section = self.current_section.as_ref().map(|_| self.footer_context.borrow().sections.len() - 1);
         let generator = FooterGenerator {
             style: self.theme.footer.clone().unwrap_or_default(),
             current_slide: self.slides.len(),
+            section,
             context: self.footer_context.clone(),
         };
-        vec![
+        operations.extend([
             // Exit any layout we're in so this gets rendered on a default screen size.
             RenderOperation::ExitLayout,
             // Pop the slide margin so we're at the terminal rect.
             RenderOperation::PopMargin,
             RenderOperation::RenderDynamic(Rc::new(generator)),
-        ]
-    }
-
-    fn push_table(&mut self, table: Table) {
-        let widths: Vec<_> = (0..table.columns())
-            .map(|column| table.iter_column(column).map(|text| text.width()).max().unwrap_or(0))
-            .collect();
-        let flattened_header = Self::prepare_table_row(table.header, &widths);
-        self.push_text(flattened_header, ElementType::Table);
-        self.push_line_break();
-
-        let mut separator = Line(Vec::new());
-        for (index, width) in widths.iter().enumerate() {
-            let mut contents = String::new();
-            let mut margin = 1;
-            if index > 0 {
-                contents.push('┼');
-                // Append an extra dash to have 1 column margin on both sides
-                if index < widths.len() - 1 {
-                    margin += 1;
+        ]);
+        operations
+    }
+
+    fn generate_footnotes(&self) -> Vec<RenderOperation> {
+        let mut lines = Vec::new();
+        for number in &self.slide_state.footnotes {
+            let Some(contents) = self.footnotes.get(number) else { continue };
+            for line in contents {
+                let mut line = line.clone();
+                for text in &mut line.0 {
+                    text.style.colors = if text.style.is_code() {
+                        self.theme.inline_code.colors
+                    } else if text.style.is_footnote_reference() {
+                        self.theme.footnotes.reference_colors
+                    } else {
+                        self.theme.footnotes.colors
+                    };
                 }
+                lines.push(WeightedLine::from(line));
             }
-            contents.extend(iter::repeat("─").take(*width + margin));
-            separator.0.push(Text::from(contents));
         }
-
-        self.push_text(separator, ElementType::Table);
-        self.push_line_break();
-
-        for row in table.rows {
-            let flattened_row = Self::prepare_table_row(row, &widths);
-            self.push_text(flattened_row, ElementType::Table);
-            self.push_line_break();
+        if lines.is_empty() {
+            return Vec::new();
         }
+        // These use the slide's margins so they're rendered before the footer pops them.
+        vec![RenderOperation::ExitLayout, RenderOperation::RenderDynamic(Rc::new(FootnotesGenerator { lines }))]
     }
 
-    fn prepare_table_row(row: TableRow, widths: &[usize]) -> Line {
-        let mut flattened_row = Line(Vec::new());
-        for (column, text) in row.0.into_iter().enumerate() {
-            if column > 0 {
-                flattened_row.0.push(Text::from(" │ "));
-            }
-            let text_length = text.width();
-            flattened_row.0.extend(text.0.into_iter());
+    fn push_table(&mut self, table: Table) {
+        let Table { header, rows, alignments } = table;
+        let header = self.prepare_table_row(header);
+        let rows = rows.into_iter().map(|row| self.prepare_table_row(row)).collect();
+        let generator = TableGenerator::new(header, rows, alignments, self.theme.table.clone());
+        self.chunk_operations.push(RenderOperation::RenderDynamic(Rc::new(generator)));
+    }
 
-            let cell_width = widths[column];
-            if text_length < cell_width {
-                let padding = " ".repeat(cell_width - text_length);
-                flattened_row.0.push(Text::from(padding));
-            }
-        }
-        flattened_row
+    fn prepare_table_row(&self, row: TableRow) -> Vec<WeightedLine> {
+        row.0
+            .into_iter()
+            .map(|mut cell| {
+                self.apply_inline_colors(&mut cell);
+                WeightedLine::from(cell)
+            })
+            .collect()
     }
 
     fn parse_image_attributes(
@@ -1049,6 +1449,40 @@ struct SlideState {
     incremental_lists: Option<bool>,
     layout: LayoutState,
     title: Option<Line>,
+    speaker_notes: Vec<String>,
+    footnotes: Vec<u32>,
+    has_table_of_contents: bool,
+    skip_slide: bool,
+    section_checkpoint: Option<SectionCheckpoint>,
+    chunk_cutoff: Option<ChunkCutoff>,
+    errors: Vec<(SourcePosition, BuildError)>,
+}
+
+/// The point in the current chunk after which everything is left out of the presentation.
+#[derive(Clone, Debug)]
+struct ChunkCutoff {
+    operations: usize,
+    mutators: usize,
+}
+
+/// The sections as they were before the current slide defined any.
+#[derive(Clone, Debug)]
+struct SectionCheckpoint {
+    sections: usize,
+    current_section: Option<String>,
+}
+
+/// A file that's being included into the presentation.
+#[derive(Debug)]
+struct IncludedFile {
+    /// The path to it, relative to the presentation.
+    path: PathBuf,
+
+    /// Its canonical path, used to detect cycles regardless of how it was referenced.
+    canonical_path: PathBuf,
+
+    /// The position of the command that included it, within the file that included it.
+    source_position: SourcePosition,
 }
 
 #[derive(Debug, Default)]
@@ -1121,6 +1555,31 @@ pub enum BuildError {
 
     #[error("language {0:?} does not support execution")]
     UnsupportedExecution(SnippetLanguage),
+
+    #[error("language {0:?} does not support sessions")]
+    UnsupportedSession(SnippetLanguage),
+
+    #[error("could not include '{path}' at {source_position}: {error}")]
+    IncludeFile { path: PathBuf, source_position: SourcePosition, error: String },
+
+    #[error("in included file '{path}' at {source_position}: {error}")]
+    IncludedFile { path: PathBuf, source_position: SourcePosition, error: Box<BuildError> },
+
+    #[error(transparent)]
+    Parse(#[from] ParseError),
+}
+
+/// An error that prevented a slide from being built.
+#[derive(Debug)]
+pub(crate) struct SlideError {
+    /// The number of the slide this error is in, if it's part of the presentation.
+    pub(crate) slide: Option<usize>,
+
+    /// The position in the presentation file of the element that caused this.
+    pub(crate) source_position: SourcePosition,
+
+    /// The error itself.
+    pub(crate) error: BuildError,
 }
 
 enum ExecutionMode {
@@ -1130,7 +1589,7 @@ enum ExecutionMode {
 
 #[derive(Debug, Clone, PartialEq, Deserialize)]
 #[serde(rename_all = "snake_case")]
-enum CommentCommand {
+pub(crate) enum CommentCommand {
     Pause,
     EndSlide,
     #[serde(alias = "newline")]
@@ -1144,6 +1603,13 @@ enum CommentCommand {
     JumpToMiddle,
     IncrementalLists(bool),
     NoFooter,
+    SpeakerNote(String),
+    Include(PathBuf),
+    Section(String),
+    TableOfContents,
+    SectionContents(String),
+    SkipSlide,
+    Only(PresentMode),
 }
 
 impl FromStr for CommentCommand {
@@ -1153,11 +1619,33 @@ impl FromStr for CommentCommand {
         #[derive(Deserialize)]
         struct CommandWrapper(#[serde(with = "serde_yaml::with::singleton_map")] CommentCommand);
 
+        if let Some(command) = Self::parse_text_command(s) {
+            return Ok(command);
+        }
         let wrapper = serde_yaml::from_str::<CommandWrapper>(s)?;
         Ok(wrapper.0)
     }
 }
 
+impl CommentCommand {
+    // Commands that take free form text, which can contain anything YAML would choke on like
+    // colons, so their argument is taken as is.
+    fn parse_text_command(input: &str) -> Option<Self> {
+        let (name, text) = input.trim().split_once(':')?;
+        let text = text.trim();
+        // YAML's quoted and block scalars are still handled by it, e.g. for multiline notes.
+        if text.is_empty() || text.starts_with(['"', '\'', '|', '>']) {
+            return None;
+        }
+        match name {
+            "speaker_note" => Some(Self::SpeakerNote(text.into())),
+            "section" => Some(Self::Section(text.into())),
+            "section_contents" => Some(Self::SectionContents(text.into())),
+            _ => None,
+        }
+    }
+}
+
 #[derive(thiserror::Error, Debug)]
 pub struct CommandParseError(#[from] serde_yaml::Error);
 
@@ -1224,7 +1712,7 @@ struct IndexedListItem {
 
 #[derive(serde::Deserialize)]
 #[serde(deny_unknown_fields)]
-struct StrictPresentationMetadata {
+pub(crate) struct StrictPresentationMetadata {
     #[serde(default)]
     title: Option<String>,
 
@@ -1294,7 +1782,9 @@ impl AsRenderOperations for Differ {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::markdown::elements::{ColumnAlignment, LineColumn};
     use rstest::rstest;
+    use std::fs;
 
     fn build_presentation(elements: Vec<MarkdownElement>) -> Presentation {
         try_build_presentation(elements).expect("build failed")
@@ -1315,6 +1805,13 @@ mod test {
         elements: Vec<MarkdownElement>,
         options: PresentationBuilderOptions,
     ) -> Result<Presentation, BuildError> {
+        with_builder(options, |builder| builder.build(positioned(elements)))
+    }
+
+    fn with_builder<T, F>(options: PresentationBuilderOptions, build: F) -> T
+    where
+        F: FnOnce(PresentationBuilder) -> T,
+    {
         let theme = PresentationTheme::default();
         let mut resources = Resources::new("/tmp", Default::default());
         let mut third_party = ThirdPartyRender::default();
@@ -1331,7 +1828,16 @@ mod test {
             bindings,
             options,
         );
-        builder.build(elements)
+        build(builder)
+    }
+
+    // Pretend every element sits on its own line.
+    fn positioned(elements: Vec<MarkdownElement>) -> Vec<(MarkdownElement, SourcePosition)> {
+        elements
+            .into_iter()
+            .enumerate()
+            .map(|(index, element)| (element, SourcePosition { start: LineColumn { line: index + 1, column: 1 } }))
+            .collect()
     }
 
     fn build_pause() -> MarkdownElement {
@@ -1360,7 +1866,7 @@ mod test {
             | JumpToBottomRow { .. }
             | InitColumnLayout { .. }
             | EnterColumn { .. }
-            | ExitLayout { .. }
+            | ExitLayout
             | ApplyMargin(_)
             | PopMargin => false,
             RenderText { .. }
@@ -1398,6 +1904,55 @@ mod test {
         extract_text_lines(&operations)
     }
 
+    #[test]
+    fn footnotes() {
+        let definition =
+            |number, text: &str| MarkdownElement::FootnoteDefinition { number, contents: vec![Line::from(text)] };
+        let elements = vec![
+            MarkdownElement::Paragraph(vec![Line::from("hi")]),
+            MarkdownElement::FootnoteReferences(vec![2]),
+            build_end_slide(),
+            MarkdownElement::Paragraph(vec![Line::from("bye")]),
+            definition(1, "first"),
+            definition(2, "second"),
+        ];
+        let presentation = build_presentation(elements);
+        let dimensions = WindowSize { rows: 10, columns: 20, width: 200, height: 200 };
+        let footnotes: Vec<_> = presentation
+            .iter_slides()
+            .map(|slide| {
+                let operations: Vec<_> = slide
+                    .iter_operations()
+                    .filter_map(|operation| match operation {
+                        RenderOperation::RenderDynamic(generator) => Some(generator.as_render_operations(&dimensions)),
+                        _ => None,
+                    })
+                    .flatten()
+                    .collect();
+                extract_text_lines(&operations)
+            })
+            .collect();
+        assert_eq!(footnotes, &[vec!["second".to_string()], vec![]]);
+    }
+
+    #[test]
+    fn speaker_notes() {
+        let elements = vec![
+            MarkdownElement::SetexHeading { text: Line::from("hello") },
+            MarkdownElement::Comment { comment: "speaker_note: first".into(), source_position: Default::default() },
+            build_pause(),
+            MarkdownElement::Comment { comment: "speaker_note: second".into(), source_position: Default::default() },
+            build_end_slide(),
+            MarkdownElement::SetexHeading { text: Line::from("bye") },
+        ];
+        let presentation = build_presentation(elements);
+        let slides: Vec<_> = presentation.iter_slides().collect();
+        assert_eq!(slides[0].title(), "hello");
+        assert_eq!(slides[0].speaker_notes(), &["first", "second"]);
+        assert_eq!(slides[1].title(), "bye");
+        assert!(slides[1].speaker_notes().is_empty());
+    }
+
     #[test]
     fn prelude_appears_once() {
         let elements = vec![
@@ -1439,18 +1994,244 @@ mod test {
         }
     }
 
+    // Expands dynamic operations so the text they generate is visible.
+    fn extract_dynamic_text_lines(slide: Slide, columns: u16) -> Vec<String> {
+        let dimensions = WindowSize { rows: 20, columns, width: columns * 10, height: 400 };
+        let operations: Vec<_> = slide
+            .into_operations()
+            .into_iter()
+            .flat_map(|operation| match operation {
+                RenderOperation::RenderDynamic(generator) => generator.as_render_operations(&dimensions),
+                operation => vec![operation],
+            })
+            .collect();
+        extract_text_lines(&operations)
+    }
+
+    fn extract_table_lines(table: Table, columns: u16) -> Vec<String> {
+        let slides = build_presentation(vec![MarkdownElement::Table(table)]).into_slides();
+        extract_dynamic_text_lines(slides.into_iter().next().unwrap(), columns)
+    }
+
     #[test]
     fn table() {
-        let elements = vec![MarkdownElement::Table(Table {
+        let table = Table {
             header: TableRow(vec![Line::from("key"), Line::from("value"), Line::from("other")]),
             rows: vec![TableRow(vec![Line::from("potato"), Line::from("bar"), Line::from("yes")])],
-        })];
-        let slides = build_presentation(elements).into_slides();
-        let lines = extract_slide_text_lines(slides.into_iter().next().unwrap());
+            alignments: vec![ColumnAlignment::Left; 3],
+        };
+        let lines = extract_table_lines(table, 100);
         let expected_lines = &["key    │ value │ other", "───────┼───────┼──────", "potato │ bar   │ yes  "];
         assert_eq!(lines, expected_lines);
     }
 
+    #[test]
+    fn table_alignments() {
+        let table = Table {
+            header: TableRow(vec![Line::from("left"), Line::from("center"), Line::from("right")]),
+            rows: vec![TableRow(vec![Line::from("a"), Line::from("b"), Line::from("c")])],
+            alignments: vec![ColumnAlignment::Left, ColumnAlignment::Center, ColumnAlignment::Right],
+        };
+        let lines = extract_table_lines(table, 100);
+        let expected_lines = &["left │ center │ right", "─────┼────────┼──────", "a    │   b    │     c"];
+        assert_eq!(lines, expected_lines);
+    }
+
+    #[test]
+    fn table_wrapping() {
+        let table = Table {
+            header: TableRow(vec![Line::from("id"), Line::from("description")]),
+            rows: vec![TableRow(vec![Line::from("1"), Line::from("a rather long piece of text")])],
+            alignments: vec![ColumnAlignment::Right, ColumnAlignment::Left],
+        };
+        // The default theme leaves no margin for tables, so there's 20 columns to work with.
+        let lines = extract_table_lines(table, 20);
+        let expected_lines =
+            &["id │ description    ", "───┼────────────────", " 1 │ a rather long  ", "   │ piece of text  "];
+        assert_eq!(lines, expected_lines);
+    }
+
+    #[test]
+    fn table_of_contents() {
+        let comment =
+            |comment: &str| MarkdownElement::Comment { comment: comment.into(), source_position: Default::default() };
+        let title = |title: &str| MarkdownElement::SetexHeading { text: Line::from(title) };
+        let elements = vec![
+            title("Agenda"),
+            comment("table_of_contents"),
+            build_end_slide(),
+            title("Preface"),
+            build_end_slide(),
+            comment("section: Intro"),
+            title("Hello"),
+            build_end_slide(),
+            MarkdownElement::Paragraph(vec![Line::from("untitled")]),
+            build_end_slide(),
+            comment("section: Details"),
+            title("Deep dive"),
+            comment("section_contents: Intro"),
+            build_end_slide(),
+            title("Summary"),
+        ];
+        let slides = build_presentation(elements).into_slides();
+        let lines: Vec<_> = slides.into_iter().map(|slide| extract_dynamic_text_lines(slide, 50)).collect();
+        assert_eq!(&lines[0][1..], &["   •Preface", "   •Intro", "      ◦Hello", "   •Details", "      ◦Summary"]);
+        assert_eq!(&lines[4][1..], &["   •Hello"]);
+    }
+
+    #[test]
+    fn footer_sections() {
+        let comment =
+            |comment: &str| MarkdownElement::Comment { comment: comment.into(), source_position: Default::default() };
+        let front_matter = r#"
+theme:
+  override:
+    footer:
+      style: template
+      left: "{section}:{section_number}:{slides_in_section}"
+"#;
+        let elements = vec![
+            MarkdownElement::FrontMatter(front_matter.into()),
+            build_end_slide(),
+            comment("section: Intro"),
+            build_end_slide(),
+            build_end_slide(),
+            comment("section: Details"),
+        ];
+        let slides = build_presentation(elements).into_slides();
+        let footers: Vec<_> =
+            slides.into_iter().map(|slide| extract_dynamic_text_lines(slide, 50).pop().unwrap_or_default()).collect();
+        assert_eq!(footers, &["::", "Intro:1:2", "Intro:1:2", "Details:2:1"]);
+    }
+
+    #[test]
+    fn skipped_slide_sections() {
+        let comment =
+            |comment: &str| MarkdownElement::Comment { comment: comment.into(), source_position: Default::default() };
+        let front_matter = r#"
+theme:
+  override:
+    footer:
+      style: template
+      left: "{section}:{section_number}:{slides_in_section}"
+"#;
+        let elements = vec![
+            MarkdownElement::FrontMatter(front_matter.into()),
+            comment("section: Backup"),
+            comment("skip_slide"),
+            build_end_slide(),
+            comment("section: Real"),
+        ];
+        let slides = build_presentation(elements).into_slides();
+        let footers: Vec<_> =
+            slides.into_iter().map(|slide| extract_dynamic_text_lines(slide, 50).pop().unwrap_or_default()).collect();
+        assert_eq!(footers, &["Real:1:1"]);
+    }
+
+    #[rstest]
+    #[case::skip_slide(PresentMode::Presentation, "skip_slide")]
+    #[case::only(PresentMode::Development, "only: export")]
+    fn all_slides_skipped(#[case] mode: PresentMode, #[case] command: &str) {
+        let elements = vec![
+            MarkdownElement::Comment { comment: command.into(), source_position: Default::default() },
+            MarkdownElement::Paragraph(vec![Line::from("hello")]),
+        ];
+        let options = PresentationBuilderOptions { mode, ..Default::default() };
+        let slides = build_presentation_with_options(elements, options).into_slides();
+        let lines: Vec<_> = slides.into_iter().map(extract_slide_text_lines).collect();
+        assert_eq!(lines, &[Vec::<String>::new()]);
+    }
+
+    #[rstest]
+    #[case::development(PresentMode::Development, vec![vec!["a"], vec!["b", "c"]])]
+    #[case::presentation(PresentMode::Presentation, vec![vec!["a"], vec!["b", "live", "c"]])]
+    #[case::export(PresentMode::Export, vec![vec!["a"], vec!["exported"], vec!["b", "c"]])]
+    fn conditional_slides(#[case] mode: PresentMode, #[case] expected: Vec<Vec<&str>>) {
+        let comment =
+            |comment: &str| MarkdownElement::Comment { comment: comment.into(), source_position: Default::default() };
+        let paragraph = |text: &str| MarkdownElement::Paragraph(vec![Line::from(text)]);
+        let elements = vec![
+            paragraph("a"),
+            build_end_slide(),
+            comment("skip_slide"),
+            paragraph("backup"),
+            build_end_slide(),
+            comment("only: export"),
+            paragraph("exported"),
+            build_end_slide(),
+            paragraph("b"),
+            build_pause(),
+            comment("only: presentation"),
+            paragraph("live"),
+            build_pause(),
+            paragraph("c"),
+        ];
+        let options = PresentationBuilderOptions { mode, ..Default::default() };
+        let slides = build_presentation_with_options(elements, options).into_slides();
+        let lines: Vec<_> = slides.into_iter().map(extract_slide_text_lines).collect();
+        assert_eq!(lines, expected);
+    }
+
+    #[test]
+    fn collect_slide_errors() {
+        let elements = vec![
+            MarkdownElement::Paragraph(vec!["hi".into()]),
+            build_column(0),
+            build_end_slide(),
+            MarkdownElement::Paragraph(vec!["fine".into()]),
+            build_end_slide(),
+            MarkdownElement::Paragraph(vec!["bye".into()]),
+        ];
+        let parse_error = MarkdownParser::new(&Arena::new()).parse("    unfenced\n").expect_err("parse succeeded");
+        let parse_error =
+            ParseError { sourcepos: SourcePosition { start: LineColumn { line: 7, column: 1 } }, ..parse_error };
+        let (presentation, errors) =
+            with_builder(Default::default(), |builder| builder.build_all(positioned(elements), vec![parse_error]))
+                .expect("build failed");
+
+        let errors: Vec<_> = errors.iter().map(|e| (e.slide, e.source_position.start.line)).collect();
+        assert_eq!(errors, &[(Some(1), 2), (Some(3), 7)]);
+
+        let slides: Vec<_> = presentation.into_slides().into_iter().map(extract_slide_text_lines).collect();
+        assert_eq!(slides[0][0], "This slide could not be built");
+        assert_eq!(slides[1], &["fine"]);
+        assert_eq!(slides[2][0], "This slide could not be built");
+    }
+
+    #[test]
+    fn source_positions() {
+        let elements = vec![
+            MarkdownElement::Paragraph(vec!["hi".into()]),
+            build_pause(),
+            MarkdownElement::Paragraph(vec!["bye".into()]),
+        ];
+        let presentation = build_presentation(elements);
+        let slide = presentation.iter_slides().next().expect("no slides");
+        let lines: Vec<_> = slide
+            .iter_operations()
+            .enumerate()
+            .filter(|(_, operation)| matches!(operation, RenderOperation::RenderText { .. }))
+            .map(|(index, _)| slide.source_position(index).map(|position| position.start.line))
+            .collect();
+        assert_eq!(lines, &[Some(1), Some(3)]);
+    }
+
+    #[test]
+    fn conditional_chunks() {
+        let comment =
+            |comment: &str| MarkdownElement::Comment { comment: comment.into(), source_position: Default::default() };
+        let elements = vec![
+            MarkdownElement::Paragraph(vec![Line::from("a")]),
+            build_pause(),
+            comment("only: export"),
+            MarkdownElement::Paragraph(vec![Line::from("b")]),
+            build_pause(),
+            MarkdownElement::Paragraph(vec![Line::from("c")]),
+        ];
+        let slides = build_presentation(elements).into_slides();
+        assert_eq!(slides[0].iter_chunks().count(), 2);
+    }
+
     #[test]
     fn layout_without_init() {
         let elements = vec![build_column(0)];
@@ -1512,6 +2293,24 @@ mod test {
     #[case::incremental_lists("newlines: 2", CommentCommand::NewLines(2))]
     #[case::incremental_lists("new_line", CommentCommand::NewLine)]
     #[case::incremental_lists("newline", CommentCommand::NewLine)]
+    #[case::speaker_note("speaker_note: remember this", CommentCommand::SpeakerNote("remember this".into()))]
+    #[case::speaker_note_colon(
+        "speaker_note: Remember: pause here",
+        CommentCommand::SpeakerNote("Remember: pause here".into())
+    )]
+    #[case::speaker_note_multiline("speaker_note: |\n  first\n  second", CommentCommand::SpeakerNote("first\nsecond".into()))]
+    #[case::speaker_note_quoted(r#"speaker_note: "a # b""#, CommentCommand::SpeakerNote("a # b".into()))]
+    #[case::include("include: intro.md", CommentCommand::Include("intro.md".into()))]
+    #[case::section("section: Intro", CommentCommand::Section("Intro".into()))]
+    #[case::table_of_contents("table_of_contents", CommentCommand::TableOfContents)]
+    #[case::section_contents("section_contents: Intro", CommentCommand::SectionContents("Intro".into()))]
+    #[case::section_colon("section: Part 1: Intro", CommentCommand::Section("Part 1: Intro".into()))]
+    #[case::section_contents_colon(
+        "section_contents: Part 1: Intro",
+        CommentCommand::SectionContents("Part 1: Intro".into())
+    )]
+    #[case::skip_slide("skip_slide", CommentCommand::SkipSlide)]
+    #[case::only("only: export", CommentCommand::Only(PresentMode::Export))]
     fn command_formatting(#[case] input: &str, #[case] expected: CommentCommand) {
         let parsed: CommentCommand = input.parse().expect("deserialization failed");
         assert_eq!(parsed, expected);
@@ -1593,6 +2392,30 @@ mod test {
         assert_eq!(lines, expected_lines);
     }
 
+    #[test]
+    fn math_without_graphics() {
+        let line = Line(vec![Text::from("x is "), Text::new("$x^2$", TextStyle::default().math())]);
+        let elements = vec![MarkdownElement::Paragraph(vec![line])];
+        let slides = build_presentation(elements).into_slides();
+        let operations: Vec<_> = slides[0].iter_operations().collect();
+        assert!(!operations.iter().any(|operation| matches!(operation, RenderOperation::RenderAsync(_))));
+
+        let lines = extract_slide_text_lines(slides.into_iter().next().unwrap());
+        assert_eq!(lines, &["x is $x^2$"]);
+    }
+
+    #[test]
+    fn task_list() {
+        let elements = vec![MarkdownElement::List(vec![
+            ListItem { depth: 0, contents: "done".into(), item_type: ListItemType::Task { checked: true } },
+            ListItem { depth: 1, contents: "pending".into(), item_type: ListItemType::Task { checked: false } },
+        ])];
+        let slides = build_presentation(elements).into_slides();
+        let lines = extract_slide_text_lines(slides.into_iter().next().unwrap());
+        let expected_lines = &["   ☑  done", "      ☐  pending"];
+        assert_eq!(lines, expected_lines);
+    }
+
     #[test]
     fn automatic_pauses() {
         let elements = vec![
@@ -1714,6 +2537,23 @@ mod test {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn snippet_stdin() {
+        let snippet = |info: &str, code: &str| MarkdownElement::Snippet {
+            info: info.into(),
+            code: code.into(),
+            source_position: Default::default(),
+        };
+        let elements = vec![snippet("bash +exec", "cat"), snippet("stdin", "potato")];
+        let presentation = build_presentation(elements);
+        let lines = extract_slide_text_lines(presentation.into_slides().into_iter().next().unwrap());
+        assert!(!lines.iter().any(|line| line.contains("potato")), "{lines:?}");
+
+        let elements = vec![snippet("bash", "cat"), snippet("stdin", "potato")];
+        let result = try_build_presentation(elements);
+        assert!(matches!(result, Err(BuildError::InvalidCode { .. })), "{result:?}");
+    }
+
     #[rstest]
     #[case::enabled(true)]
     #[case::disabled(false)]
@@ -1772,6 +2612,101 @@ mod test {
         assert_eq!(attributes.width, expectation.map(Percent));
     }
 
+    fn include_file(contents: &str) -> (tempfile::NamedTempFile, MarkdownElement) {
+        let mut file = tempfile::NamedTempFile::new().expect("failed to create tempfile");
+        let name = file.path().file_name().expect("no file name").to_string_lossy().to_string();
+        let contents = contents.replace("{self}", &name);
+        std::io::Write::write_all(&mut file, contents.as_bytes()).expect("write failed");
+        let element =
+            MarkdownElement::Comment { comment: format!("include: {name}"), source_position: Default::default() };
+        (file, element)
+    }
+
+    #[test]
+    fn include() {
+        let (_file, include) = include_file("hello\n\n<!-- end_slide -->\n\nbye\n");
+        let elements = vec![
+            MarkdownElement::Paragraph(vec![Line::from("before")]),
+            include,
+            MarkdownElement::Paragraph(vec![Line::from("after")]),
+        ];
+        let slides = build_presentation(elements).into_slides();
+        let lines: Vec<_> = slides.into_iter().map(extract_slide_text_lines).collect();
+        assert_eq!(lines, &[vec!["before", "hello"], vec!["bye", "after"]]);
+    }
+
+    #[rstest]
+    #[case::invalid_command("hi\n\n<!-- potato -->\n", "invalid command at 3:1")]
+    #[case::parse_error("    unfenced code\n", "parse error at 1:5")]
+    #[case::front_matter("---\ntitle: hi\n---\n", "front matter")]
+    #[case::recursive("<!-- include: {self} -->\n", "file includes itself")]
+    fn include_errors(#[case] contents: &str, #[case] expected: &str) {
+        let (file, include) = include_file(contents);
+        let error = try_build_presentation(vec![include]).expect_err("build succeeded");
+        let name = file.path().file_name().unwrap().to_string_lossy();
+        let message = error.to_string();
+        assert!(message.starts_with(&format!("in included file '{name}'")), "{message}");
+        assert!(message.contains(expected), "{message}");
+    }
+
+    #[test]
+    fn include_error_positions() {
+        let (file, include) = include_file("hi\n\n<!-- column: 0 -->\n");
+        let MarkdownElement::Comment { comment, .. } = include else { panic!("not a comment") };
+        let source_position = SourcePosition { start: LineColumn { line: 2, column: 1 } };
+        let elements = vec![MarkdownElement::Paragraph(vec![Line::from("before")]), MarkdownElement::Comment {
+            comment,
+            source_position,
+        }];
+        let (_, errors) = with_builder(Default::default(), |builder| builder.build_all(positioned(elements), vec![]))
+            .expect("build failed");
+
+        let [error] = errors.as_slice() else { panic!("unexpected errors: {errors:?}") };
+        assert_eq!(error.source_position.start.line, 2);
+        let name = file.path().file_name().unwrap().to_string_lossy();
+        let message = error.error.to_string();
+        assert!(message.starts_with(&format!("in included file '{name}' at 3:1: ")), "{message}");
+    }
+
+    #[test]
+    fn nested_include_relative_to_includer() {
+        let dir = tempfile::tempdir_in("/tmp").expect("failed to create tempdir");
+        let chapter = dir.path().join("chapter");
+        fs::create_dir(&chapter).expect("failed to create dir");
+        fs::write(chapter.join("outer.md"), "<!-- include: inner.md -->\n").expect("write failed");
+        fs::write(chapter.join("inner.md"), "hello\n").expect("write failed");
+
+        let name = dir.path().file_name().unwrap().to_string_lossy();
+        let include = MarkdownElement::Comment {
+            comment: format!("include: {name}/chapter/outer.md"),
+            source_position: Default::default(),
+        };
+        let slides = build_presentation(vec![include]).into_slides();
+        let lines: Vec<_> = slides.into_iter().map(extract_slide_text_lines).collect();
+        assert_eq!(lines, &[vec!["hello"]]);
+    }
+
+    #[test]
+    fn include_cycle_through_different_path() {
+        let dir = tempfile::tempdir_in("/tmp").expect("failed to create tempdir");
+        let name = dir.path().file_name().unwrap().to_string_lossy();
+        fs::write(dir.path().join("a.md"), "<!-- include: b.md -->\n").expect("write failed");
+        fs::write(dir.path().join("b.md"), format!("<!-- include: ../{name}/a.md -->\n")).expect("write failed");
+
+        let include =
+            MarkdownElement::Comment { comment: format!("include: {name}/a.md"), source_position: Default::default() };
+        let error = try_build_presentation(vec![include]).expect_err("build succeeded");
+        assert!(error.to_string().contains("file includes itself"), "{error}");
+    }
+
+    #[test]
+    fn include_missing_file() {
+        let include =
+            MarkdownElement::Comment { comment: "include: potato.md".into(), source_position: Default::default() };
+        let error = try_build_presentation(vec![include]).expect_err("build succeeded");
+        assert!(matches!(error, BuildError::IncludeFile { .. }), "{error}");
+    }
+
     #[test]
     fn external_snippet() {
         let temp = tempfile::NamedTempFile::new().expect("failed to create tempfile");
@@ -1,6 +1,7 @@
 use super::padding::NumberPadder;
 use crate::{
     PresentationTheme,
+    execute::{ExecutionTimeout, ExecutionTimeoutParseError},
     markdown::{
         elements::{Percent, PercentParseError},
         text::{WeightedLine, WeightedText},
@@ -202,13 +203,50 @@ impl CodeBlockParser {
         Ok(code)
     }
 
+    /// Use the contents of a code block as the standard input of a snippet.
+    pub(crate) fn set_stdin(snippet: &mut Snippet, contents: String) -> ParseResult<()> {
+        if snippet.attributes.stdin.is_some() {
+            return Err(CodeBlockParseError::DuplicateAttribute("stdin"));
+        }
+        snippet.attributes.stdin = Some(SnippetStdin::Contents(contents));
+        Self::validate_attributes(&snippet.attributes)
+    }
+
     fn parse_block_info(input: &str) -> ParseResult<(SnippetLanguage, SnippetAttributes)> {
         let (language, input) = Self::parse_language(input);
         let attributes = Self::parse_attributes(input)?;
+        Self::validate_attributes(&attributes)?;
+        Ok((language, attributes))
+    }
+
+    fn validate_attributes(attributes: &SnippetAttributes) -> ParseResult<()> {
+        use CodeBlockParseError::{IncompatibleAttributes, NotExecutableSnippet};
         if attributes.width.is_some() && !attributes.auto_render {
             return Err(CodeBlockParseError::NotRenderSnippet("width"));
         }
-        Ok((language, attributes))
+        let executes = attributes.execute || attributes.execute_replace;
+        let has_session = attributes.session.is_some();
+        let has_stdin = attributes.stdin.is_some();
+        let has_args = !attributes.args.is_empty();
+        for (name, is_set) in [("session", has_session), ("stdin", has_stdin), ("args", has_args)] {
+            if is_set && !executes {
+                return Err(NotExecutableSnippet(name));
+            }
+        }
+        if has_session && attributes.acquire_terminal {
+            return Err(IncompatibleAttributes("session", "acquire_terminal"));
+        }
+        // Sessions read the code to run from their standard input and they're started only once.
+        if has_session && has_stdin {
+            return Err(IncompatibleAttributes("session", "stdin"));
+        }
+        if has_session && has_args {
+            return Err(IncompatibleAttributes("session", "args"));
+        }
+        if has_stdin && attributes.acquire_terminal {
+            return Err(IncompatibleAttributes("stdin", "acquire_terminal"));
+        }
+        Ok(())
     }
 
     fn parse_language(input: &str) -> (SnippetLanguage, &str) {
@@ -236,6 +274,10 @@ impl CodeBlockParser {
                 Attribute::AcquireTerminal => attributes.acquire_terminal = true,
                 Attribute::HighlightedLines(lines) => attributes.highlight_groups = lines,
                 Attribute::Width(width) => attributes.width = Some(width),
+                Attribute::Timeout(timeout) => attributes.timeout = Some(timeout),
+                Attribute::Session(name) => attributes.session = Some(name),
+                Attribute::Stdin(path) => attributes.stdin = Some(SnippetStdin::File(path)),
+                Attribute::Args(args) => attributes.args = args,
             };
             processed_attributes.push(discriminant);
             input = rest;
@@ -263,6 +305,26 @@ impl CodeBlockParser {
                         let (width, input) = Self::parse_width(value)?;
                         return Ok((Some(Attribute::Width(width)), input));
                     }
+                    token if token.starts_with("timeout:") => {
+                        let value = input.split_once("+timeout:").unwrap().1;
+                        let (timeout, input) = Self::parse_timeout(value)?;
+                        return Ok((Some(Attribute::Timeout(timeout)), input));
+                    }
+                    token if token.starts_with("session:") => {
+                        let value = input.split_once("+session:").unwrap().1;
+                        let (name, input) = Self::parse_session(value)?;
+                        return Ok((Some(Attribute::Session(name)), input));
+                    }
+                    token if token.starts_with("stdin:") => {
+                        let value = input.split_once("+stdin:").unwrap().1;
+                        let (path, input) = Self::parse_stdin(value)?;
+                        return Ok((Some(Attribute::Stdin(path)), input));
+                    }
+                    token if token.starts_with("args:") => {
+                        let value = input.split_once("+args:").unwrap().1;
+                        let (args, input) = Self::parse_args(value)?;
+                        return Ok((Some(Attribute::Args(args)), input));
+                    }
                     _ => return Err(CodeBlockParseError::InvalidToken(Self::next_identifier(input).into())),
                 };
                 (Some(attribute), &input[token.len() + 1..])
@@ -334,6 +396,60 @@ impl CodeBlockParser {
         Ok((value, &input[end_index..]))
     }
 
+    fn parse_timeout(input: &str) -> ParseResult<(ExecutionTimeout, &str)> {
+        let end_index = input.find(' ').unwrap_or(input.len());
+        let value = input[0..end_index].parse().map_err(CodeBlockParseError::InvalidTimeout)?;
+        Ok((value, &input[end_index..]))
+    }
+
+    fn parse_session(input: &str) -> ParseResult<(String, &str)> {
+        let end_index = input.find(' ').unwrap_or(input.len());
+        let name = &input[0..end_index];
+        if name.is_empty() {
+            return Err(CodeBlockParseError::EmptySessionName);
+        }
+        Ok((name.into(), &input[end_index..]))
+    }
+
+    fn parse_stdin(input: &str) -> ParseResult<(PathBuf, &str)> {
+        let end_index = input.find(' ').unwrap_or(input.len());
+        let path = &input[0..end_index];
+        if path.is_empty() {
+            return Err(CodeBlockParseError::EmptyStdinPath);
+        }
+        Ok((path.into(), &input[end_index..]))
+    }
+
+    fn parse_args(input: &str) -> ParseResult<(Vec<String>, &str)> {
+        // Quotes allow passing in multiple arguments, which are split like a shell would.
+        let (value, rest) = match input.strip_prefix('"') {
+            Some(input) => {
+                let end_index =
+                    Self::find_closing_quote(input).ok_or(CodeBlockParseError::InvalidArgs("no closing '\"'"))?;
+                (&input[..end_index], &input[end_index + 1..])
+            }
+            None => input.split_at(input.find(' ').unwrap_or(input.len())),
+        };
+        let args = shlex::split(value).ok_or(CodeBlockParseError::InvalidArgs("unbalanced quotes"))?;
+        if args.is_empty() {
+            return Err(CodeBlockParseError::InvalidArgs("no arguments given"));
+        }
+        Ok((args, rest))
+    }
+
+    // Finds the first double quote that isn't escaped.
+    fn find_closing_quote(input: &str) -> Option<usize> {
+        let mut escaped = false;
+        for (index, c) in input.char_indices() {
+            match c {
+                '\\' if !escaped => escaped = true,
+                '"' if !escaped => return Some(index),
+                _ => escaped = false,
+            };
+        }
+        None
+    }
+
     fn skip_whitespace(input: &str) -> &str {
         input.trim_start_matches(' ')
     }
@@ -357,11 +473,29 @@ pub enum CodeBlockParseError {
     #[error("invalid width: {0}")]
     InvalidWidth(PercentParseError),
 
+    #[error("invalid timeout: {0}")]
+    InvalidTimeout(ExecutionTimeoutParseError),
+
     #[error("duplicate attribute: {0}")]
     DuplicateAttribute(&'static str),
 
     #[error("attribute {0} can only be set in +render blocks")]
     NotRenderSnippet(&'static str),
+
+    #[error("session name can't be empty")]
+    EmptySessionName,
+
+    #[error("stdin file path can't be empty")]
+    EmptyStdinPath,
+
+    #[error("invalid args: {0}")]
+    InvalidArgs(&'static str),
+
+    #[error("attribute {0} can only be set in +exec and +exec_replace blocks")]
+    NotExecutableSnippet(&'static str),
+
+    #[error("attributes {0} and {1} can't be used together")]
+    IncompatibleAttributes(&'static str, &'static str),
 }
 
 #[derive(EnumDiscriminants)]
@@ -374,6 +508,10 @@ enum Attribute {
     Width(Percent),
     NoBackground,
     AcquireTerminal,
+    Timeout(ExecutionTimeout),
+    Session(String),
+    Stdin(PathBuf),
+    Args(Vec<String>),
 }
 
 /// A code snippet.
@@ -588,6 +726,33 @@ pub(crate) struct SnippetAttributes {
 
     /// Whether this code snippet acquires the terminal when ran.
     pub(crate) acquire_terminal: bool,
+
+    /// The maximum amount of time this snippet can run for.
+    ///
+    /// Overrides the one for the snippet's language, if any.
+    pub(crate) timeout: Option<ExecutionTimeout>,
+
+    /// The name of the session this snippet runs in, if any.
+    ///
+    /// Snippets in the same session share a single interpreter so anything defined in one of them can be used by the
+    /// ones that run after it.
+    pub(crate) session: Option<String>,
+
+    /// Where the standard input of this snippet comes from, if anywhere.
+    pub(crate) stdin: Option<SnippetStdin>,
+
+    /// The arguments appended to the last command used to run this snippet.
+    pub(crate) args: Vec<String>,
+}
+
+/// The standard input of a snippet.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum SnippetStdin {
+    /// A file, relative to the presentation.
+    File(PathBuf),
+
+    /// The contents of the code block that follows the snippet.
+    Contents(String),
 }
 
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
@@ -630,6 +795,7 @@ mod test {
     use super::*;
     use Highlight::*;
     use rstest::rstest;
+    use std::time::Duration;
 
     fn parse_language(input: &str) -> SnippetLanguage {
         let (language, _) = CodeBlockParser::parse_block_info(input).expect("parse failed");
@@ -762,6 +928,81 @@ mod test {
         try_parse_attributes("mermaid +width:50%").expect_err("parse succeeded");
     }
 
+    #[test]
+    fn parse_timeout() {
+        let attributes = parse_attributes("bash +exec +timeout:5s +line_numbers");
+        assert_eq!(attributes.timeout, Some(ExecutionTimeout(Duration::from_secs(5))));
+        assert!(attributes.line_numbers);
+    }
+
+    #[test]
+    fn invalid_timeout() {
+        try_parse_attributes("bash +exec +timeout:5").expect_err("parse succeeded");
+        try_parse_attributes("bash +exec +timeout:").expect_err("parse succeeded");
+    }
+
+    #[test]
+    fn parse_session() {
+        let attributes = parse_attributes("python +exec +session:demo +line_numbers");
+        assert_eq!(attributes.session.as_deref(), Some("demo"));
+        assert!(attributes.line_numbers);
+    }
+
+    #[rstest]
+    #[case::no_name("python +exec +session:")]
+    #[case::not_executable("python +session:demo")]
+    #[case::acquire_terminal("python +exec +acquire_terminal +session:demo")]
+    fn invalid_session(#[case] input: &str) {
+        try_parse_attributes(input).expect_err("parse succeeded");
+    }
+
+    #[test]
+    fn parse_stdin_and_args() {
+        let attributes = parse_attributes(r#"bash +exec +stdin:input.txt +args:"-n 3" +line_numbers"#);
+        assert_eq!(attributes.stdin, Some(SnippetStdin::File("input.txt".into())));
+        assert_eq!(attributes.args, &["-n", "3"]);
+        assert!(attributes.line_numbers);
+    }
+
+    #[rstest]
+    #[case::single_quotes(r#"bash +exec +args:"-n 'hello world'""#, &["-n", "hello world"])]
+    #[case::escaped_quotes(r#"bash +exec +args:"say \"hi\" 3""#, &["say", "\"hi\"", "3"])]
+    #[case::escaped_space(r#"bash +exec +args:"hello\ world""#, &["hello world"])]
+    fn parse_quoted_args(#[case] input: &str, #[case] expected: &[&str]) {
+        let attributes = parse_attributes(input);
+        assert_eq!(attributes.args, expected);
+    }
+
+    #[test]
+    fn parse_single_arg() {
+        let attributes = parse_attributes("bash +exec +args:--verbose");
+        assert_eq!(attributes.args, &["--verbose"]);
+    }
+
+    #[rstest]
+    #[case::no_path("bash +exec +stdin:")]
+    #[case::no_args("bash +exec +args:")]
+    #[case::empty_args(r#"bash +exec +args:"  ""#)]
+    #[case::unclosed_quote(r#"bash +exec +args:"-n 3"#)]
+    #[case::unbalanced_quotes(r#"bash +exec +args:"-n 'hello""#)]
+    #[case::not_executable("bash +stdin:input.txt")]
+    #[case::session("bash +exec +session:demo +args:-n")]
+    #[case::acquire_terminal("bash +exec +acquire_terminal +stdin:input.txt")]
+    fn invalid_stdin_and_args(#[case] input: &str) {
+        try_parse_attributes(input).expect_err("parse succeeded");
+    }
+
+    #[test]
+    fn set_stdin() {
+        let mut snippet = CodeBlockParser::parse("bash +exec".into(), "cat".into()).expect("parse failed");
+        CodeBlockParser::set_stdin(&mut snippet, "hi".into()).expect("set failed");
+        assert_eq!(snippet.attributes.stdin, Some(SnippetStdin::Contents("hi".into())));
+
+        let mut snippet =
+            CodeBlockParser::parse("bash +exec +stdin:input.txt".into(), "cat".into()).expect("parse failed");
+        CodeBlockParser::set_stdin(&mut snippet, "hi".into()).expect_err("set succeeded");
+    }
+
     #[test]
     fn code_visible_lines() {
         let contents = r##"# fn main() {
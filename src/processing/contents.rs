@@ -0,0 +1,160 @@
+use crate::{
+    markdown::elements::{Line, Text},
+    presentation::{AsRenderOperations, RenderOperation},
+    processing::modals::{IndexEntry, SharedIndexEntries},
+    render::properties::WindowSize,
+    style::TextStyle,
+    theme::{Alignment, Margin, TableOfContentsStyle},
+};
+
+/// Renders a list of the titles of the slides in the presentation.
+///
+/// Slides are only known once the entire presentation is built so this is generated at render
+/// time.
+#[derive(Debug)]
+pub(crate) struct TableOfContentsGenerator {
+    entries: SharedIndexEntries,
+    section: Option<String>,
+    current_section: Option<String>,
+    style: TableOfContentsStyle,
+}
+
+impl TableOfContentsGenerator {
+    /// Construct a new generator.
+    ///
+    /// If `section` is set, only the slides in that section are listed. Entries that belong to
+    /// `current_section` are highlighted.
+    pub(crate) fn new(
+        entries: SharedIndexEntries,
+        section: Option<String>,
+        current_section: Option<String>,
+        style: TableOfContentsStyle,
+    ) -> Self {
+        Self { entries, section, current_section, style }
+    }
+
+    fn push_item(&self, title: &Line, depth: u8, current: bool, operations: &mut Vec<RenderOperation>) {
+        // Match the indentation used for regular lists.
+        let padding = (depth as u16 + 1) * 3;
+        let bullet = if depth == 0 { '•' } else { '◦' };
+        let mut style = TextStyle::colored(self.style.colors);
+        if current {
+            style = TextStyle::colored(self.style.current_colors).bold();
+        }
+        let prefix = format!("{}{bullet}", " ".repeat(padding as usize));
+        let mut title = title.clone();
+        for chunk in &mut title.0 {
+            chunk.style.merge(&style);
+        }
+        operations.extend([
+            RenderOperation::RenderText {
+                line: vec![Text::new(prefix, style)].into(),
+                alignment: Alignment::Left { margin: Margin::Fixed(0) },
+            },
+            RenderOperation::RenderText {
+                line: title.into(),
+                alignment: Alignment::Left { margin: Margin::Fixed(padding + 3) },
+            },
+            RenderOperation::RenderLineBreak,
+        ]);
+    }
+
+    fn is_current(&self, entry: &IndexEntry) -> bool {
+        entry.section.is_some() && entry.section == self.current_section
+    }
+}
+
+impl AsRenderOperations for TableOfContentsGenerator {
+    fn as_render_operations(&self, _dimensions: &WindowSize) -> Vec<RenderOperation> {
+        let entries = self.entries.borrow();
+        // Agenda slides would otherwise list themselves.
+        let entries = entries.iter().filter(|entry| entry.has_title && !entry.has_table_of_contents);
+        let mut operations = Vec::new();
+        if let Some(section) = &self.section {
+            for entry in entries.filter(|entry| entry.section.as_ref() == Some(section)) {
+                self.push_item(&entry.title, 0, false, &mut operations);
+            }
+            return operations;
+        }
+
+        // Slides are nested under the section they belong to, if any.
+        let mut last_section = None;
+        for entry in entries {
+            let current = self.is_current(entry);
+            if let Some(section) = &entry.section {
+                if last_section != Some(section) {
+                    self.push_item(&Line::from(section.clone()), 0, current, &mut operations);
+                    last_section = Some(section);
+                }
+                self.push_item(&entry.title, 1, current, &mut operations);
+            } else {
+                self.push_item(&entry.title, 0, current, &mut operations);
+            }
+        }
+        operations
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        render::{
+            engine::{RenderEngine, RenderEngineOptions},
+            virt::{TerminalGrid, VirtualTerminal},
+        },
+        style::{Color, Colors},
+    };
+    use std::{cell::RefCell, rc::Rc};
+
+    fn entry(title: &str, section: Option<&str>) -> IndexEntry {
+        IndexEntry {
+            title: Line::from(title),
+            has_title: true,
+            section: section.map(String::from),
+            has_table_of_contents: false,
+        }
+    }
+
+    fn render(generator: TableOfContentsGenerator) -> TerminalGrid {
+        let dimensions = WindowSize { rows: 8, columns: 40, width: 400, height: 160 };
+        let mut terminal = VirtualTerminal::new(&dimensions);
+        let engine = RenderEngine::new(&mut terminal, dimensions.clone(), RenderEngineOptions::default());
+        let operations = generator.as_render_operations(&dimensions);
+        engine.render(operations.iter()).expect("render failed");
+        terminal.into_contents()
+    }
+
+    #[test]
+    fn current_section() {
+        let entries = vec![entry("Welcome", None), entry("Hello", Some("Intro")), entry("Deep dive", Some("Details"))];
+        let current = Colors { foreground: Some(Color::Red), background: None };
+        let style = TableOfContentsStyle { colors: Default::default(), current_colors: current };
+        let generator =
+            TableOfContentsGenerator::new(Rc::new(RefCell::new(entries)), None, Some("Intro".into()), style);
+        let grid = render(generator);
+        let rows: Vec<_> = grid
+            .rows
+            .iter()
+            .map(|row| row.iter().map(|c| c.character).collect::<String>().trim_end().to_string())
+            .collect();
+        assert_eq!(rows, &[
+            "   •  Welcome",
+            "   •  Intro",
+            "      ◦  Hello",
+            "   •  Details",
+            "      ◦  Deep dive",
+            "",
+            "",
+            ""
+        ]);
+
+        let style_at = |row: usize, column: usize| grid.rows[row][column].style;
+        assert_eq!(style_at(0, 6).colors.foreground, None);
+        for (row, column) in [(1, 3), (1, 6), (2, 9)] {
+            assert_eq!(style_at(row, column).colors, current);
+            assert!(style_at(row, column).is_bold());
+        }
+        assert!(!style_at(3, 6).is_bold());
+    }
+}
@@ -1,7 +1,7 @@
 use super::separator::{RenderSeparator, SeparatorWidth};
 use crate::{
-    ansi::AnsiSplitter,
-    execute::{ExecutionHandle, ExecutionState, ProcessStatus, SnippetExecutor},
+    ansi::TerminalScreen,
+    execute::{ExecutionHandle, ExecutionState, ProcessStatus, SnippetExecutor, TerminalSize},
     markdown::{
         elements::{Line, Text},
         text::WeightedLine,
@@ -26,13 +26,19 @@ use std::{
 
 const MINIMUM_SEPARATOR_WIDTH: u16 = 32;
 
+// Snippets run on a terminal as wide as their output block, but at least this wide.
+const MINIMUM_TERMINAL_COLUMNS: u16 = 40;
+
+// The output block grows as needed so this is only the height programs see.
+const TERMINAL_ROWS: u16 = 24;
+
 #[derive(Debug)]
 struct RunSnippetOperationInner {
     handle: Option<ExecutionHandle>,
     output_lines: Vec<WeightedLine>,
     state: RenderAsyncState,
     max_line_length: u16,
-    starting_style: TextStyle,
+    screen: TerminalScreen,
 }
 
 #[derive(Debug)]
@@ -66,12 +72,13 @@ impl RunSnippetOperation {
             Alignment::Left { .. } | Alignment::Right { .. } => block_length,
             Alignment::Center { minimum_size, .. } => block_length.max(*minimum_size),
         };
+        let terminal_size = Self::terminal_size(block_length);
         let inner = RunSnippetOperationInner {
             handle: None,
             output_lines: Vec::new(),
             state: RenderAsyncState::default(),
             max_line_length: 0,
-            starting_style: TextStyle::default(),
+            screen: TerminalScreen::new(terminal_size.columns, terminal_size.rows),
         };
         Self {
             code,
@@ -86,6 +93,10 @@ impl RunSnippetOperation {
             separator,
         }
     }
+
+    fn terminal_size(block_length: u16) -> TerminalSize {
+        TerminalSize { columns: block_length.max(MINIMUM_TERMINAL_COLUMNS), rows: TERMINAL_ROWS }
+    }
 }
 
 #[derive(Debug)]
@@ -154,7 +165,7 @@ impl RenderAsync for RunSnippetOperation {
         let mut inner = self.inner.borrow_mut();
         if let Some(handle) = inner.handle.as_mut() {
             let mut state = handle.state.lock().unwrap();
-            let ExecutionState { output, status } = &mut *state;
+            let ExecutionState { output, status, .. } = &mut *state;
             *self.state_description.borrow_mut() = match status {
                 ProcessStatus::Running => Text::new("running", TextStyle::default().colors(self.status_colors.running)),
                 ProcessStatus::Success => {
@@ -163,27 +174,32 @@ impl RenderAsync for RunSnippetOperation {
                 ProcessStatus::Failure => {
                     Text::new("finished with error", TextStyle::default().colors(self.status_colors.failure))
                 }
+                ProcessStatus::TimedOut => {
+                    Text::new("timed out", TextStyle::default().colors(self.status_colors.timed_out))
+                }
+                ProcessStatus::Cancelled => {
+                    Text::new("cancelled", TextStyle::default().colors(self.status_colors.cancelled))
+                }
             };
-            let new_lines = mem::take(output);
-            let modified = !new_lines.is_empty();
+            let new_output = mem::take(output);
+            let modified = !new_output.is_empty();
             let is_finished = status.is_finished();
             drop(state);
 
-            let mut max_line_length = 0;
-            let (new_lines, style) = AnsiSplitter::new(inner.starting_style).split_lines(&new_lines);
-            for line in &new_lines {
-                let width = u16::try_from(line.width()).unwrap_or(u16::MAX);
-                max_line_length = max_line_length.max(width);
+            if modified {
+                // Output can rewrite anything that's on the screen so lines are rebuilt from scratch every time.
+                inner.screen.feed(&new_output);
+                let lines: Vec<WeightedLine> = inner.screen.lines().into_iter().map(WeightedLine::from).collect();
+                let max_line_length = lines.iter().map(|line| line.width()).max().unwrap_or(0);
+                inner.max_line_length = u16::try_from(max_line_length).unwrap_or(u16::MAX);
+                inner.output_lines = lines;
             }
-            inner.starting_style = style;
             if is_finished {
                 inner.handle.take();
                 inner.state = RenderAsyncState::JustFinishedRendering;
             } else {
                 inner.state = RenderAsyncState::Rendering { modified };
             }
-            inner.output_lines.extend(new_lines);
-            inner.max_line_length = inner.max_line_length.max(max_line_length);
         }
         inner.state.clone()
     }
@@ -193,7 +209,7 @@ impl RenderAsync for RunSnippetOperation {
         if !matches!(inner.state, RenderAsyncState::NotStarted) {
             return false;
         }
-        match self.executor.execute_async(&self.code) {
+        match self.executor.execute_async(&self.code, Self::terminal_size(self.block_length)) {
             Ok(handle) => {
                 inner.handle = Some(handle);
                 inner.state = RenderAsyncState::Rendering { modified: false };
@@ -206,6 +222,16 @@ impl RenderAsync for RunSnippetOperation {
             }
         }
     }
+
+    fn cancel(&self) -> bool {
+        match &self.inner.borrow().handle {
+            Some(handle) => {
+                handle.cancel();
+                true
+            }
+            None => false,
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -1,5 +1,5 @@
 use crate::{
-    markdown::elements::Text,
+    markdown::{elements::Text, text::WeightedLine},
     presentation::{AsRenderOperations, RenderOperation},
     render::properties::WindowSize,
     style::{Colors, TextStyle},
@@ -17,32 +17,53 @@ pub(crate) struct FooterContext {
     pub(crate) event: String,
     pub(crate) location: String,
     pub(crate) date: String,
+    pub(crate) sections: Vec<FooterSection>,
+}
+
+/// A section in the presentation, as defined via the `section` comment command.
+#[derive(Debug, Default)]
+pub(crate) struct FooterSection {
+    pub(crate) name: String,
+    pub(crate) total_slides: usize,
 }
 
 #[derive(Debug)]
 pub(crate) struct FooterGenerator {
     pub(crate) current_slide: usize,
+    /// The index of the section the slide belongs to within [FooterContext::sections].
+    pub(crate) section: Option<usize>,
     pub(crate) context: Rc<RefCell<FooterContext>>,
     pub(crate) style: FooterStyle,
 }
 
 impl FooterGenerator {
     fn render_template(
+        &self,
         template: &str,
-        current_slide: &str,
         context: &FooterContext,
         colors: Colors,
         alignment: Alignment,
     ) -> RenderOperation {
+        let current_slide = (self.current_slide + 1).to_string();
+        let section = self.section.and_then(|index| context.sections.get(index).map(|section| (index, section)));
+        let (section_name, section_number, slides_in_section) = match section {
+            Some((index, section)) => {
+                (section.name.as_str(), (index + 1).to_string(), section.total_slides.to_string())
+            }
+            None => ("", String::new(), String::new()),
+        };
         let contents = template
-            .replace("{current_slide}", current_slide)
+            .replace("{current_slide}", &current_slide)
             .replace("{total_slides}", &context.total_slides.to_string())
             .replace("{title}", &context.title)
             .replace("{sub_title}", &context.sub_title)
             .replace("{event}", &context.event)
             .replace("{location}", &context.location)
             .replace("{date}", &context.date)
-            .replace("{author}", &context.author);
+            .replace("{author}", &context.author)
+            .replace("{section_number}", &section_number)
+            .replace("{slides_in_section}", &slides_in_section)
+            .replace("{section}", section_name);
         let text = Text::new(contents, TextStyle::default().colors(colors));
         RenderOperation::RenderText { line: vec![text].into(), alignment }
     }
@@ -53,7 +74,6 @@ impl AsRenderOperations for FooterGenerator {
         let context = self.context.borrow();
         match &self.style {
             FooterStyle::Template { left, center, right, colors } => {
-                let current_slide = (self.current_slide + 1).to_string();
                 // We print this one row below the bottom so there's one row of padding.
                 let mut operations = vec![RenderOperation::JumpToBottomRow { index: 1 }];
                 let margin = Margin::Fixed(1);
@@ -64,7 +84,7 @@ impl AsRenderOperations for FooterGenerator {
                 ];
                 for (text, alignment) in [left, center, right].iter().zip(alignments) {
                     if let Some(text) = text {
-                        operations.push(Self::render_template(text, &current_slide, &context, *colors, alignment));
+                        operations.push(self.render_template(text, &context, *colors, alignment));
                     }
                 }
                 operations
@@ -85,3 +105,28 @@ impl AsRenderOperations for FooterGenerator {
         }
     }
 }
+
+/// Renders the footnotes referenced in a slide right above the footer.
+#[derive(Debug)]
+pub(crate) struct FootnotesGenerator {
+    pub(crate) lines: Vec<WeightedLine>,
+}
+
+impl AsRenderOperations for FootnotesGenerator {
+    fn as_render_operations(&self, dimensions: &WindowSize) -> Vec<RenderOperation> {
+        // Lines that don't fit get wrapped so account for that to know which row to start from.
+        let columns = dimensions.columns.max(1) as usize;
+        let rows: usize = self.lines.iter().map(|line| line.split(columns).count().max(1)).sum();
+        let mut operations = vec![RenderOperation::JumpToBottomRow { index: rows.saturating_sub(1) as u16 }];
+        for line in &self.lines {
+            operations.extend([
+                RenderOperation::RenderText {
+                    line: line.clone(),
+                    alignment: Alignment::Left { margin: Margin::Fixed(0) },
+                },
+                RenderOperation::RenderLineBreak,
+            ]);
+        }
+        operations
+    }
+}
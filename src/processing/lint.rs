@@ -0,0 +1,482 @@
+use crate::{
+    custom::OptionsConfig,
+    execute::SnippetExecutor,
+    markdown::{
+        elements::{MarkdownElement, SourcePosition},
+        parse::{MarkdownParser, ParseError},
+    },
+    processing::{
+        builder::{
+            BuildError, CommandParseError, CommentCommand, PresentationBuilder, PresentationBuilderOptions,
+            StrictPresentationMetadata,
+        },
+        code::{CodeBlockParser, SnippetLanguage},
+        snippets::{is_expected_output, is_stdin},
+    },
+    resource::Resources,
+    theme::PresentationTheme,
+};
+use comrak::Arena;
+use serde_yaml::{Mapping, Value};
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+/// Finds problems in a presentation without having to present it.
+///
+/// Unlike building a presentation, which stops at the first error, this looks at every element
+/// and reports everything it finds.
+pub(crate) struct PresentationLinter<'a> {
+    resources: &'a mut Resources,
+    code_executor: &'a SnippetExecutor,
+    options: PresentationBuilderOptions,
+    layout: Option<ColumnLayout>,
+    included_files: Vec<IncludedFile>,
+    lints: Vec<Lint>,
+}
+
+impl<'a> PresentationLinter<'a> {
+    /// Construct a new linter.
+    pub(crate) fn new(
+        resources: &'a mut Resources,
+        code_executor: &'a SnippetExecutor,
+        options: PresentationBuilderOptions,
+    ) -> Self {
+        Self { resources, code_executor, options, layout: None, included_files: Vec::new(), lints: Vec::new() }
+    }
+
+    /// Lint the elements in a presentation, along with the ones in any files it includes.
+    pub(crate) fn lint(mut self, elements: &[(MarkdownElement, SourcePosition)]) -> Vec<Lint> {
+        self.lint_elements(elements);
+        self.finish_layout();
+        self.lints
+    }
+
+    fn lint_elements(&mut self, elements: &[(MarkdownElement, SourcePosition)]) {
+        for (element, position) in elements {
+            match element {
+                MarkdownElement::FrontMatter(contents) => self.lint_front_matter(contents, *position),
+                MarkdownElement::Image { path, source_position, .. } => self.lint_image(path, *source_position),
+                MarkdownElement::Snippet { info, code, source_position } => {
+                    self.lint_snippet(info, code, *source_position)
+                }
+                MarkdownElement::Comment { comment, source_position } => self.lint_comment(comment, *source_position),
+                MarkdownElement::SetexHeading { .. } if self.options.implicit_slide_ends => self.finish_layout(),
+                MarkdownElement::ThematicBreak if self.options.end_slide_shorthand => self.finish_layout(),
+                _ => (),
+            };
+        }
+    }
+
+    fn push(&mut self, position: SourcePosition, error: LintError) {
+        self.lints.push(Lint { file: self.current_file(), position: Some(position), error });
+    }
+
+    // The included file being linted, relative to the presentation, if any.
+    fn current_file(&self) -> Option<PathBuf> {
+        self.included_files.last().map(|file| file.path.clone())
+    }
+
+    // Paths in included files are relative to the file that contains them, like when building.
+    fn resolve_path(&self, path: &Path) -> PathBuf {
+        match self.included_files.last().and_then(|file| file.path.parent()) {
+            Some(parent) => parent.join(path),
+            None => path.into(),
+        }
+    }
+
+    fn lint_front_matter(&mut self, contents: &str, position: SourcePosition) {
+        let metadata = match serde_yaml::from_str::<Mapping>(contents) {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                self.push(position, LintError::InvalidFrontMatter(e.to_string()));
+                return;
+            }
+        };
+        // The contents start right after the line with the opening delimiter.
+        let key_position = |key: &str| {
+            let index = contents.lines().position(|line| line.trim_start().starts_with(&format!("{key}:")));
+            position.offset_lines(index.map(|index| index + 1).unwrap_or_default())
+        };
+        for (key, mut value) in metadata {
+            let Some(name) = key.as_str().map(ToString::to_string) else {
+                continue;
+            };
+            if name == "theme" {
+                if let Some(overrides) = value.as_mapping_mut().and_then(|theme| theme.remove("override")) {
+                    if let Err(e) = serde_yaml::from_value::<PresentationTheme>(overrides) {
+                        self.push(key_position("override"), LintError::InvalidThemeOverride(e.to_string()));
+                    }
+                }
+            } else if name == "options" {
+                if let Ok(options) = serde_yaml::from_value::<OptionsConfig>(value.clone()) {
+                    self.options.merge(options);
+                }
+            }
+            // Check each key on its own so that one of them being wrong doesn't hide the rest.
+            let entry = Value::Mapping(Mapping::from_iter([(key, value)]));
+            if let Err(e) = serde_yaml::from_value::<StrictPresentationMetadata>(entry) {
+                let error = match e.to_string().starts_with("unknown field") {
+                    true => LintError::UnknownFrontMatterKey(name.clone()),
+                    false => LintError::InvalidFrontMatter(format!("'{name}': {e}")),
+                };
+                self.push(key_position(&name), error);
+            }
+        }
+    }
+
+    fn lint_image(&mut self, path: &Path, position: SourcePosition) {
+        let path = self.resolve_path(path);
+        if let Err(e) = self.resources.image(&path) {
+            self.push(position, LintError::LoadImage { path, error: e.to_string() });
+        }
+    }
+
+    fn lint_include(&mut self, path: &Path, position: SourcePosition) {
+        let path = self.resolve_path(path);
+        let canonical_path = match self.resources.canonicalize(&path) {
+            Ok(path) => path,
+            Err(e) => {
+                self.push(position, LintError::Include { path, error: e.to_string() });
+                return;
+            }
+        };
+        if self.included_files.iter().any(|file| file.canonical_path == canonical_path) {
+            self.push(position, LintError::Include { path, error: "file includes itself".into() });
+            return;
+        }
+        let contents = match self.resources.included_file(&path) {
+            Ok(contents) => contents,
+            Err(e) => {
+                self.push(position, LintError::Include { path, error: e.to_string() });
+                return;
+            }
+        };
+        let arena = Arena::new();
+        let (elements, parse_errors) = MarkdownParser::new(&arena).parse_all(&contents);
+
+        // Everything from here on is reported against the included file.
+        self.included_files.push(IncludedFile { path, canonical_path });
+        for error in parse_errors {
+            self.push(error.sourcepos, error.into());
+        }
+        if let Some((MarkdownElement::FrontMatter(_), position)) = elements.first() {
+            self.push(*position, LintError::IncludedFrontMatter);
+        }
+        self.lint_elements(&elements);
+        self.included_files.pop();
+    }
+
+    fn lint_snippet(&mut self, info: &str, code: &str, position: SourcePosition) {
+        if is_expected_output(info) || is_stdin(info) {
+            return;
+        }
+        let snippet = match CodeBlockParser::parse(info.into(), code.into()) {
+            Ok(snippet) => snippet,
+            Err(e) => {
+                self.push(position, LintError::InvalidCode(e.to_string()));
+                return;
+            }
+        };
+        let executes = snippet.attributes.execute || snippet.attributes.execute_replace;
+        match &snippet.language {
+            SnippetLanguage::Unknown(language) if !language.is_empty() => {
+                self.push(position, LintError::UnknownLanguage(language.clone()))
+            }
+            // The language for these is only known once the file they point to is loaded.
+            SnippetLanguage::File => (),
+            language if executes && !self.code_executor.is_execution_supported(language) => {
+                let language = info.split(|c: char| c.is_whitespace() || c == '+').next().unwrap_or_default();
+                self.push(position, LintError::NoExecutor(language.into()))
+            }
+            _ => (),
+        };
+    }
+
+    fn lint_comment(&mut self, comment: &str, position: SourcePosition) {
+        let comment = comment.trim();
+        if PresentationBuilder::should_ignore_comment(comment, &self.options.command_prefix) {
+            return;
+        }
+        let comment = comment.trim_start_matches(&self.options.command_prefix);
+        let command = match comment.parse::<CommentCommand>() {
+            Ok(command) => command,
+            Err(e) => {
+                self.push(position, LintError::InvalidCommand(e));
+                return;
+            }
+        };
+        match command {
+            CommentCommand::InitColumnLayout(columns) => {
+                self.finish_layout();
+                self.layout =
+                    Some(ColumnLayout { file: self.current_file(), position, used: vec![false; columns.len()] });
+            }
+            CommentCommand::Column(column) => {
+                if let Some(used) = self.layout.as_mut().and_then(|layout| layout.used.get_mut(column)) {
+                    *used = true;
+                }
+            }
+            CommentCommand::ResetLayout | CommentCommand::EndSlide => self.finish_layout(),
+            CommentCommand::Include(path) => self.lint_include(&path, position),
+            _ => (),
+        };
+    }
+
+    fn finish_layout(&mut self) {
+        let Some(layout) = self.layout.take() else {
+            return;
+        };
+        let unused: Vec<_> =
+            layout.used.iter().enumerate().filter(|(_, used)| !**used).map(|(column, _)| column).collect();
+        if !unused.is_empty() {
+            let error = LintError::UnusedColumns(unused);
+            self.lints.push(Lint { file: layout.file, position: Some(layout.position), error });
+        }
+    }
+}
+
+struct ColumnLayout {
+    file: Option<PathBuf>,
+    position: SourcePosition,
+    used: Vec<bool>,
+}
+
+struct IncludedFile {
+    path: PathBuf,
+    canonical_path: PathBuf,
+}
+
+/// A problem found in a presentation.
+#[derive(Debug)]
+pub(crate) struct Lint {
+    /// The included file this problem is in, relative to the presentation, if it's not in the
+    /// presentation file itself.
+    pub(crate) file: Option<PathBuf>,
+
+    /// The position in the file this problem is at, if known.
+    pub(crate) position: Option<SourcePosition>,
+
+    /// The problem itself.
+    pub(crate) error: LintError,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum LintError {
+    #[error(transparent)]
+    Parse(#[from] ParseError),
+
+    #[error("invalid front matter: {0}")]
+    InvalidFrontMatter(String),
+
+    #[error("unknown front matter key '{0}'")]
+    UnknownFrontMatterKey(String),
+
+    #[error("invalid theme override: {0}")]
+    InvalidThemeOverride(String),
+
+    #[error("could not load image '{path}': {error}")]
+    LoadImage { path: PathBuf, error: String },
+
+    #[error("invalid code: {0}")]
+    InvalidCode(String),
+
+    #[error("unknown snippet language '{0}'")]
+    UnknownLanguage(String),
+
+    #[error("snippet can't be executed as there's no executor for '{0}'")]
+    NoExecutor(String),
+
+    #[error("invalid command: {0}")]
+    InvalidCommand(CommandParseError),
+
+    #[error("could not include '{path}': {error}")]
+    Include { path: PathBuf, error: String },
+
+    #[error("front matter is only allowed in the presentation file")]
+    IncludedFrontMatter,
+
+    #[error("column layout never uses column(s) {}", join_columns(.0))]
+    UnusedColumns(Vec<usize>),
+
+    #[error("building presentation failed: {0}")]
+    Build(#[from] BuildError),
+}
+
+fn join_columns(columns: &[usize]) -> String {
+    columns.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+}
+
+/// The problems found in a presentation.
+#[derive(Debug)]
+pub struct LintReport {
+    path: PathBuf,
+    lints: Vec<Lint>,
+}
+
+impl LintReport {
+    pub(crate) fn new(path: PathBuf, lints: Vec<Lint>) -> Self {
+        Self { path, lints }
+    }
+
+    /// Whether no problems were found.
+    pub fn is_empty(&self) -> bool {
+        self.lints.is_empty()
+    }
+}
+
+impl fmt::Display for LintReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Included files are relative to the presentation so they're displayed relative to wherever it is.
+        let directory = self.path.parent().unwrap_or(Path::new(""));
+        for Lint { file, position, error } in &self.lints {
+            let path = match file {
+                Some(file) => directory.join(file),
+                None => self.path.clone(),
+            };
+            let path = path.display();
+            match position {
+                Some(position) => writeln!(f, "{path}:{position}: {error}")?,
+                None => writeln!(f, "{path}: {error}")?,
+            };
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::markdown::parse::MarkdownParser;
+    use std::fs;
+
+    fn lint(input: &str) -> Vec<String> {
+        let arena = Arena::new();
+        let elements = MarkdownParser::new(&arena).parse(input).expect("parse failed");
+        let mut resources = Resources::new("/tmp", Default::default());
+        let executor = SnippetExecutor::default();
+        let linter = PresentationLinter::new(&mut resources, &executor, Default::default());
+        let lints = linter.lint(&elements);
+        LintReport::new("deck.md".into(), lints).to_string().lines().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn front_matter() {
+        let input = r"---
+title: hi
+potato: 42
+theme:
+  override:
+    default:
+      colors:
+        foreground: not-a-color
+tomato: 1
+---
+
+hi
+";
+        let lints = lint(input);
+        assert_eq!(lints.len(), 3, "{lints:?}");
+        assert_eq!(lints[0], "deck.md:3:1: unknown front matter key 'potato'");
+        assert!(lints[1].starts_with("deck.md:5:1: invalid theme override: "), "{}", lints[1]);
+        assert_eq!(lints[2], "deck.md:9:1: unknown front matter key 'tomato'");
+    }
+
+    #[test]
+    fn snippets() {
+        let input = r"
+```rust +exec
+fn main() {}
+```
+
+```potato
+hi
+```
+
+```json +exec
+{}
+```
+
+```
+plain
+```
+";
+        let lints = lint(input);
+        assert_eq!(lints, &[
+            "deck.md:6:1: unknown snippet language 'potato'",
+            "deck.md:10:1: snippet can't be executed as there's no executor for 'json'",
+        ]);
+    }
+
+    #[test]
+    fn missing_image() {
+        let lints = lint("![](this-image-does-not-exist.png)\n");
+        assert_eq!(lints.len(), 1, "{lints:?}");
+        assert!(
+            lints[0].starts_with("deck.md:1:1: could not load image 'this-image-does-not-exist.png'"),
+            "{}",
+            lints[0]
+        );
+    }
+
+    #[test]
+    fn column_layouts() {
+        let input = r"
+<!-- column_layout: [1, 1, 1] -->
+<!-- column: 1 -->
+hi
+<!-- reset_layout -->
+
+<!-- column_layout: [1, 1] -->
+<!-- column: 0 -->
+<!-- column: 1 -->
+<!-- end_slide -->
+
+<!-- column_layout: [1, 1] -->
+<!-- potato -->
+";
+        let lints = lint(input);
+        assert_eq!(lints.len(), 3, "{lints:?}");
+        assert_eq!(lints[0], "deck.md:2:1: column layout never uses column(s) 0, 2");
+        assert!(lints[1].starts_with("deck.md:13:1: invalid command: unknown variant `potato`"), "{}", lints[1]);
+        assert_eq!(lints[2], "deck.md:12:1: column layout never uses column(s) 0, 1");
+    }
+
+    #[test]
+    fn included_files() {
+        let dir = tempfile::tempdir_in("/tmp").expect("failed to create tempdir");
+        let name = dir.path().file_name().unwrap().to_string_lossy();
+        fs::create_dir(dir.path().join("chapter")).expect("failed to create dir");
+        fs::write(dir.path().join("outer.md"), "<!-- include: chapter/inner.md -->\n<!-- include: potato.md -->\n")
+            .expect("write failed");
+        fs::write(dir.path().join("chapter/inner.md"), "hi\n\n<!-- potato -->\n\n![](missing.png)\n")
+            .expect("write failed");
+
+        let lints = lint(&format!("<!-- include: {name}/outer.md -->\n"));
+        assert_eq!(lints.len(), 3, "{lints:?}");
+        let inner = format!("{name}/chapter/inner.md");
+        assert!(lints[0].starts_with(&format!("{inner}:3:1: invalid command")), "{}", lints[0]);
+        assert!(
+            lints[1].starts_with(&format!("{inner}:5:1: could not load image '{name}/chapter/missing.png'")),
+            "{}",
+            lints[1]
+        );
+        assert!(
+            lints[2].starts_with(&format!("{name}/outer.md:2:1: could not include '{name}/potato.md'")),
+            "{}",
+            lints[2]
+        );
+    }
+
+    #[test]
+    fn text_commands() {
+        let input = r"
+<!-- section: Part 1: Intro -->
+<!-- section_contents: Part 1: Intro -->
+<!-- speaker_note: Remember: pause here -->
+";
+        let lints = lint(input);
+        assert!(lints.is_empty(), "{lints:?}");
+    }
+}
@@ -0,0 +1,228 @@
+use crate::{
+    markdown::{elements::Text, text::WeightedLine},
+    media::{image::Image, printer::ResourceProperties},
+    presentation::{AsRenderOperations, ImageProperties, ImageSize, RenderAsync, RenderAsyncState, RenderOperation},
+    processing::builder::DEFAULT_IMAGE_Z_INDEX,
+    render::{layout::Layout, properties::WindowSize},
+    style::{Color, TextStyle},
+    theme::Alignment,
+    third_party::RenderThirdParty,
+};
+use std::rc::Rc;
+use unicode_width::UnicodeWidthStr;
+
+// A non breaking space so the space reserved for a formula is never split when wrapping.
+const PLACEHOLDER: &str = "\u{a0}";
+
+/// A piece of a line that contains math formulas.
+#[derive(Debug)]
+pub(crate) enum MathSegment {
+    /// A piece of regular text.
+    Text(Text),
+
+    /// A formula, along with its source text, which is displayed until it's rendered.
+    Formula { source: Text, render: Rc<RenderThirdParty> },
+}
+
+/// Renders a line of text that contains formulas, placing each one of them inline as an image.
+#[derive(Debug)]
+pub(crate) struct RenderInlineMath {
+    segments: Vec<MathSegment>,
+    alignment: Alignment,
+    background_color: Option<Color>,
+}
+
+impl RenderInlineMath {
+    pub(crate) fn new(segments: Vec<MathSegment>, alignment: Alignment, background_color: Option<Color>) -> Self {
+        Self { segments, alignment, background_color }
+    }
+
+    fn formulas(&self) -> impl Iterator<Item = &Rc<RenderThirdParty>> {
+        self.segments.iter().filter_map(|segment| match segment {
+            MathSegment::Text(_) => None,
+            MathSegment::Formula { render, .. } => Some(render),
+        })
+    }
+
+    fn source_line(&self) -> WeightedLine {
+        let texts: Vec<_> = self
+            .segments
+            .iter()
+            .map(|segment| match segment {
+                MathSegment::Text(text) | MathSegment::Formula { source: text, .. } => text.clone(),
+            })
+            .collect();
+        texts.into()
+    }
+
+    // The number of columns a formula takes up when scaled down to be a single row tall.
+    fn formula_columns(image: &Image, dimensions: &WindowSize, max_columns: u16) -> u16 {
+        let (width, height) = image.dimensions();
+        let mut cell_ratio = dimensions.pixels_per_row() / dimensions.pixels_per_column();
+        if !cell_ratio.is_normal() {
+            // Assume cells are twice as tall as they are wide if we don't know their size.
+            cell_ratio = 2.0;
+        }
+        let columns = (width as f64 / height.max(1) as f64 * cell_ratio).ceil() as u16;
+        columns.clamp(1, max_columns.max(1))
+    }
+}
+
+impl AsRenderOperations for RenderInlineMath {
+    fn as_render_operations(&self, dimensions: &WindowSize) -> Vec<RenderOperation> {
+        let images: Option<Vec<Image>> = self.formulas().map(|render| render.image()).collect();
+        let Some(images) = images else {
+            // Display the formulas as text until they're rendered, or if rendering them failed.
+            return vec![RenderOperation::RenderText { line: self.source_line(), alignment: self.alignment.clone() }];
+        };
+
+        // Reserve space for each formula so that the line is wrapped as if they were text.
+        let max_columns = Layout::new(self.alignment.clone()).compute(dimensions, dimensions.columns).max_line_length;
+        let mut formula_columns = images.iter().map(|image| Self::formula_columns(image, dimensions, max_columns));
+        let mut texts = Vec::new();
+        for segment in &self.segments {
+            match segment {
+                MathSegment::Text(text) => texts.push(text.clone()),
+                MathSegment::Formula { .. } => {
+                    let columns = formula_columns.next().unwrap_or(1);
+                    texts.push(Text::new(PLACEHOLDER.repeat(columns as usize), TextStyle::default().math()));
+                }
+            }
+        }
+        let line = WeightedLine::from(texts);
+        let positioning = Layout::new(self.alignment.clone()).compute(dimensions, line.width() as u16);
+
+        let mut images = images.into_iter();
+        let mut operations = Vec::new();
+        for (index, row) in line.split(positioning.max_line_length as usize).enumerate() {
+            if index > 0 {
+                operations.push(RenderOperation::RenderLineBreak);
+            }
+            let row: Vec<_> = row
+                .into_iter()
+                .map(|chunk| {
+                    let (text, style) = chunk.into_parts();
+                    Text::new(text, style)
+                })
+                .collect();
+            let width: usize = row.iter().map(|text| text.content.width()).sum();
+            let mut column = Layout::new(self.alignment.clone()).compute(dimensions, width as u16).start_column;
+            let mut row_images = Vec::new();
+            for text in &row {
+                let columns = text.content.width() as u16;
+                if text.style.is_math() {
+                    if let Some(image) = images.next() {
+                        let properties = ImageProperties {
+                            z_index: DEFAULT_IMAGE_Z_INDEX,
+                            size: ImageSize::Positioned { column, columns, rows: 1 },
+                            restore_cursor: true,
+                            background_color: self.background_color,
+                        };
+                        row_images.push(RenderOperation::RenderImage(image, properties));
+                    }
+                }
+                column += columns;
+            }
+            operations.push(RenderOperation::RenderText { line: row.into(), alignment: self.alignment.clone() });
+            operations.extend(row_images);
+        }
+        operations
+    }
+}
+
+impl RenderAsync for RenderInlineMath {
+    fn start_render(&self) -> bool {
+        false
+    }
+
+    fn poll_state(&self) -> RenderAsyncState {
+        let mut finished = false;
+        let mut rendering = false;
+        for render in self.formulas() {
+            match render.poll_state() {
+                RenderAsyncState::JustFinishedRendering => finished = true,
+                RenderAsyncState::NotStarted | RenderAsyncState::Rendering { .. } => rendering = true,
+                RenderAsyncState::Rendered => (),
+            };
+        }
+        match (finished, rendering) {
+            (true, true) => RenderAsyncState::Rendering { modified: true },
+            (true, false) => RenderAsyncState::JustFinishedRendering,
+            (false, true) => RenderAsyncState::Rendering { modified: false },
+            (false, false) => RenderAsyncState::Rendered,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        media::register::ImageRegistry,
+        render::{
+            engine::{RenderEngine, RenderEngineOptions},
+            virt::VirtualTerminal,
+        },
+        theme::Margin,
+    };
+    use image::DynamicImage;
+
+    fn render(segments: Vec<MathSegment>) -> (Vec<String>, Vec<(u16, u16, u16)>) {
+        let operation = RenderInlineMath::new(segments, Alignment::Left { margin: Margin::Fixed(1) }, None);
+        // Cells are 10x20 pixels.
+        let dimensions = WindowSize { rows: 3, columns: 14, width: 140, height: 60 };
+        let mut terminal = VirtualTerminal::new(&dimensions);
+        let engine = RenderEngine::new(&mut terminal, dimensions.clone(), RenderEngineOptions::default());
+        let operations = operation.as_render_operations(&dimensions);
+        engine.render(operations.iter()).expect("render failed");
+        let grid = terminal.into_contents();
+        let rows = grid
+            .rows
+            .into_iter()
+            .map(|row| {
+                row.into_iter()
+                    .map(|c| c.character)
+                    .collect::<String>()
+                    .replace(PLACEHOLDER, "#")
+                    .trim_end()
+                    .to_string()
+            })
+            .collect();
+        let images = grid.images.into_iter().map(|image| (image.row, image.column, image.columns)).collect();
+        (rows, images)
+    }
+
+    fn formula(source: &str, width: u32, height: u32) -> MathSegment {
+        let image =
+            ImageRegistry::default().register_image(DynamicImage::new_rgb8(width, height)).expect("invalid image");
+        MathSegment::Formula {
+            source: Text::new(source, TextStyle::default().math()),
+            render: Rc::new(RenderThirdParty::with_image(Some(image))),
+        }
+    }
+
+    #[test]
+    fn inline_formulas() {
+        let segments = vec![
+            MathSegment::Text("a ".into()),
+            formula("$x$", 20, 20),
+            MathSegment::Text(" b c d".into()),
+            formula("$y$", 40, 20),
+        ];
+        let (rows, images) = render(segments);
+        assert_eq!(rows, &[" a ## b c d", " ####", ""]);
+        assert_eq!(images, &[(0, 3, 2), (1, 1, 4)]);
+    }
+
+    #[test]
+    fn pending_formulas() {
+        let pending = Rc::new(RenderThirdParty::with_image(None));
+        let segments = vec![MathSegment::Text("a ".into()), MathSegment::Formula {
+            source: Text::new("$x$", TextStyle::default().math()),
+            render: pending,
+        }];
+        let (rows, images) = render(segments);
+        assert_eq!(rows, &[" a $x$", "", ""]);
+        assert!(images.is_empty());
+    }
+}
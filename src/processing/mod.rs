@@ -1,7 +1,12 @@
 pub(crate) mod builder;
 pub(crate) mod code;
+pub(crate) mod contents;
 pub(crate) mod execution;
 pub(crate) mod footer;
+pub(crate) mod lint;
+pub(crate) mod math;
 pub(crate) mod modals;
 pub(crate) mod padding;
 pub(crate) mod separator;
+pub(crate) mod snippets;
+pub(crate) mod table;
@@ -13,22 +13,50 @@ use crate::{
     processing::padding::NumberPadder,
     render::properties::WindowSize,
     style::{Colors, TextStyle},
-    theme::Margin,
+    theme::{Alignment, Margin},
 };
-use std::{iter, rc::Rc};
-use unicode_width::UnicodeWidthStr;
+use std::{cell::RefCell, iter, rc::Rc};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 static MODAL_Z_INDEX: i32 = -1;
+static NO_MATCHES_TEXT: &str = "No matching slides";
+static SECTION_INDENTATION: &str = "  ";
+
+/// A slide in the slide index.
+#[derive(Clone, Debug)]
+pub(crate) struct IndexEntry {
+    /// The slide's title, or a placeholder if it doesn't have one.
+    pub(crate) title: Line,
+
+    /// Whether the slide has an explicit title.
+    pub(crate) has_title: bool,
+
+    /// The section the slide belongs to.
+    pub(crate) section: Option<String>,
+
+    /// Whether the slide contains a table of contents.
+    pub(crate) has_table_of_contents: bool,
+}
+
+/// The entries in the slide index, which are shared with anything that needs to list slides.
+pub(crate) type SharedIndexEntries = Rc<RefCell<Vec<IndexEntry>>>;
 
 #[derive(Default)]
 pub(crate) struct IndexBuilder {
-    titles: Vec<Line>,
+    entries: SharedIndexEntries,
     background: Option<Image>,
 }
 
 impl IndexBuilder {
-    pub(crate) fn add_title(&mut self, title: Line) {
-        self.titles.push(title);
+    pub(crate) fn add_entry(&mut self, entry: IndexEntry) {
+        self.entries.borrow_mut().push(entry);
+    }
+
+    /// Get the entries in the index.
+    ///
+    /// These keep growing as slides are added to the index.
+    pub(crate) fn entries(&self) -> SharedIndexEntries {
+        self.entries.clone()
     }
 
     pub(crate) fn set_background(&mut self, background: Image) {
@@ -37,18 +65,31 @@ impl IndexBuilder {
 
     pub(crate) fn build(self, theme: &PresentationTheme, state: PresentationState) -> Vec<RenderOperation> {
         let mut builder = ModalBuilder::new("Slides");
-        let padder = NumberPadder::new(self.titles.len());
-        for (index, mut title) in self.titles.into_iter().enumerate() {
+        // Make sure the placeholder shown when the filter matches nothing fits.
+        builder.min_width = NO_MATCHES_TEXT.width() as u16;
+        let entries = self.entries.borrow();
+        let padder = NumberPadder::new(entries.len());
+        let titles = entries.iter().map(|entry| entry.title.text()).collect();
+        let sections: Vec<_> = entries.iter().map(|entry| entry.section.clone()).collect();
+        for (index, entry) in entries.iter().enumerate() {
+            let mut title = entry.title.clone();
             let index = padder.pad_right(index + 1);
-            title.0.insert(0, format!("{index}: ").into());
+            // Slides that belong to a section are nested under its heading.
+            let indentation = if entry.section.is_some() { SECTION_INDENTATION } else { "" };
+            title.0.insert(0, format!("{indentation}{index}: ").into());
             builder.content.push(title);
         }
+        for section in sections.iter().flatten() {
+            builder.min_width = builder.min_width.max(section.width() as u16);
+        }
         let base_color = theme.modals.colors.merge(&theme.default_style.colors);
         let selection_style = TextStyle::default().colors(theme.modals.selection_colors).bold();
         let ModalContent { prefix, content, suffix, content_width } = builder.build(base_color);
         let drawer = IndexDrawer {
             prefix,
             rows: content,
+            titles,
+            sections,
             suffix,
             state,
             content_width,
@@ -63,6 +104,8 @@ impl IndexBuilder {
 struct IndexDrawer {
     prefix: Vec<RenderOperation>,
     rows: Vec<ContentRow>,
+    titles: Vec<String>,
+    sections: Vec<Option<String>>,
     suffix: Vec<RenderOperation>,
     content_width: u16,
     state: PresentationState,
@@ -73,31 +116,162 @@ struct IndexDrawer {
 impl AsRenderOperations for IndexDrawer {
     fn as_render_operations(&self, dimensions: &WindowSize) -> Vec<RenderOperation> {
         let current_slide_index = self.state.current_slide_index();
-        let max_rows = (dimensions.rows as f64 * 0.8) as u16;
-        let (skip, take) = match self.rows.len() as u16 > max_rows {
+        let index = self.state.slide_index();
+        let matches: Vec<_> = (0..self.rows.len()).filter(|slide| index.matches(&self.titles[*slide])).collect();
+        let grouped = self.group_by_section(&matches);
+        let cursor = grouped.iter().position(|row| *row == IndexRow::Slide(index.cursor)).unwrap_or_default();
+        // Leave room for the separator and filter rows at the bottom.
+        let max_rows = ((dimensions.rows as f64 * 0.8) as usize).saturating_sub(2).max(1);
+        let (skip, take) = match grouped.len() > max_rows {
             true => {
-                let start = (current_slide_index as u16).saturating_sub(max_rows / 2);
-                let start = start.min(self.rows.len() as u16 - max_rows);
-                (start as usize, max_rows as usize)
+                let start = cursor.saturating_sub(max_rows / 2);
+                let start = start.min(grouped.len() - max_rows);
+                (start, max_rows)
             }
-            false => (0, self.rows.len()),
+            false => (0, grouped.len()),
         };
-        let visible_rows = self.rows.iter().enumerate().skip(skip).take(take);
-        let mut operations = vec![CenterModalContent::new(self.content_width, take, self.background.clone()).into()];
-        operations.extend(self.prefix.iter().cloned());
-        for (index, row) in visible_rows {
-            let mut row = row.clone();
-            if index == current_slide_index {
+        let mut rows = Vec::new();
+        for row in grouped.into_iter().skip(skip).take(take) {
+            let slide = match row {
+                IndexRow::Section(name) => {
+                    let text = Text::new(name, TextStyle::default().bold());
+                    rows.push(ModalBuilder::build_line(vec![text], self.content_width));
+                    continue;
+                }
+                IndexRow::Slide(slide) => slide,
+            };
+            let mut row = self.rows[slide].clone();
+            if slide == index.cursor {
                 row = row.with_style(self.selection_style);
+            } else if slide == current_slide_index {
+                row = row.with_style(TextStyle::default().bold());
             }
+            rows.push(row);
+        }
+        if matches.is_empty() {
+            let text = Text::new(NO_MATCHES_TEXT, TextStyle::default().italics());
+            rows.push(ModalBuilder::build_line(vec![text], self.content_width));
+        }
+        let content_height = rows.len() + 2;
+        let mut operations =
+            vec![CenterModalContent::new(self.content_width, content_height, self.background.clone()).into()];
+        operations.extend(self.prefix.iter().cloned());
+        for row in rows {
             let operation = RenderOperation::RenderText { line: row.build(), alignment: Default::default() };
             operations.extend([operation, RenderOperation::RenderLineBreak]);
         }
+        operations.extend(Border::Separator.render_line(self.content_width));
+        let filter = self.build_filter_row(&index.filter);
+        operations.extend([
+            RenderOperation::RenderText { line: filter.build(), alignment: Default::default() },
+            RenderOperation::RenderLineBreak,
+        ]);
         operations.extend(self.suffix.iter().cloned());
         operations
     }
 }
 
+/// A row in the slide index.
+#[derive(Debug, PartialEq)]
+enum IndexRow<'a> {
+    Section(&'a str),
+    Slide(usize),
+}
+
+impl IndexDrawer {
+    // Insert a heading every time the section changes between the slides being displayed.
+    fn group_by_section(&self, slides: &[usize]) -> Vec<IndexRow<'_>> {
+        let mut rows = Vec::new();
+        let mut last_section = None;
+        for slide in slides {
+            let section = self.sections[*slide].as_deref();
+            if section.is_some() && section != last_section {
+                rows.extend(section.map(IndexRow::Section));
+            }
+            last_section = section;
+            rows.push(IndexRow::Slide(*slide));
+        }
+        rows
+    }
+
+    fn build_filter_row(&self, filter: &str) -> ContentRow {
+        if filter.is_empty() {
+            let text = Text::new("Type to filter", TextStyle::default().italics());
+            return ModalBuilder::build_line(vec![text], self.content_width);
+        }
+        let label = "Filter: ";
+        // Only keep the end of the filter if it's too long so the last typed characters are visible.
+        let available = (self.content_width as usize).saturating_sub(6 + label.len());
+        let mut width = 0;
+        let mut visible: Vec<_> = filter
+            .chars()
+            .rev()
+            .take_while(|c| {
+                width += c.width().unwrap_or(0);
+                width <= available
+            })
+            .collect();
+        visible.reverse();
+        let text =
+            vec![Text::new(label, TextStyle::default().bold()), Text::from(visible.into_iter().collect::<String>())];
+        ModalBuilder::build_line(text, self.content_width)
+    }
+}
+
+pub(crate) struct SearchBarBuilder;
+
+impl SearchBarBuilder {
+    pub(crate) fn build(theme: &PresentationTheme, state: PresentationState) -> Vec<RenderOperation> {
+        let colors = theme.search.colors.merge(&theme.modals.colors).merge(&theme.default_style.colors);
+        vec![RenderOperation::RenderDynamic(Rc::new(SearchBarDrawer { colors, state }))]
+    }
+
+    /// The style used to highlight text that matches the search query.
+    pub(crate) fn match_style(theme: &PresentationTheme) -> TextStyle {
+        let colors = theme.search.match_colors.merge(&theme.modals.selection_colors);
+        TextStyle::default().colors(colors).bold().underlined()
+    }
+}
+
+/// Draws the search query, along with the position within the matches, at the bottom of the screen.
+#[derive(Debug)]
+struct SearchBarDrawer {
+    colors: Colors,
+    state: PresentationState,
+}
+
+impl AsRenderOperations for SearchBarDrawer {
+    fn as_render_operations(&self, dimensions: &WindowSize) -> Vec<RenderOperation> {
+        let search = self.state.search();
+        let mut text = format!("/{}", search.query);
+        if !search.editing {
+            match search.current {
+                Some(index) => text.push_str(&format!("  [{}/{}]", index + 1, search.matches.len())),
+                None => text.push_str("  [no matches]"),
+            };
+        }
+        // Keep the end of the text visible if it doesn't fit and cover the entire row otherwise.
+        let columns = dimensions.columns as usize;
+        let mut width = 0;
+        let mut visible: Vec<_> = text
+            .chars()
+            .rev()
+            .take_while(|c| {
+                width += c.width().unwrap_or(0);
+                width <= columns
+            })
+            .collect();
+        visible.reverse();
+        let mut text: String = visible.into_iter().collect();
+        text.push_str(&" ".repeat(columns.saturating_sub(text.width())));
+        vec![
+            RenderOperation::SetColors(self.colors),
+            RenderOperation::JumpToBottomRow { index: 0 },
+            RenderOperation::RenderText { line: text.into(), alignment: Alignment::Left { margin: Margin::Fixed(0) } },
+        ]
+    }
+}
+
 #[derive(Default)]
 pub(crate) struct KeyBindingsModalBuilder {
     background: Option<Image>,
@@ -119,9 +293,13 @@ impl KeyBindingsModalBuilder {
             Self::build_line("Last slide", &config.last_slide),
             Self::build_line("Go to slide", &config.go_to_slide),
             Self::build_line("Execute code", &config.execute_code),
+            Self::build_line("Cancel code execution", &config.cancel_execution),
             Self::build_line("Reload", &config.reload),
             Self::build_line("Toggle slide index", &config.toggle_slide_index),
             Self::build_line("Close modal", &config.close_modal),
+            Self::build_line("Search", &config.search),
+            Self::build_line("Scroll up", &config.scroll_up),
+            Self::build_line("Scroll down", &config.scroll_down),
             Self::build_line("Exit", &config.exit),
         ]);
         let lines = builder.content.len();
@@ -148,18 +326,19 @@ impl KeyBindingsModalBuilder {
 struct ModalBuilder {
     heading: String,
     content: Vec<Line>,
+    min_width: u16,
 }
 
 impl ModalBuilder {
     fn new<S: Into<String>>(heading: S) -> Self {
-        Self { heading: heading.into(), content: Vec::new() }
+        // Ensure we have a minimum width so it doesn't look too narrow.
+        Self { heading: heading.into(), content: Vec::new(), min_width: 12 }
     }
 
     fn build(self, colors: Colors) -> ModalContent {
         let longest_line = self.content.iter().map(Line::width).max().unwrap_or(0) as u16;
         let longest_line = longest_line.max(self.heading.len() as u16);
-        // Ensure we have a minimum width so it doesn't look too narrow.
-        let longest_line = longest_line.max(12);
+        let longest_line = longest_line.max(self.min_width);
         // The final text looks like "|  <content>  |"
         let content_width = longest_line + 6;
         let mut prefix = vec![RenderOperation::SetColors(colors)];
@@ -187,7 +366,7 @@ impl ModalBuilder {
         let padding = missing / 2;
         let mut output = " ".repeat(padding);
         output.push_str(&text);
-        output.extend(iter::repeat(' ').take(padding));
+        output.extend(iter::repeat_n(' ', padding));
         output
     }
 
@@ -319,3 +498,55 @@ impl From<CenterModalContent> for RenderOperation {
         Self::RenderDynamic(Rc::new(op))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::render::{
+        engine::{RenderEngine, RenderEngineOptions},
+        virt::VirtualTerminal,
+    };
+
+    fn entry(title: &str, section: Option<&str>) -> IndexEntry {
+        IndexEntry {
+            title: Line::from(title),
+            has_title: true,
+            section: section.map(String::from),
+            has_table_of_contents: false,
+        }
+    }
+
+    #[test]
+    fn index_sections() {
+        let mut builder = IndexBuilder::default();
+        for entry in [entry("Welcome", None), entry("Hello", Some("Intro")), entry("Deep dive", Some("Details"))] {
+            builder.add_entry(entry);
+        }
+        let operations = builder.build(&Default::default(), Default::default());
+        let dimensions = WindowSize { rows: 16, columns: 40, width: 400, height: 320 };
+        let mut terminal = VirtualTerminal::new(&dimensions);
+        let engine = RenderEngine::new(&mut terminal, dimensions.clone(), RenderEngineOptions::default());
+        engine.render(operations.iter()).expect("render failed");
+        let rows: Vec<_> = terminal
+            .into_contents()
+            .rows
+            .into_iter()
+            .map(|row| row.into_iter().map(|c| c.character).collect::<String>().trim().to_string())
+            .filter(|row| !row.is_empty())
+            .collect();
+        let expected = &[
+            "┌──────────────────────┐",
+            "│        Slides        │",
+            "├──────────────────────┤",
+            "│  1: Welcome          │",
+            "│  Intro               │",
+            "│    2: Hello          │",
+            "│  Details             │",
+            "│    3: Deep dive      │",
+            "├──────────────────────┤",
+            "│  Type to filter      │",
+            "└──────────────────────┘",
+        ];
+        assert_eq!(rows, expected);
+    }
+}
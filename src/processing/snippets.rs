@@ -0,0 +1,450 @@
+use crate::{
+    execute::{ProcessStatus, SnippetExecutor},
+    markdown::{
+        elements::{MarkdownElement, SourcePosition},
+        parse::MarkdownParser,
+    },
+    presentation::PresentationMetadata,
+    processing::{
+        builder::{CommentCommand, PresentationBuilder, PresentationBuilderOptions},
+        code::{CodeBlockParser, ExternalFile, Snippet, SnippetLanguage, SnippetStdin},
+    },
+    resource::Resources,
+};
+use comrak::Arena;
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+/// The language used in a code block that contains the output a snippet is expected to produce.
+const EXPECTED_OUTPUT_LANGUAGE: &str = "expected-output";
+
+/// Whether a code block, given its info string, contains the expected output for the snippet
+/// before it.
+pub(crate) fn is_expected_output(info: &str) -> bool {
+    info.split_whitespace().next() == Some(EXPECTED_OUTPUT_LANGUAGE)
+}
+
+/// The language used in a code block that contains the standard input for the snippet before it.
+const STDIN_LANGUAGE: &str = "stdin";
+
+/// Whether a code block, given its info string, contains the standard input for the snippet
+/// before it.
+pub(crate) fn is_stdin(info: &str) -> bool {
+    info.split_whitespace().next() == Some(STDIN_LANGUAGE)
+}
+
+/// Runs every executable snippet in a presentation without displaying it.
+pub(crate) struct SnippetRunner<'a> {
+    resources: &'a mut Resources,
+    code_executor: &'a SnippetExecutor,
+    options: PresentationBuilderOptions,
+    included_files: Vec<IncludedFile>,
+    runs: Vec<SnippetRun>,
+}
+
+impl<'a> SnippetRunner<'a> {
+    /// Construct a new runner.
+    pub(crate) fn new(
+        resources: &'a mut Resources,
+        code_executor: &'a SnippetExecutor,
+        options: PresentationBuilderOptions,
+    ) -> Self {
+        Self { resources, code_executor, options, included_files: Vec::new(), runs: Vec::new() }
+    }
+
+    /// Run the executable snippets in the given elements, in the order they appear in, including
+    /// the ones in any files they include.
+    pub(crate) fn run(mut self, elements: &[(MarkdownElement, SourcePosition)]) -> Vec<SnippetRun> {
+        // The front matter can change the prefix commands use, which we need to find includes.
+        if let Some((MarkdownElement::FrontMatter(contents), _)) = elements.first() {
+            if let Ok(PresentationMetadata { options: Some(options), .. }) = serde_yaml::from_str(contents) {
+                self.options.merge(options);
+            }
+        }
+        self.run_elements(elements);
+        self.runs
+    }
+
+    fn run_elements(&mut self, elements: &[(MarkdownElement, SourcePosition)]) {
+        let mut elements = elements.iter().map(|(element, _)| element).peekable();
+        while let Some(element) = elements.next() {
+            match element {
+                MarkdownElement::Snippet { info, code, source_position } => {
+                    let stdin = elements.next_if(|element| is_snippet(element, is_stdin)).map(snippet_code);
+                    let expected_output =
+                        elements.next_if(|element| is_snippet(element, is_expected_output)).map(snippet_code);
+                    if let Some(outcome) = self.run_snippet(info, code, stdin, expected_output) {
+                        self.push(*source_position, outcome);
+                    }
+                }
+                MarkdownElement::Comment { comment, source_position } => {
+                    if let Some(path) = self.include_path(comment) {
+                        self.run_include(&path, *source_position);
+                    }
+                }
+                _ => (),
+            };
+        }
+    }
+
+    fn push(&mut self, position: SourcePosition, outcome: SnippetOutcome) {
+        let file = self.included_files.last().map(|file| file.path.clone());
+        self.runs.push(SnippetRun { file, position, outcome });
+    }
+
+    fn include_path(&self, comment: &str) -> Option<PathBuf> {
+        let comment = comment.trim();
+        if PresentationBuilder::should_ignore_comment(comment, &self.options.command_prefix) {
+            return None;
+        }
+        match comment.trim_start_matches(&self.options.command_prefix).parse() {
+            Ok(CommentCommand::Include(path)) => Some(path),
+            _ => None,
+        }
+    }
+
+    // Paths in included files are relative to the file that contains them, like when building.
+    fn resolve_path(&self, path: &Path) -> PathBuf {
+        match self.included_files.last().and_then(|file| file.path.parent()) {
+            Some(parent) => parent.join(path),
+            None => path.into(),
+        }
+    }
+
+    fn run_include(&mut self, path: &Path, position: SourcePosition) {
+        let path = self.resolve_path(path);
+        let failed =
+            |e: &dyn fmt::Display| SnippetOutcome::Failed(format!("could not include '{}': {e}", path.display()));
+        let canonical_path = match self.resources.canonicalize(&path) {
+            Ok(path) => path,
+            Err(e) => return self.push(position, failed(&e)),
+        };
+        if self.included_files.iter().any(|file| file.canonical_path == canonical_path) {
+            return self.push(position, failed(&"file includes itself"));
+        }
+        let contents = match self.resources.included_file(&path) {
+            Ok(contents) => contents,
+            Err(e) => return self.push(position, failed(&e)),
+        };
+        let arena = Arena::new();
+        let elements = match MarkdownParser::new(&arena).parse(&contents) {
+            Ok(elements) => elements,
+            Err(e) => return self.push(position, failed(&e)),
+        };
+        self.included_files.push(IncludedFile { path, canonical_path });
+        self.run_elements(&elements);
+        self.included_files.pop();
+    }
+
+    fn run_snippet(
+        &mut self,
+        info: &str,
+        code: &str,
+        stdin: Option<&str>,
+        expected_output: Option<&str>,
+    ) -> Option<SnippetOutcome> {
+        let mut snippet = match CodeBlockParser::parse(info.into(), code.into()) {
+            Ok(snippet) => snippet,
+            Err(e) => return Some(SnippetOutcome::Failed(format!("invalid snippet: {e}"))),
+        };
+        if let Some(stdin) = stdin {
+            if let Err(e) = CodeBlockParser::set_stdin(&mut snippet, stdin.into()) {
+                return Some(SnippetOutcome::Failed(format!("invalid snippet: {e}")));
+            }
+        }
+        if !snippet.attributes.execute && !snippet.attributes.execute_replace {
+            return None;
+        }
+        if let Some(SnippetStdin::File(path)) = &mut snippet.attributes.stdin {
+            *path = self.resolve_path(path);
+        }
+        // These take over the terminal so they can't run without one.
+        if snippet.attributes.acquire_terminal {
+            return Some(SnippetOutcome::Skipped("needs a terminal"));
+        }
+        let snippet = match snippet.language {
+            SnippetLanguage::File => match self.load_external_snippet(snippet) {
+                Ok(snippet) => snippet,
+                Err(e) => return Some(SnippetOutcome::Failed(e)),
+            },
+            _ => snippet,
+        };
+        let output = match self.code_executor.execute_captured(&snippet) {
+            Ok(output) => output,
+            Err(e) => return Some(SnippetOutcome::Failed(e.to_string())),
+        };
+        let outcome = match expected_output {
+            _ if matches!(output.status, ProcessStatus::TimedOut) => SnippetOutcome::Failed("timed out".into()),
+            // Whatever a snippet writes to stderr usually explains why it failed, but not everything uses it.
+            _ if !matches!(output.status, ProcessStatus::Success) => match output.errors.is_empty() {
+                true => SnippetOutcome::Failed(output.output),
+                false => SnippetOutcome::Failed(output.errors),
+            },
+            Some(expected) if expected.trim_end() != output.output.trim_end() => {
+                SnippetOutcome::OutputMismatch { expected: expected.into(), actual: output.output }
+            }
+            _ => SnippetOutcome::Passed,
+        };
+        Some(outcome)
+    }
+
+    fn load_external_snippet(&mut self, mut snippet: Snippet) -> Result<Snippet, String> {
+        let file: ExternalFile =
+            serde_yaml::from_str(&snippet.contents).map_err(|e| format!("invalid snippet: {e}"))?;
+        let contents = self
+            .resources
+            .external_snippet(self.resolve_path(&file.path))
+            .map_err(|e| format!("failed to load {}: {e}", file.path.display()))?;
+        snippet.language = file.language;
+        snippet.contents = contents;
+        Ok(snippet)
+    }
+}
+
+struct IncludedFile {
+    path: PathBuf,
+    canonical_path: PathBuf,
+}
+
+fn is_snippet(element: &MarkdownElement, predicate: fn(&str) -> bool) -> bool {
+    matches!(element, MarkdownElement::Snippet { info, .. } if predicate(info))
+}
+
+fn snippet_code(element: &MarkdownElement) -> &str {
+    match element {
+        MarkdownElement::Snippet { code, .. } => code,
+        _ => "",
+    }
+}
+
+/// The result of running a snippet.
+#[derive(Debug)]
+pub(crate) struct SnippetRun {
+    /// The included file the snippet is in, relative to the presentation, if it's not in the
+    /// presentation file itself.
+    pub(crate) file: Option<PathBuf>,
+
+    /// The position in the file the snippet is at.
+    pub(crate) position: SourcePosition,
+
+    /// How running it went.
+    pub(crate) outcome: SnippetOutcome,
+}
+
+#[derive(Debug)]
+pub(crate) enum SnippetOutcome {
+    /// The snippet ran successfully and produced the expected output, if any.
+    Passed,
+
+    /// The snippet failed to run, along with the reason why.
+    Failed(String),
+
+    /// The snippet ran successfully but produced a different output than the expected one.
+    OutputMismatch { expected: String, actual: String },
+
+    /// The snippet can't be run, along with the reason why.
+    Skipped(&'static str),
+}
+
+/// The results of running every snippet in a presentation.
+#[derive(Debug)]
+pub struct SnippetReport {
+    path: PathBuf,
+    runs: Vec<SnippetRun>,
+}
+
+impl SnippetReport {
+    pub(crate) fn new(path: PathBuf, runs: Vec<SnippetRun>) -> Self {
+        Self { path, runs }
+    }
+
+    /// Whether none of the snippets failed.
+    pub fn is_success(&self) -> bool {
+        self.runs.iter().all(|run| matches!(run.outcome, SnippetOutcome::Passed | SnippetOutcome::Skipped(_)))
+    }
+}
+
+impl fmt::Display for SnippetReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Included files are relative to the presentation so they're displayed relative to wherever it is.
+        let directory = self.path.parent().unwrap_or(Path::new(""));
+        let (mut passed, mut failed, mut skipped) = (0, 0, 0);
+        for SnippetRun { file, position, outcome } in &self.runs {
+            let path = match file {
+                Some(file) => directory.join(file),
+                None => self.path.clone(),
+            };
+            write!(f, "{}:{position}: ", path.display())?;
+            match outcome {
+                SnippetOutcome::Passed => {
+                    passed += 1;
+                    writeln!(f, "ok")?;
+                }
+                SnippetOutcome::Failed(error) => {
+                    failed += 1;
+                    writeln!(f, "failed")?;
+                    write_indented(f, error, 2)?;
+                }
+                SnippetOutcome::OutputMismatch { expected, actual } => {
+                    failed += 1;
+                    writeln!(f, "unexpected output")?;
+                    writeln!(f, "  expected:")?;
+                    write_indented(f, expected, 4)?;
+                    writeln!(f, "  got:")?;
+                    write_indented(f, actual, 4)?;
+                }
+                SnippetOutcome::Skipped(reason) => {
+                    skipped += 1;
+                    writeln!(f, "skipped: {reason}")?;
+                }
+            };
+        }
+        writeln!(f, "{passed} passed, {failed} failed, {skipped} skipped")
+    }
+}
+
+fn write_indented(f: &mut fmt::Formatter<'_>, text: &str, indent: usize) -> fmt::Result {
+    for line in text.lines() {
+        writeln!(f, "{:indent$}{line}", "")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::markdown::parse::MarkdownParser;
+    use rstest::rstest;
+    use std::fs;
+
+    fn run(input: &str) -> SnippetReport {
+        let arena = Arena::new();
+        let elements = MarkdownParser::new(&arena).parse(input).expect("parse failed");
+        let mut resources = Resources::new("/tmp", Default::default());
+        let executor = SnippetExecutor::new(Default::default(), "/tmp".into()).expect("invalid executor");
+        let runs = SnippetRunner::new(&mut resources, &executor, Default::default()).run(&elements);
+        SnippetReport::new("deck.md".into(), runs)
+    }
+
+    #[test]
+    fn report() {
+        let input = r"
+```bash +exec
+echo hi
+```
+
+```expected-output
+hi
+```
+
+```bash +exec
+echo bye
+```
+
+```expected-output
+hi
+```
+
+```bash +exec_replace
+echo oops >&2
+exit 1
+```
+
+```bash
+this one isn't executed
+```
+
+```bash +exec +acquire_terminal
+vim
+```
+";
+        let report = run(input);
+        let expected = "\
+deck.md:2:1: ok
+deck.md:10:1: unexpected output
+  expected:
+    hi
+  got:
+    bye
+deck.md:18:1: failed
+  oops
+deck.md:27:1: skipped: needs a terminal
+1 passed, 2 failed, 1 skipped
+";
+        assert_eq!(report.to_string(), expected);
+        assert!(!report.is_success());
+    }
+
+    #[test]
+    fn stdin() {
+        let input = r"
+```bash +exec
+cat
+```
+
+```stdin
+hi
+```
+
+```expected-output
+hi
+```
+";
+        let report = run(input);
+        assert_eq!(report.to_string(), "deck.md:2:1: ok\n1 passed, 0 failed, 0 skipped\n");
+    }
+
+    #[test]
+    fn included_files() {
+        let dir = tempfile::tempdir_in("/tmp").expect("failed to create tempdir");
+        let name = dir.path().file_name().unwrap().to_string_lossy();
+        fs::create_dir(dir.path().join("chapter")).expect("failed to create dir");
+        fs::write(dir.path().join("chapter/outer.md"), "<!-- include: inner.md -->\n").expect("write failed");
+        fs::write(dir.path().join("chapter/inner.md"), "hi\n\n```bash +exec\necho hi\n```\n").expect("write failed");
+
+        let report = run(&format!("<!-- include: {name}/chapter/outer.md -->\n<!-- include: potato.md -->\n"));
+        let report = report.to_string();
+        let lines: Vec<_> = report.lines().collect();
+        assert_eq!(lines.len(), 4, "{report}");
+        assert_eq!(lines[0], format!("{name}/chapter/inner.md:3:1: ok"));
+        assert_eq!(lines[1], "deck.md:2:1: failed");
+        assert!(lines[2].starts_with("  could not include 'potato.md'"), "{report}");
+        assert_eq!(lines[3], "1 passed, 1 failed, 0 skipped");
+    }
+
+    #[rstest]
+    #[case::passed("echo hi\necho oops >&2", "deck.md:1:1: ok\n1 passed, 0 failed, 0 skipped\n")]
+    #[case::failed("echo hi\necho oops >&2\nexit 1", "deck.md:1:1: failed\n  oops\n0 passed, 1 failed, 0 skipped\n")]
+    #[case::failed_without_errors("echo bye\nexit 1", "deck.md:1:1: failed\n  bye\n0 passed, 1 failed, 0 skipped\n")]
+    fn stderr(#[case] code: &str, #[case] expected: &str) {
+        let report = run(&format!("```bash +exec\n{code}\n```\n\n```expected-output\nhi\n```\n"));
+        assert_eq!(report.to_string(), expected);
+    }
+
+    #[test]
+    fn timeout() {
+        let report = run("```bash +exec +timeout:200ms\nsleep 10\n```\n");
+        assert_eq!(report.to_string(), "deck.md:1:1: failed\n  timed out\n0 passed, 1 failed, 0 skipped\n");
+    }
+
+    #[test]
+    fn stdin_file_in_included_file() {
+        let dir = tempfile::tempdir_in("/tmp").expect("failed to create tempdir");
+        let name = dir.path().file_name().unwrap().to_string_lossy();
+        fs::create_dir(dir.path().join("chapter")).expect("failed to create dir");
+        fs::write(dir.path().join("chapter/input.txt"), "hi\n").expect("write failed");
+        let chapter = "```bash +exec +stdin:input.txt\ncat\n```\n\n```expected-output\nhi\n```\n";
+        fs::write(dir.path().join("chapter/inner.md"), chapter).expect("write failed");
+
+        let report = run(&format!("<!-- include: {name}/chapter/inner.md -->\n"));
+        assert_eq!(report.to_string(), format!("{name}/chapter/inner.md:1:1: ok\n1 passed, 0 failed, 0 skipped\n"));
+    }
+
+    #[test]
+    fn success() {
+        let report = run("```bash +exec\necho hi\n```\n");
+        assert!(report.is_success());
+    }
+}
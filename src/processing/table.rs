@@ -0,0 +1,382 @@
+use crate::{
+    markdown::{
+        elements::{ColumnAlignment, Text},
+        text::{WeightedLine, WeightedTextRef},
+    },
+    presentation::{AsRenderOperations, RenderOperation},
+    render::{layout::Layout, properties::WindowSize},
+    style::{Colors, TextStyle},
+    theme::{Alignment, TableBorder, TableStyle},
+};
+use std::iter;
+use unicode_width::UnicodeWidthStr;
+
+const DEFAULT_PADDING: u8 = 1;
+
+/// The characters used to draw a table's borders.
+#[derive(Debug)]
+struct BorderCharacters {
+    horizontal: char,
+    vertical: char,
+    cross: char,
+    top_left: char,
+    top: char,
+    top_right: char,
+    left: char,
+    right: char,
+    bottom_left: char,
+    bottom: char,
+    bottom_right: char,
+}
+
+impl BorderCharacters {
+    const LIGHT: Self = Self::new(['─', '│', '┼', '┌', '┬', '┐', '├', '┤', '└', '┴', '┘']);
+    const HEAVY: Self = Self::new(['━', '┃', '╋', '┏', '┳', '┓', '┣', '┫', '┗', '┻', '┛']);
+    const ROUNDED: Self = Self::new(['─', '│', '┼', '╭', '┬', '╮', '├', '┤', '╰', '┴', '╯']);
+    const ASCII: Self = Self::new(['-', '|', '+', '+', '+', '+', '+', '+', '+', '+', '+']);
+
+    // Takes the characters in the same order as the fields in this type.
+    const fn new(characters: [char; 11]) -> Self {
+        let [horizontal, vertical, cross, top_left, top, top_right, left, right, bottom_left, bottom, bottom_right] =
+            characters;
+        Self { horizontal, vertical, cross, top_left, top, top_right, left, right, bottom_left, bottom, bottom_right }
+    }
+
+    fn from_border(border: &TableBorder) -> Option<&'static Self> {
+        match border {
+            TableBorder::None => None,
+            TableBorder::Light => Some(&Self::LIGHT),
+            TableBorder::Heavy => Some(&Self::HEAVY),
+            TableBorder::Rounded => Some(&Self::ROUNDED),
+            TableBorder::Ascii => Some(&Self::ASCII),
+        }
+    }
+}
+
+/// Renders a table, wrapping the contents of its cells if it doesn't fit in the window.
+#[derive(Debug)]
+pub(crate) struct TableGenerator {
+    header: Vec<WeightedLine>,
+    rows: Vec<Vec<WeightedLine>>,
+    alignments: Vec<ColumnAlignment>,
+    alignment: Alignment,
+    style: TableStyle,
+    content: String,
+}
+
+impl TableGenerator {
+    pub(crate) fn new(
+        header: Vec<WeightedLine>,
+        rows: Vec<Vec<WeightedLine>>,
+        alignments: Vec<ColumnAlignment>,
+        style: TableStyle,
+    ) -> Self {
+        let content = iter::once(&header)
+            .chain(&rows)
+            .map(|row| row.iter().map(WeightedLine::text).collect::<Vec<_>>().join(" "))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let alignment = style.alignment.clone().unwrap_or_default();
+        Self { header, rows, alignments, alignment, style, content }
+    }
+
+    fn columns(&self) -> usize {
+        self.header.len()
+    }
+
+    fn natural_widths(&self) -> Vec<usize> {
+        (0..self.columns())
+            .map(|column| {
+                iter::once(&self.header)
+                    .chain(&self.rows)
+                    .filter_map(|row| row.get(column))
+                    .map(WeightedLine::width)
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect()
+    }
+
+    // Shrinks the widest columns until the table fits in `available` columns.
+    //
+    // Columns that are narrower than an even share of the remaining space keep their width and
+    // the rest of the space is split evenly among the others.
+    fn fit_widths(widths: &[usize], available: usize) -> Vec<usize> {
+        if widths.iter().sum::<usize>() <= available {
+            return widths.to_vec();
+        }
+        let mut output = vec![0; widths.len()];
+        let mut pending: Vec<_> = (0..widths.len()).collect();
+        let mut budget = available;
+        loop {
+            let share = budget / pending.len();
+            let (fitting, rest): (Vec<_>, Vec<_>) = pending.iter().partition(|index| widths[**index] <= share);
+            if fitting.is_empty() {
+                break;
+            }
+            for index in fitting {
+                output[index] = widths[index];
+                budget -= widths[index];
+            }
+            pending = rest;
+        }
+        let share = budget / pending.len();
+        let mut extra = budget % pending.len();
+        for index in pending {
+            output[index] = share + usize::from(extra > 0);
+            extra = extra.saturating_sub(1);
+        }
+        // Columns need at least one character to make progress when wrapping.
+        output.into_iter().map(|width| width.max(1)).collect()
+    }
+
+    fn into_texts(chunks: Vec<WeightedTextRef>) -> Vec<Text> {
+        chunks
+            .into_iter()
+            .map(|chunk| {
+                let (text, style) = chunk.into_parts();
+                Text::new(text, style)
+            })
+            .collect()
+    }
+}
+
+impl AsRenderOperations for TableGenerator {
+    fn as_render_operations(&self, dimensions: &WindowSize) -> Vec<RenderOperation> {
+        if self.columns() == 0 {
+            return Vec::new();
+        }
+        let characters = BorderCharacters::from_border(&self.style.border);
+        let layout = TableLayout {
+            characters,
+            outer_border: characters.is_some() && self.style.outer_border,
+            padding: self.style.padding.unwrap_or(DEFAULT_PADDING) as usize,
+            widths: Vec::new(),
+            border_colors: self.style.border_colors,
+            alignment: self.alignment.clone(),
+        };
+        let max_width = Layout::new(self.alignment.clone()).compute(dimensions, dimensions.columns).max_line_length;
+        let available = (max_width as usize).saturating_sub(layout.decorations_width(self.columns()));
+        let layout = TableLayout { widths: Self::fit_widths(&self.natural_widths(), available), ..layout };
+
+        let mut header_style = TextStyle::colored(self.style.header.colors);
+        if self.style.header.bold.unwrap_or_default() {
+            header_style = header_style.bold();
+        }
+        let mut operations = Vec::new();
+        if let Some(characters) = characters.filter(|_| layout.outer_border) {
+            layout.push_rule(characters.top_left, characters.top, characters.top_right, &mut operations);
+        }
+        layout.push_row(&self.header, &self.alignments, header_style, &mut operations);
+        if let Some(characters) = characters {
+            layout.push_rule(characters.left, characters.cross, characters.right, &mut operations);
+        }
+        for (index, row) in self.rows.iter().enumerate() {
+            let colors = match &self.style.rows.alternate_colors {
+                Some(colors) if index % 2 == 1 => *colors,
+                _ => self.style.rows.colors,
+            };
+            layout.push_row(row, &self.alignments, TextStyle::colored(colors), &mut operations);
+        }
+        if let Some(characters) = characters.filter(|_| layout.outer_border) {
+            layout.push_rule(characters.bottom_left, characters.bottom, characters.bottom_right, &mut operations);
+        }
+        operations
+    }
+
+    fn diffable_content(&self) -> Option<&str> {
+        Some(&self.content)
+    }
+}
+
+/// The layout of a table once its column widths are known.
+struct TableLayout {
+    characters: Option<&'static BorderCharacters>,
+    outer_border: bool,
+    padding: usize,
+    widths: Vec<usize>,
+    border_colors: Colors,
+    alignment: Alignment,
+}
+
+impl TableLayout {
+    // The width of everything in a row that isn't the contents of a cell.
+    fn decorations_width(&self, columns: usize) -> usize {
+        let borders = if self.outer_border { columns + 1 } else { columns - 1 };
+        let paddings: usize = (0..columns).map(|column| self.cell_padding(column, columns)).map(|(l, r)| l + r).sum();
+        borders + paddings
+    }
+
+    // The padding on the left and right side of a cell.
+    //
+    // Unless there's an outer border, there's no padding at the edges of the table.
+    fn cell_padding(&self, column: usize, columns: usize) -> (usize, usize) {
+        let left = if column > 0 || self.outer_border { self.padding } else { 0 };
+        let right = if column < columns - 1 || self.outer_border { self.padding } else { 0 };
+        (left, right)
+    }
+
+    fn vertical(&self) -> char {
+        self.characters.map(|characters| characters.vertical).unwrap_or(' ')
+    }
+
+    fn border_style(&self, row_style: &TextStyle) -> TextStyle {
+        TextStyle::colored(self.border_colors.merge(&Colors { foreground: None, ..row_style.colors }))
+    }
+
+    fn push_row(
+        &self,
+        row: &[WeightedLine],
+        alignments: &[ColumnAlignment],
+        style: TextStyle,
+        operations: &mut Vec<RenderOperation>,
+    ) {
+        let cells: Vec<Vec<Vec<Text>>> = self
+            .widths
+            .iter()
+            .enumerate()
+            .map(|(column, width)| match row.get(column) {
+                Some(cell) => cell.split(*width).map(TableGenerator::into_texts).collect(),
+                None => Vec::new(),
+            })
+            .collect();
+        let height = cells.iter().map(Vec::len).max().unwrap_or(0).max(1);
+        let border = Text::new(self.vertical().to_string(), self.border_style(&style));
+        for line_index in 0..height {
+            let mut line = Vec::new();
+            if self.outer_border {
+                line.push(border.clone());
+            }
+            for (column, width) in self.widths.iter().enumerate() {
+                if column > 0 {
+                    line.push(border.clone());
+                }
+                let mut texts = cells[column].get(line_index).cloned().unwrap_or_default();
+                for text in &mut texts {
+                    text.style.merge(&style);
+                }
+                let alignment = alignments.get(column).copied().unwrap_or_default();
+                let (left, right) = self.cell_padding(column, self.widths.len());
+                let text_width: usize = texts.iter().map(|text| text.content.width()).sum();
+                let padding = width.saturating_sub(text_width);
+                let (left, right) = match alignment {
+                    ColumnAlignment::Left => (left, right + padding),
+                    ColumnAlignment::Center => (left + padding / 2, right + padding - padding / 2),
+                    ColumnAlignment::Right => (left + padding, right),
+                };
+                let blank_style = TextStyle::colored(style.colors);
+                line.push(Text::new(" ".repeat(left), blank_style));
+                line.extend(texts);
+                line.push(Text::new(" ".repeat(right), blank_style));
+            }
+            if self.outer_border {
+                line.push(border.clone());
+            }
+            line.retain(|text| !text.content.is_empty());
+            operations.push(RenderOperation::RenderText { line: line.into(), alignment: self.alignment.clone() });
+            operations.push(RenderOperation::RenderLineBreak);
+        }
+    }
+
+    fn push_rule(&self, left: char, cross: char, right: char, operations: &mut Vec<RenderOperation>) {
+        let Some(characters) = self.characters else { return };
+        let mut contents = String::new();
+        if self.outer_border {
+            contents.push(left);
+        }
+        for (column, width) in self.widths.iter().enumerate() {
+            if column > 0 {
+                contents.push(cross);
+            }
+            let (left, right) = self.cell_padding(column, self.widths.len());
+            contents.extend(iter::repeat_n(characters.horizontal, left + width + right));
+        }
+        if self.outer_border {
+            contents.push(right);
+        }
+        let line = vec![Text::new(contents, TextStyle::colored(self.border_colors))];
+        operations.push(RenderOperation::RenderText { line: line.into(), alignment: self.alignment.clone() });
+        operations.push(RenderOperation::RenderLineBreak);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        render::{
+            engine::{RenderEngine, RenderEngineOptions},
+            virt::{TerminalGrid, VirtualTerminal},
+        },
+        style::Color,
+        theme::{TableHeaderStyle, TableRowsStyle},
+    };
+    use rstest::rstest;
+
+    fn generator(style: TableStyle) -> TableGenerator {
+        let header = vec!["a".into(), "b".into()];
+        let rows = vec![vec!["x".into(), "yy".into()], vec!["z".into(), "w".into()]];
+        TableGenerator::new(header, rows, vec![ColumnAlignment::Left; 2], style)
+    }
+
+    fn render(generator: &TableGenerator) -> TerminalGrid {
+        let dimensions = WindowSize { rows: 7, columns: 12, width: 120, height: 140 };
+        let mut terminal = VirtualTerminal::new(&dimensions);
+        let engine = RenderEngine::new(&mut terminal, dimensions.clone(), RenderEngineOptions::default());
+        let operations = generator.as_render_operations(&dimensions);
+        engine.render(operations.iter()).expect("render failed");
+        terminal.into_contents()
+    }
+
+    fn render_text(generator: &TableGenerator) -> Vec<String> {
+        render(generator)
+            .rows
+            .into_iter()
+            .map(|row| row.into_iter().map(|c| c.character).collect::<String>().trim_end().to_string())
+            .filter(|row| !row.is_empty())
+            .collect()
+    }
+
+    #[rstest]
+    #[case::none(TableBorder::None, false, &["a   b", "x   yy", "z   w"])]
+    #[case::light(TableBorder::Light, false, &["a │ b", "──┼───", "x │ yy", "z │ w"])]
+    #[case::heavy(TableBorder::Heavy, true, &["┏━━━┳━━━━┓", "┃ a ┃ b  ┃", "┣━━━╋━━━━┫", "┃ x ┃ yy ┃", "┃ z ┃ w  ┃", "┗━━━┻━━━━┛"])]
+    #[case::rounded(TableBorder::Rounded, true, &["╭───┬────╮", "│ a │ b  │", "├───┼────┤", "│ x │ yy │", "│ z │ w  │", "╰───┴────╯"])]
+    #[case::ascii(TableBorder::Ascii, false, &["a | b", "--+---", "x | yy", "z | w"])]
+    #[case::none_ignores_outer(TableBorder::None, true, &["a   b", "x   yy", "z   w"])]
+    fn borders(#[case] border: TableBorder, #[case] outer_border: bool, #[case] expected: &[&str]) {
+        let style = TableStyle { border, outer_border, ..Default::default() };
+        let rows = render_text(&generator(style));
+        assert_eq!(rows, expected);
+    }
+
+    #[test]
+    fn padding() {
+        let style = TableStyle { padding: Some(0), outer_border: true, ..Default::default() };
+        let rows = render_text(&generator(style));
+        assert_eq!(rows, &["┌─┬──┐", "│a│b │", "├─┼──┤", "│x│yy│", "│z│w │", "└─┴──┘"]);
+    }
+
+    #[test]
+    fn colors() {
+        let header = Colors { foreground: Some(Color::Red), background: Some(Color::Blue) };
+        let odd = Colors { foreground: None, background: Some(Color::Green) };
+        let even = Colors { foreground: None, background: Some(Color::Yellow) };
+        let style = TableStyle {
+            header: TableHeaderStyle { colors: header, bold: Some(true) },
+            rows: TableRowsStyle { colors: odd, alternate_colors: Some(even) },
+            border_colors: Colors { foreground: Some(Color::Magenta), background: None },
+            ..Default::default()
+        };
+        let grid = render(&generator(style));
+        let header_cell = grid.rows[0][0].style;
+        assert_eq!(header_cell.colors, header);
+        assert!(header_cell.is_bold());
+
+        // Borders keep the row's background so it spans the entire row.
+        let border = grid.rows[2][2].style;
+        assert_eq!(border.colors, Colors { foreground: Some(Color::Magenta), background: Some(Color::Green) });
+        assert_eq!(grid.rows[2][5].style.colors.background, Some(Color::Green));
+        assert_eq!(grid.rows[3][0].style.colors.background, Some(Color::Yellow));
+    }
+}
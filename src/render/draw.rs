@@ -1,5 +1,5 @@
 use super::{
-    engine::RenderEngine,
+    engine::{RenderEngine, RenderEngineOptions},
     terminal::{Terminal, TerminalWrite},
 };
 use crate::{
@@ -31,13 +31,15 @@ where
         Ok(Self { terminal, font_size_fallback })
     }
 
-    /// Render a slide.
-    pub(crate) fn render_slide(&mut self, presentation: &Presentation) -> RenderResult {
+    /// Render a slide, scrolled down by the given number of rows.
+    ///
+    /// This returns the maximum offset the slide can be scrolled by.
+    pub(crate) fn render_slide(&mut self, presentation: &Presentation, scroll_offset: u16) -> Result<u16, RenderError> {
         let dimensions = WindowSize::current(self.font_size_fallback)?;
         let slide = presentation.current_slide();
-        let engine = self.create_engine(dimensions);
-        engine.render(slide.iter_visible_operations())?;
-        Ok(())
+        let options = RenderEngineOptions { highlight: presentation.search_highlight(), ..Default::default() };
+        let engine = RenderEngine::new(&mut self.terminal, dimensions, options);
+        engine.render_scrolled(slide.iter_visible_operations(), scroll_offset)
     }
 
     /// Render an error.
@@ -45,6 +47,7 @@ where
         let dimensions = WindowSize::current(self.font_size_fallback)?;
         let heading_text = match source {
             ErrorSource::Presentation => "Error loading presentation".to_string(),
+            ErrorSource::Slides => "Some slides could not be built".to_string(),
             ErrorSource::Slide(slide) => {
                 format!("Error in slide {slide}")
             }
@@ -75,6 +78,17 @@ where
         Ok(())
     }
 
+    /// Render an arbitrary set of operations.
+    pub(crate) fn render_operations<'a>(
+        &mut self,
+        operations: impl Iterator<Item = &'a RenderOperation>,
+    ) -> RenderResult {
+        let dimensions = WindowSize::current(self.font_size_fallback)?;
+        let engine = self.create_engine(dimensions);
+        engine.render(operations)?;
+        Ok(())
+    }
+
     pub(crate) fn render_slide_index(&mut self, presentation: &Presentation) -> RenderResult {
         let dimensions = WindowSize::current(self.font_size_fallback)?;
         let engine = self.create_engine(dimensions);
@@ -82,6 +96,13 @@ where
         Ok(())
     }
 
+    pub(crate) fn render_search_bar(&mut self, presentation: &Presentation) -> RenderResult {
+        let dimensions = WindowSize::current(self.font_size_fallback)?;
+        let engine = self.create_engine(dimensions);
+        engine.render(presentation.iter_search_bar_operations())?;
+        Ok(())
+    }
+
     pub(crate) fn render_key_bindings(&mut self, presentation: &Presentation) -> RenderResult {
         let dimensions = WindowSize::current(self.font_size_fallback)?;
         let engine = self.create_engine(dimensions);
@@ -89,7 +110,7 @@ where
         Ok(())
     }
 
-    fn create_engine(&mut self, dimensions: WindowSize) -> RenderEngine<W> {
+    fn create_engine(&mut self, dimensions: WindowSize) -> RenderEngine<'_, Terminal<W>> {
         let options = Default::default();
         RenderEngine::new(&mut self.terminal, dimensions, options)
     }
@@ -128,5 +149,6 @@ pub enum RenderError {
 
 pub(crate) enum ErrorSource {
     Presentation,
+    Slides,
     Slide(usize),
 }
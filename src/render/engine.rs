@@ -2,8 +2,10 @@ use super::{
     draw::{RenderError, RenderResult},
     layout::Layout,
     properties::CursorPosition,
-    terminal::{Terminal, TerminalWrite},
+    scroll::ScrolledTerminal,
+    terminal::TerminalIo,
     text::TextDrawer,
+    validate::{Overflow, OverflowKind},
 };
 use crate::{
     markdown::text::WeightedLine,
@@ -16,7 +18,7 @@ use crate::{
         AsRenderOperations, BlockLine, ImageProperties, ImageSize, MarginProperties, RenderAsync, RenderOperation,
     },
     render::{layout::Positioning, properties::WindowSize},
-    style::Colors,
+    style::{Colors, TextStyle},
     theme::Alignment,
 };
 use std::mem;
@@ -24,55 +26,115 @@ use std::mem;
 #[derive(Debug, Default)]
 pub(crate) struct RenderEngineOptions {
     pub(crate) validate_overflows: bool,
+    pub(crate) highlight: Option<TextHighlight>,
 }
 
-pub(crate) struct RenderEngine<'a, W>
+/// A piece of text that's highlighted everywhere it appears.
+#[derive(Debug)]
+pub(crate) struct TextHighlight {
+    pub(crate) query: String,
+    pub(crate) style: TextStyle,
+}
+
+pub(crate) struct RenderEngine<'a, T>
 where
-    W: TerminalWrite,
+    T: TerminalIo,
 {
-    terminal: &'a mut Terminal<W>,
+    terminal: ScrolledTerminal<'a, T>,
     window_rects: Vec<WindowRect>,
     colors: Colors,
     max_modified_row: u16,
     layout: LayoutState,
     options: RenderEngineOptions,
+    operation_index: usize,
+    vertical_overflow_index: Option<usize>,
+    overflows: Option<Vec<Overflow>>,
 }
 
-impl<'a, W> RenderEngine<'a, W>
+impl<'a, T> RenderEngine<'a, T>
 where
-    W: TerminalWrite,
+    T: TerminalIo,
 {
-    pub(crate) fn new(
-        terminal: &'a mut Terminal<W>,
-        window_dimensions: WindowSize,
-        options: RenderEngineOptions,
-    ) -> Self {
-        let max_modified_row = terminal.cursor_row;
+    pub(crate) fn new(terminal: &'a mut T, window_dimensions: WindowSize, options: RenderEngineOptions) -> Self {
+        let max_modified_row = terminal.cursor_row();
         let current_rect = WindowRect { dimensions: window_dimensions, start_column: 0 };
         let window_rects = vec![current_rect.clone()];
         Self {
-            terminal,
+            terminal: ScrolledTerminal::new(terminal),
             window_rects,
             colors: Default::default(),
             max_modified_row,
             layout: Default::default(),
             options,
+            operation_index: 0,
+            vertical_overflow_index: None,
+            overflows: None,
         }
     }
 
     pub(crate) fn render<'b>(mut self, operations: impl Iterator<Item = &'b RenderOperation>) -> RenderResult {
+        self.render_operations(operations)
+    }
+
+    /// Render a slide scrolled down by the given number of rows.
+    ///
+    /// Anything that doesn't fit in the slide's area is left out and a scrollbar is drawn if the
+    /// slide doesn't fit entirely on the screen. This returns the maximum offset the slide can be
+    /// scrolled by.
+    pub(crate) fn render_scrolled<'b>(
+        mut self,
+        operations: impl Iterator<Item = &'b RenderOperation>,
+        offset: u16,
+    ) -> Result<u16, RenderError> {
+        self.terminal.scroll(offset);
+        self.render_operations(operations)?;
+        Ok(self.terminal.max_offset())
+    }
+
+    /// Render the given operations and find every place in which they don't fit on the screen.
+    ///
+    /// Unlike validating overflows via [RenderEngineOptions], this doesn't stop at the first one.
+    pub(crate) fn find_overflows<'b>(
+        mut self,
+        operations: impl Iterator<Item = &'b RenderOperation>,
+    ) -> Result<Vec<Overflow>, RenderError> {
+        self.overflows = Some(Vec::new());
+        self.render_operations(operations)?;
+        Ok(self.overflows.unwrap_or_default())
+    }
+
+    fn render_operations<'b>(&mut self, operations: impl Iterator<Item = &'b RenderOperation>) -> RenderResult {
+        let max_rows = self.window_rects[0].dimensions.rows;
         self.terminal.begin_update()?;
-        for operation in operations {
+        for (index, operation) in operations.enumerate() {
+            self.operation_index = index;
             self.render_one(operation)?;
+            if self.vertical_overflow_index.is_none() && self.max_modified_row > max_rows {
+                self.vertical_overflow_index = Some(index);
+            }
         }
+        self.pin_to_screen()?;
         self.terminal.end_update()?;
         self.terminal.flush()?;
-        if self.options.validate_overflows && self.max_modified_row > self.window_rects[0].dimensions.rows {
-            return Err(RenderError::VerticalOverflow);
+        if let Some(operation) = self.vertical_overflow_index {
+            let kind = OverflowKind::Vertical { rows: self.max_modified_row - max_rows };
+            self.report_overflow(Overflow { operation, kind })?;
         }
         Ok(())
     }
 
+    fn report_overflow(&mut self, overflow: Overflow) -> RenderResult {
+        match (&mut self.overflows, &overflow.kind) {
+            (Some(overflows), _) => {
+                overflows.push(overflow);
+                Ok(())
+            }
+            (None, _) if !self.options.validate_overflows => Ok(()),
+            (None, OverflowKind::Horizontal { .. }) => Err(RenderError::HorizontalOverflow),
+            (None, OverflowKind::Vertical { .. }) => Err(RenderError::VerticalOverflow),
+        }
+    }
+
     fn render_one(&mut self, operation: &RenderOperation) -> RenderResult {
         match operation {
             RenderOperation::ClearScreen => self.clear_screen(),
@@ -92,7 +154,7 @@ where
             RenderOperation::EnterColumn { column } => self.enter_column(*column),
             RenderOperation::ExitLayout => self.exit_layout(),
         }?;
-        self.max_modified_row = self.max_modified_row.max(self.terminal.cursor_row);
+        self.max_modified_row = self.max_modified_row.max(self.terminal.cursor_row());
         Ok(())
     }
 
@@ -117,6 +179,7 @@ where
         let current = self.current_rect();
         let margin = horizontal_margin.as_characters(current.dimensions.columns);
         let new_rect = current.apply_margin(margin).shrink_rows(*bottom_slide_margin);
+        self.terminal.set_visible_rows(new_rect.dimensions.rows);
         self.window_rects.push(new_rect);
         Ok(())
     }
@@ -151,6 +214,8 @@ where
     }
 
     fn jump_to_bottom(&mut self, index: u16) -> RenderResult {
+        // Anything placed at the bottom stays there regardless of how much the slide is scrolled.
+        self.pin_to_screen()?;
         let target_row = self.current_dimensions().rows.saturating_sub(index).saturating_sub(1);
         self.terminal.move_to_row(target_row)?;
         Ok(())
@@ -160,9 +225,11 @@ where
         let layout = self.build_layout(alignment.clone());
         let dimensions = self.current_dimensions();
         let positioning = layout.compute(dimensions, text.width() as u16);
+        let highlighted = self.highlight(text);
+        let text = highlighted.as_ref().unwrap_or(text);
         let prefix = "".into();
         let text_drawer = TextDrawer::new(&prefix, 0, text, positioning, &self.colors)?;
-        text_drawer.draw(self.terminal)?;
+        text_drawer.draw(&mut self.terminal)?;
         // Restore colors
         self.apply_colors()
     }
@@ -174,7 +241,7 @@ where
 
     fn render_image(&mut self, image: &Image, properties: &ImageProperties) -> RenderResult {
         let rect = self.current_rect();
-        let starting_position = CursorPosition { row: self.terminal.cursor_row, column: rect.start_column };
+        let starting_position = CursorPosition { row: self.terminal.cursor_row(), column: rect.start_column };
 
         let (width, height) = image.dimensions();
         let (cursor_position, columns, rows) = match properties.size {
@@ -183,6 +250,10 @@ where
                 (CursorPosition { row: starting_position.row, column: scale.start_column }, scale.columns, scale.rows)
             }
             ImageSize::Specific(columns, rows) => (starting_position.clone(), columns, rows),
+            ImageSize::Positioned { column, columns, rows } => {
+                let column = starting_position.column + column;
+                (CursorPosition { row: starting_position.row, column }, columns, rows)
+            }
             ImageSize::WidthScaled { ratio } => {
                 let extra_columns = (rect.dimensions.columns as f64 * (1.0 - ratio)).ceil() as u16;
                 let dimensions = rect.dimensions.shrink_columns(extra_columns);
@@ -223,23 +294,32 @@ where
 
         let dimensions = self.current_dimensions();
         let Positioning { max_line_length, start_column } = layout.compute(dimensions, *block_length);
-        if self.options.validate_overflows && text.width() as u16 > max_line_length {
-            return Err(RenderError::HorizontalOverflow);
+        let width = text.width() as u16;
+        if width > max_line_length {
+            let kind = OverflowKind::Horizontal { columns: width - max_line_length };
+            self.report_overflow(Overflow { operation: self.operation_index, kind })?;
         }
 
         self.terminal.move_to_column(start_column)?;
 
         let positioning = Positioning { max_line_length, start_column };
+        let highlighted = self.highlight(text);
+        let text = highlighted.as_ref().unwrap_or(text);
         let text_drawer = TextDrawer::new(prefix, *right_padding_length, text, positioning, &self.colors)?
             .with_surrounding_block(*block_color)
             .repeat_prefix_on_wrap(*repeat_prefix_on_wrap);
-        text_drawer.draw(self.terminal)?;
+        text_drawer.draw(&mut self.terminal)?;
 
         // Restore colors
         self.apply_colors()?;
         Ok(())
     }
 
+    fn highlight(&self, text: &WeightedLine) -> Option<WeightedLine> {
+        let highlight = self.options.highlight.as_ref()?;
+        text.highlight(&highlight.query, &highlight.style)
+    }
+
     fn render_dynamic(&mut self, generator: &dyn AsRenderOperations) -> RenderResult {
         let operations = generator.as_render_operations(self.current_dimensions());
         for operation in operations {
@@ -261,7 +341,7 @@ where
             self.exit_layout()?;
         }
         let columns = columns.iter().copied().map(u16::from).collect();
-        let current_position = self.terminal.cursor_row;
+        let current_position = self.terminal.cursor_row();
         self.layout = LayoutState::InitializedColumn { columns, start_row: current_position };
         Ok(())
     }
@@ -316,6 +396,34 @@ where
         }
     }
 
+    fn pin_to_screen(&mut self) -> RenderResult {
+        if self.terminal.is_pinned() {
+            return Ok(());
+        }
+        self.terminal.pin();
+        if self.terminal.max_offset() > 0 {
+            self.render_scrollbar()?;
+        }
+        Ok(())
+    }
+
+    fn render_scrollbar(&mut self) -> RenderResult {
+        let rows = self.terminal.visible_rows().min(self.window_rects[0].dimensions.rows);
+        let content_rows = self.terminal.content_rows().max(1);
+        let max_offset = self.terminal.max_offset();
+        let thumb_rows = (rows as u32 * rows as u32 / content_rows as u32).max(1) as u16;
+        let offset = self.terminal.offset().min(max_offset);
+        let thumb_start = (offset as u32 * rows.saturating_sub(thumb_rows) as u32 / max_offset as u32) as u16;
+        let column = self.window_rects[0].dimensions.columns.saturating_sub(1);
+        let style = TextStyle::colored(self.colors);
+        for row in 0..rows {
+            let character = if (thumb_start..thumb_start + thumb_rows).contains(&row) { "█" } else { "│" };
+            self.terminal.move_to(column, row)?;
+            self.terminal.print_styled_line(character, &style)?;
+        }
+        Ok(())
+    }
+
     fn build_layout(&self, alignment: Alignment) -> Layout {
         Layout::new(alignment).with_start_column(self.current_rect().start_column)
     }
@@ -3,6 +3,8 @@ pub(crate) mod engine;
 pub(crate) mod highlighting;
 pub(crate) mod layout;
 pub(crate) mod properties;
+pub(crate) mod scroll;
 pub(crate) mod terminal;
 pub(crate) mod text;
 pub(crate) mod validate;
+pub(crate) mod virt;
@@ -0,0 +1,330 @@
+use super::terminal::TerminalIo;
+use crate::{
+    media::{
+        image::Image,
+        printer::{PrintImageError, PrintOptions},
+    },
+    style::{Color, Colors, TextStyle},
+};
+use std::io;
+use unicode_width::UnicodeWidthStr;
+
+/// A terminal that lets content be scrolled vertically.
+///
+/// Rows are expressed in terms of the content being drawn, which is moved up by the scroll offset
+/// before it reaches the underlying terminal. Anything that doesn't land within the visible rows is
+/// dropped, and images are only drawn if they fit entirely within them.
+///
+/// Once pinned, rows are expressed in terms of the screen instead. This is used for anything that
+/// sits at a fixed position, like the footer.
+pub(crate) struct ScrolledTerminal<'a, T: TerminalIo> {
+    terminal: &'a mut T,
+    offset: u16,
+    clip: bool,
+    pinned: bool,
+    visible_rows: u16,
+    row: u16,
+    column: u16,
+    // Whether the underlying terminal's cursor is somewhere other than our own.
+    stale: bool,
+    content_rows: u16,
+}
+
+impl<'a, T: TerminalIo> ScrolledTerminal<'a, T> {
+    /// Construct a terminal that doesn't scroll or clip anything.
+    pub(crate) fn new(terminal: &'a mut T) -> Self {
+        let row = terminal.cursor_row();
+        Self {
+            terminal,
+            offset: 0,
+            clip: false,
+            pinned: false,
+            visible_rows: u16::MAX,
+            row,
+            column: 0,
+            stale: false,
+            content_rows: 0,
+        }
+    }
+
+    /// Scroll the content by the given number of rows and start clipping it.
+    pub(crate) fn scroll(&mut self, offset: u16) {
+        self.offset = offset;
+        self.clip = true;
+        self.stale = true;
+    }
+
+    /// Set the number of rows, starting at the top of the screen, content can be drawn on.
+    pub(crate) fn set_visible_rows(&mut self, rows: u16) {
+        self.visible_rows = rows;
+    }
+
+    pub(crate) fn visible_rows(&self) -> u16 {
+        self.visible_rows
+    }
+
+    pub(crate) fn offset(&self) -> u16 {
+        self.offset
+    }
+
+    /// Stop scrolling and clipping, making rows refer to the screen from now on.
+    pub(crate) fn pin(&mut self) {
+        if !self.pinned {
+            self.row = self.row.saturating_sub(self.offset);
+            self.pinned = true;
+        }
+    }
+
+    pub(crate) fn is_pinned(&self) -> bool {
+        self.pinned
+    }
+
+    /// The number of rows drawn on before the terminal was pinned.
+    pub(crate) fn content_rows(&self) -> u16 {
+        self.content_rows
+    }
+
+    /// The maximum offset the content can be scrolled by so that its last row is visible.
+    pub(crate) fn max_offset(&self) -> u16 {
+        match self.clip {
+            true => self.content_rows.saturating_sub(self.visible_rows),
+            false => 0,
+        }
+    }
+
+    fn screen_row(&self) -> Option<u16> {
+        if self.pinned {
+            return Some(self.row);
+        }
+        let row = self.row.checked_sub(self.offset)?;
+        let visible = !self.clip || row < self.visible_rows;
+        visible.then_some(row)
+    }
+
+    // Move the underlying terminal's cursor to ours if it's visible, using `forward` if the
+    // cursor is in sync already.
+    fn sync<F>(&mut self, forward: F) -> io::Result<()>
+    where
+        F: FnOnce(&mut T, u16) -> io::Result<()>,
+    {
+        match self.screen_row() {
+            Some(row) if self.stale => {
+                self.stale = false;
+                self.terminal.move_to(self.column, row)
+            }
+            Some(row) => forward(self.terminal, row),
+            None => {
+                self.stale = true;
+                Ok(())
+            }
+        }
+    }
+
+    fn track_content(&mut self, rows: u16) {
+        if !self.pinned {
+            self.content_rows = self.content_rows.max(self.row.saturating_add(rows));
+        }
+    }
+}
+
+impl<T: TerminalIo> TerminalIo for ScrolledTerminal<'_, T> {
+    fn begin_update(&mut self) -> io::Result<()> {
+        self.terminal.begin_update()
+    }
+
+    fn end_update(&mut self) -> io::Result<()> {
+        self.terminal.end_update()
+    }
+
+    fn cursor_row(&self) -> u16 {
+        self.row
+    }
+
+    fn move_to(&mut self, column: u16, row: u16) -> io::Result<()> {
+        self.row = row;
+        self.column = column;
+        self.stale = true;
+        self.sync(|_, _| Ok(()))
+    }
+
+    fn move_to_row(&mut self, row: u16) -> io::Result<()> {
+        self.row = row;
+        self.sync(|terminal, row| terminal.move_to_row(row))
+    }
+
+    fn move_to_column(&mut self, column: u16) -> io::Result<()> {
+        self.column = column;
+        self.sync(|terminal, _| terminal.move_to_column(column))
+    }
+
+    fn move_down(&mut self, amount: u16) -> io::Result<()> {
+        self.row = self.row.saturating_add(amount);
+        self.sync(|terminal, _| terminal.move_down(amount))
+    }
+
+    fn move_to_next_line(&mut self, amount: u16) -> io::Result<()> {
+        self.row = self.row.saturating_add(amount);
+        self.column = 0;
+        self.sync(|terminal, _| terminal.move_to_next_line(amount))
+    }
+
+    fn print_line(&mut self, text: &str) -> io::Result<()> {
+        self.track_content(1);
+        self.sync(|_, _| Ok(()))?;
+        if !self.stale {
+            self.terminal.print_line(text)?;
+        }
+        self.column = self.column.saturating_add(text.width() as u16);
+        Ok(())
+    }
+
+    fn print_styled_line(&mut self, text: &str, style: &TextStyle) -> io::Result<()> {
+        self.track_content(1);
+        self.sync(|_, _| Ok(()))?;
+        if !self.stale {
+            self.terminal.print_styled_line(text, style)?;
+        }
+        self.column = self.column.saturating_add(text.width() as u16);
+        Ok(())
+    }
+
+    fn clear_screen(&mut self) -> io::Result<()> {
+        self.terminal.clear_screen()?;
+        self.row = 0;
+        self.stale = self.screen_row().is_none();
+        Ok(())
+    }
+
+    fn set_colors(&mut self, colors: Colors) -> io::Result<()> {
+        self.terminal.set_colors(colors)
+    }
+
+    fn set_background_color(&mut self, color: Color) -> io::Result<()> {
+        self.terminal.set_background_color(color)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.terminal.flush()
+    }
+
+    fn print_image(&mut self, image: &Image, options: &PrintOptions) -> Result<(), PrintImageError> {
+        self.track_content(options.rows);
+        let fits = match self.screen_row() {
+            Some(row) => !self.clip || self.pinned || row.saturating_add(options.rows) <= self.visible_rows,
+            None => false,
+        };
+        if fits {
+            self.sync(|_, _| Ok(()))?;
+            self.terminal.print_image(image, options)?;
+        } else {
+            self.stale = true;
+        }
+        self.row = self.row.saturating_add(options.rows);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        presentation::{MarginProperties, RenderOperation},
+        render::{engine::RenderEngine, properties::WindowSize, virt::VirtualTerminal},
+        theme::Margin,
+    };
+
+    fn render<F>(offset: u16, visible_rows: u16, draw: F) -> Vec<String>
+    where
+        F: FnOnce(&mut ScrolledTerminal<VirtualTerminal>) -> io::Result<()>,
+    {
+        let dimensions = WindowSize { rows: 4, columns: 6, width: 60, height: 80 };
+        let mut terminal = VirtualTerminal::new(&dimensions);
+        let mut scrolled = ScrolledTerminal::new(&mut terminal);
+        scrolled.scroll(offset);
+        scrolled.set_visible_rows(visible_rows);
+        draw(&mut scrolled).expect("draw failed");
+        terminal
+            .into_contents()
+            .rows
+            .into_iter()
+            .map(|row| row.into_iter().map(|c| c.character).collect::<String>().trim_end().to_string())
+            .collect()
+    }
+
+    fn print_rows(terminal: &mut ScrolledTerminal<VirtualTerminal>, count: u16) -> io::Result<()> {
+        for row in 0..count {
+            terminal.move_to_column(1)?;
+            terminal.print_line(&row.to_string())?;
+            terminal.move_to_next_line(1)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn no_offset() {
+        let rows = render(0, 3, |terminal| print_rows(terminal, 5));
+        assert_eq!(rows, &[" 0", " 1", " 2", ""]);
+    }
+
+    #[test]
+    fn offset() {
+        let rows = render(2, 3, |terminal| print_rows(terminal, 6));
+        assert_eq!(rows, &[" 2", " 3", " 4", ""]);
+    }
+
+    #[test]
+    fn pinned() {
+        let rows = render(2, 3, |terminal| {
+            print_rows(terminal, 6)?;
+            terminal.pin();
+            terminal.move_to(0, 3)?;
+            terminal.print_line("footer")
+        });
+        assert_eq!(rows, &[" 2", " 3", " 4", "footer"]);
+    }
+
+    #[test]
+    fn scrolled_slide() {
+        let mut operations = vec![RenderOperation::ApplyMargin(MarginProperties {
+            horizontal_margin: Margin::Fixed(0),
+            bottom_slide_margin: 1,
+        })];
+        for line in ["a", "b", "c", "d", "e"] {
+            operations.extend([
+                RenderOperation::RenderText { line: line.into(), alignment: Default::default() },
+                RenderOperation::RenderLineBreak,
+            ]);
+        }
+        operations.extend([
+            RenderOperation::PopMargin,
+            RenderOperation::JumpToBottomRow { index: 0 },
+            RenderOperation::RenderText { line: "footer".into(), alignment: Default::default() },
+        ]);
+        let dimensions = WindowSize { rows: 4, columns: 8, width: 80, height: 80 };
+        let mut terminal = VirtualTerminal::new(&dimensions);
+        let engine = RenderEngine::new(&mut terminal, dimensions, Default::default());
+        let max_offset = engine.render_scrolled(operations.iter(), 1).expect("render failed");
+        assert_eq!(max_offset, 2);
+
+        let rows: Vec<_> = terminal
+            .into_contents()
+            .rows
+            .into_iter()
+            .map(|row| row.into_iter().map(|c| c.character).collect::<String>())
+            .collect();
+        assert_eq!(rows, &["b      │", "c      █", "d      │", "footer  "]);
+    }
+
+    #[test]
+    fn content_rows() {
+        let dimensions = WindowSize { rows: 4, columns: 6, width: 60, height: 80 };
+        let mut terminal = VirtualTerminal::new(&dimensions);
+        let mut scrolled = ScrolledTerminal::new(&mut terminal);
+        scrolled.scroll(1);
+        print_rows(&mut scrolled, 6).unwrap();
+        scrolled.move_to_next_line(3).unwrap();
+        scrolled.pin();
+        scrolled.print_line("footer").unwrap();
+        assert_eq!(scrolled.content_rows(), 6);
+    }
+}
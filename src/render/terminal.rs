@@ -3,11 +3,10 @@ use crate::{
         image::Image,
         printer::{ImagePrinter, PrintImage, PrintImageError, PrintOptions},
     },
-    style::{Color, Colors},
+    style::{Color, Colors, TextStyle},
 };
 use crossterm::{
-    QueueableCommand, cursor,
-    style::{self, StyledContent},
+    QueueableCommand, cursor, event, style,
     terminal::{self},
 };
 use std::{
@@ -15,6 +14,25 @@ use std::{
     sync::Arc,
 };
 
+/// A handle that allows drawing on a terminal-like surface.
+pub(crate) trait TerminalIo {
+    fn begin_update(&mut self) -> io::Result<()>;
+    fn end_update(&mut self) -> io::Result<()>;
+    fn cursor_row(&self) -> u16;
+    fn move_to(&mut self, column: u16, row: u16) -> io::Result<()>;
+    fn move_to_row(&mut self, row: u16) -> io::Result<()>;
+    fn move_to_column(&mut self, column: u16) -> io::Result<()>;
+    fn move_down(&mut self, amount: u16) -> io::Result<()>;
+    fn move_to_next_line(&mut self, amount: u16) -> io::Result<()>;
+    fn print_line(&mut self, text: &str) -> io::Result<()>;
+    fn print_styled_line(&mut self, text: &str, style: &TextStyle) -> io::Result<()>;
+    fn clear_screen(&mut self) -> io::Result<()>;
+    fn set_colors(&mut self, colors: Colors) -> io::Result<()>;
+    fn set_background_color(&mut self, color: Color) -> io::Result<()>;
+    fn flush(&mut self) -> io::Result<()>;
+    fn print_image(&mut self, image: &Image, options: &PrintOptions) -> Result<(), PrintImageError>;
+}
+
 /// A wrapper over the terminal write handle.
 pub(crate) struct Terminal<W>
 where
@@ -22,100 +40,127 @@ where
 {
     writer: W,
     image_printer: Arc<ImagePrinter>,
-    pub(crate) cursor_row: u16,
+    cursor_row: u16,
+    mouse_capture: bool,
 }
 
 impl<W: TerminalWrite> Terminal<W> {
     pub(crate) fn new(mut writer: W, image_printer: Arc<ImagePrinter>) -> io::Result<Self> {
         writer.init()?;
-        Ok(Self { writer, image_printer, cursor_row: 0 })
+        Ok(Self { writer, image_printer, cursor_row: 0, mouse_capture: false })
+    }
+
+    /// Capture mouse events, like the wheel being scrolled.
+    ///
+    /// This gets in the way of selecting text with the mouse so it's only done when asked to.
+    pub(crate) fn enable_mouse_capture(&mut self) -> io::Result<()> {
+        self.writer.queue(event::EnableMouseCapture)?;
+        self.writer.flush()?;
+        self.mouse_capture = true;
+        Ok(())
+    }
+
+    pub(crate) fn suspend(&mut self) {
+        self.deinit();
+    }
+
+    pub(crate) fn resume(&mut self) {
+        let _ = self.writer.init();
+        if self.mouse_capture {
+            let _ = self.writer.queue(event::EnableMouseCapture);
+        }
     }
 
-    pub(crate) fn begin_update(&mut self) -> io::Result<()> {
+    fn deinit(&mut self) {
+        if self.mouse_capture {
+            let _ = self.writer.queue(event::DisableMouseCapture);
+        }
+        self.writer.deinit();
+    }
+}
+
+impl<W: TerminalWrite> TerminalIo for Terminal<W> {
+    fn begin_update(&mut self) -> io::Result<()> {
         self.writer.queue(terminal::BeginSynchronizedUpdate)?;
         Ok(())
     }
 
-    pub(crate) fn end_update(&mut self) -> io::Result<()> {
+    fn end_update(&mut self) -> io::Result<()> {
         self.writer.queue(terminal::EndSynchronizedUpdate)?;
         Ok(())
     }
 
-    pub(crate) fn move_to(&mut self, column: u16, row: u16) -> io::Result<()> {
+    fn cursor_row(&self) -> u16 {
+        self.cursor_row
+    }
+
+    fn move_to(&mut self, column: u16, row: u16) -> io::Result<()> {
         self.writer.queue(cursor::MoveTo(column, row))?;
         self.cursor_row = row;
         Ok(())
     }
 
-    pub(crate) fn move_to_row(&mut self, row: u16) -> io::Result<()> {
+    fn move_to_row(&mut self, row: u16) -> io::Result<()> {
         self.writer.queue(cursor::MoveToRow(row))?;
         self.cursor_row = row;
         Ok(())
     }
 
-    pub(crate) fn move_to_column(&mut self, column: u16) -> io::Result<()> {
+    fn move_to_column(&mut self, column: u16) -> io::Result<()> {
         self.writer.queue(cursor::MoveToColumn(column))?;
         Ok(())
     }
 
-    pub(crate) fn move_down(&mut self, amount: u16) -> io::Result<()> {
+    fn move_down(&mut self, amount: u16) -> io::Result<()> {
         self.writer.queue(cursor::MoveDown(amount))?;
         self.cursor_row += amount;
         Ok(())
     }
 
-    pub(crate) fn move_to_next_line(&mut self, amount: u16) -> io::Result<()> {
+    fn move_to_next_line(&mut self, amount: u16) -> io::Result<()> {
         self.writer.queue(cursor::MoveToNextLine(amount))?;
         self.cursor_row += amount;
         Ok(())
     }
 
-    pub(crate) fn print_line(&mut self, text: &str) -> io::Result<()> {
+    fn print_line(&mut self, text: &str) -> io::Result<()> {
         self.writer.queue(style::Print(text))?;
         Ok(())
     }
 
-    pub(crate) fn print_styled_line(&mut self, content: StyledContent<String>) -> io::Result<()> {
-        self.writer.queue(style::PrintStyledContent(content))?;
+    fn print_styled_line(&mut self, text: &str, style: &TextStyle) -> io::Result<()> {
+        self.writer.queue(style::PrintStyledContent(style.apply(text)))?;
         Ok(())
     }
 
-    pub(crate) fn clear_screen(&mut self) -> io::Result<()> {
+    fn clear_screen(&mut self) -> io::Result<()> {
         self.writer.queue(terminal::Clear(terminal::ClearType::All))?;
         self.cursor_row = 0;
         Ok(())
     }
 
-    pub(crate) fn set_colors(&mut self, colors: Colors) -> io::Result<()> {
+    fn set_colors(&mut self, colors: Colors) -> io::Result<()> {
         self.writer.queue(style::ResetColor)?;
         self.writer.queue(style::SetColors(colors.into()))?;
         Ok(())
     }
 
-    pub(crate) fn set_background_color(&mut self, color: Color) -> io::Result<()> {
+    fn set_background_color(&mut self, color: Color) -> io::Result<()> {
         self.writer.queue(style::SetBackgroundColor(color.into()))?;
         Ok(())
     }
 
-    pub(crate) fn flush(&mut self) -> io::Result<()> {
+    fn flush(&mut self) -> io::Result<()> {
         self.writer.flush()?;
         Ok(())
     }
 
-    pub(crate) fn print_image(&mut self, image: &Image, options: &PrintOptions) -> Result<(), PrintImageError> {
+    fn print_image(&mut self, image: &Image, options: &PrintOptions) -> Result<(), PrintImageError> {
         self.move_to_column(options.cursor_position.column)?;
         self.image_printer.print(&image.resource, options, &mut self.writer)?;
         self.cursor_row += options.rows;
         Ok(())
     }
-
-    pub(crate) fn suspend(&mut self) {
-        self.writer.deinit();
-    }
-
-    pub(crate) fn resume(&mut self) {
-        let _ = self.writer.init();
-    }
 }
 
 impl<W> Drop for Terminal<W>
@@ -123,7 +168,7 @@ where
     W: TerminalWrite,
 {
     fn drop(&mut self) {
-        self.writer.deinit();
+        self.deinit();
     }
 }
 
@@ -1,4 +1,4 @@
-use super::terminal::{Terminal, TerminalWrite};
+use super::terminal::TerminalIo;
 use crate::{
     markdown::{
         elements::Text,
@@ -77,19 +77,16 @@ impl<'a> TextDrawer<'a> {
     /// Draw text on the given handle.
     ///
     /// This performs word splitting and word wrapping.
-    pub(crate) fn draw<W>(self, terminal: &mut Terminal<W>) -> RenderResult
+    pub(crate) fn draw<T>(self, terminal: &mut T) -> RenderResult
     where
-        W: TerminalWrite,
+        T: TerminalIo,
     {
         let mut line_length: u16 = 0;
 
         // Print the prefix at the beginning of the line.
-        let styled_prefix = {
-            let Text { content, style } = self.prefix.text();
-            style.apply(content)
-        };
+        let Text { content: prefix, style: prefix_style } = self.prefix.text();
         terminal.move_to_column(self.positioning.start_column)?;
-        terminal.print_styled_line(styled_prefix.clone())?;
+        terminal.print_styled_line(prefix, prefix_style)?;
 
         let start_column = self.positioning.start_column + self.prefix_length;
         for (line_index, line) in self.line.split(self.positioning.max_line_length as usize).enumerate() {
@@ -103,7 +100,7 @@ impl<'a> TextDrawer<'a> {
                 if self.prefix_length > 0 {
                     terminal.move_to_column(self.positioning.start_column)?;
                     if self.repeat_prefix {
-                        terminal.print_styled_line(styled_prefix.clone())?;
+                        terminal.print_styled_line(prefix, prefix_style)?;
                     } else {
                         self.print_block_background(self.prefix_length, terminal)?;
                     }
@@ -114,8 +111,7 @@ impl<'a> TextDrawer<'a> {
                 line_length = line_length.saturating_add(chunk.width() as u16);
 
                 let (text, style) = chunk.into_parts();
-                let text = style.apply(text);
-                terminal.print_styled_line(text)?;
+                terminal.print_styled_line(text, &style)?;
 
                 // Crossterm resets colors if any attributes are set so let's just re-apply colors
                 // if the format has anything on it at all.
@@ -128,9 +124,9 @@ impl<'a> TextDrawer<'a> {
         Ok(())
     }
 
-    fn print_block_background<W>(&self, line_length: u16, terminal: &mut Terminal<W>) -> RenderResult
+    fn print_block_background<T>(&self, line_length: u16, terminal: &mut T) -> RenderResult
     where
-        W: TerminalWrite,
+        T: TerminalIo,
     {
         if self.draw_block {
             let remaining =
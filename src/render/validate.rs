@@ -1,24 +1,22 @@
-use super::{properties::WindowSize, terminal::TerminalWrite};
+use super::{properties::WindowSize, virt::VirtualTerminal};
 use crate::{
-    ImagePrinter,
+    markdown::elements::SourcePosition,
     presentation::Presentation,
     render::{
         draw::RenderError,
         engine::{RenderEngine, RenderEngineOptions},
-        terminal::Terminal,
     },
 };
-use std::{io, sync::Arc};
+use std::fmt;
 
 pub(crate) struct OverflowValidator;
 
 impl OverflowValidator {
     pub(crate) fn validate(presentation: &Presentation, dimensions: WindowSize) -> Result<(), OverflowError> {
-        let printer = Arc::new(ImagePrinter::Null);
         for (index, slide) in presentation.iter_slides().enumerate() {
             let index = index + 1;
-            let mut terminal = Terminal::new(io::Empty::default(), printer.clone()).map_err(RenderError::from)?;
-            let options = RenderEngineOptions { validate_overflows: true };
+            let mut terminal = VirtualTerminal::new(&dimensions);
+            let options = RenderEngineOptions { validate_overflows: true, ..Default::default() };
             let engine = RenderEngine::new(&mut terminal, dimensions.clone(), options);
             match engine.render(slide.iter_visible_operations()) {
                 Ok(()) => (),
@@ -29,14 +27,38 @@ impl OverflowValidator {
         }
         Ok(())
     }
-}
 
-impl TerminalWrite for io::Empty {
-    fn init(&mut self) -> io::Result<()> {
-        Ok(())
-    }
+    /// Find every overflow in a presentation when rendering it using each of the given sizes.
+    ///
+    /// Slides are rendered with all of their chunks visible.
+    pub(crate) fn find_all(
+        presentation: &mut Presentation,
+        sizes: &[WindowSize],
+    ) -> Result<OverflowReport, RenderError> {
+        let total_slides = presentation.iter_slides().count();
+        let mut report = OverflowReport::default();
+        for dimensions in sizes {
+            for index in 0..total_slides {
+                presentation.go_to_slide(index);
+                let total_chunks = presentation.current_slide().iter_chunks().count();
+                presentation.jump_chunk(total_chunks.saturating_sub(1));
 
-    fn deinit(&mut self) {}
+                let slide = presentation.current_slide();
+                let mut terminal = VirtualTerminal::new(dimensions);
+                let engine = RenderEngine::new(&mut terminal, dimensions.clone(), Default::default());
+                for overflow in engine.find_overflows(slide.iter_visible_operations())? {
+                    report.push(SlideOverflow {
+                        columns: dimensions.columns,
+                        rows: dimensions.rows,
+                        slide: index + 1,
+                        kind: overflow.kind,
+                        source_position: slide.source_position(overflow.operation),
+                    });
+                }
+            }
+        }
+        Ok(report)
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -50,3 +72,179 @@ pub(crate) enum OverflowError {
     #[error(transparent)]
     Render(#[from] RenderError),
 }
+
+/// A place in which a slide doesn't fit on the screen.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Overflow {
+    /// The index of the operation, within the ones being rendered, that caused this.
+    pub(crate) operation: usize,
+
+    /// The kind of overflow.
+    pub(crate) kind: OverflowKind,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum OverflowKind {
+    /// A line is wider than the space it's given by this many columns.
+    Horizontal { columns: u16 },
+
+    /// A slide is taller than the screen by this many rows.
+    Vertical { rows: u16 },
+}
+
+/// The overflows found in a presentation.
+#[derive(Debug, Default)]
+pub struct OverflowReport {
+    overflows: Vec<SlideOverflow>,
+}
+
+impl OverflowReport {
+    /// Whether the presentation fits on the screen in all the sizes it was checked against.
+    pub fn is_empty(&self) -> bool {
+        self.overflows.is_empty()
+    }
+
+    fn push(&mut self, overflow: SlideOverflow) {
+        // Lines in the same code block overflow individually but they're a single problem.
+        if let Some(last) = self.overflows.last_mut() {
+            if last.is_same_place(&overflow) {
+                if let (OverflowKind::Horizontal { columns }, OverflowKind::Horizontal { columns: other }) =
+                    (&mut last.kind, &overflow.kind)
+                {
+                    *columns = (*columns).max(*other);
+                }
+                return;
+            }
+        }
+        self.overflows.push(overflow);
+    }
+}
+
+impl fmt::Display for OverflowReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for overflow in &self.overflows {
+            writeln!(f, "{overflow}")?;
+        }
+        Ok(())
+    }
+}
+
+/// An overflow in a specific slide when rendered using a specific size.
+#[derive(Debug)]
+struct SlideOverflow {
+    columns: u16,
+    rows: u16,
+    slide: usize,
+    kind: OverflowKind,
+    source_position: Option<SourcePosition>,
+}
+
+impl SlideOverflow {
+    fn is_same_place(&self, other: &Self) -> bool {
+        let same_kind = matches!(
+            (&self.kind, &other.kind),
+            (OverflowKind::Horizontal { .. }, OverflowKind::Horizontal { .. })
+                | (OverflowKind::Vertical { .. }, OverflowKind::Vertical { .. })
+        );
+        let same_line = match (&self.source_position, &other.source_position) {
+            (Some(ours), Some(theirs)) => ours.start.line == theirs.start.line,
+            (None, None) => true,
+            _ => false,
+        };
+        self.columns == other.columns && self.rows == other.rows && self.slide == other.slide && same_kind && same_line
+    }
+}
+
+impl fmt::Display for SlideOverflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self { columns, rows, slide, kind, source_position } = self;
+        write!(f, "{columns}x{rows}: slide {slide} ")?;
+        match kind {
+            OverflowKind::Horizontal { columns } => {
+                write!(f, "overflows horizontally by {}", plural(*columns, "column"))?
+            }
+            OverflowKind::Vertical { rows } => write!(f, "overflows vertically by {}", plural(*rows, "row"))?,
+        };
+        if let Some(position) = source_position {
+            write!(f, " at line {}", position.start.line)?;
+        }
+        Ok(())
+    }
+}
+
+fn plural(count: u16, noun: &str) -> String {
+    match count {
+        1 => format!("{count} {noun}"),
+        _ => format!("{count} {noun}s"),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        markdown::elements::LineColumn,
+        presentation::{BlockLine, RenderOperation, Slide, SlideChunk},
+        theme::{Alignment, Margin},
+    };
+
+    fn position(line: usize) -> SourcePosition {
+        SourcePosition { start: LineColumn { line, column: 1 } }
+    }
+
+    fn block_line(text: &str) -> RenderOperation {
+        RenderOperation::RenderBlockLine(BlockLine {
+            prefix: "".into(),
+            right_padding_length: 0,
+            repeat_prefix_on_wrap: false,
+            text: text.into(),
+            block_length: text.len() as u16,
+            alignment: Alignment::Left { margin: Margin::Fixed(0) },
+            block_color: None,
+        })
+    }
+
+    fn text_lines(count: usize) -> Vec<RenderOperation> {
+        (0..count)
+            .flat_map(|_| {
+                [
+                    RenderOperation::RenderText { line: "hi".into(), alignment: Default::default() },
+                    RenderOperation::RenderLineBreak,
+                ]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn find_all() {
+        let mut first = text_lines(1);
+        first.extend([block_line("0123456789abcdefghijkl"), RenderOperation::RenderLineBreak]);
+        first.extend([block_line("0123456789"), RenderOperation::RenderLineBreak]);
+        let first = SlideChunk::new(first, vec![]).with_source_positions(vec![(0, position(1)), (2, position(3))]);
+        let second = SlideChunk::new(text_lines(5), vec![]).with_source_positions(vec![(0, position(8))]);
+        let slides = vec![Slide::new(vec![first], vec![]), Slide::new(vec![SlideChunk::default(), second], vec![])];
+        let mut presentation = Presentation::from(slides);
+
+        let sizes = [WindowSize { rows: 4, columns: 20, width: 200, height: 80 }, WindowSize {
+            rows: 10,
+            columns: 40,
+            width: 400,
+            height: 200,
+        }];
+        let report = OverflowValidator::find_all(&mut presentation, &sizes).expect("render failed");
+        let expected = "\
+20x4: slide 1 overflows horizontally by 2 columns at line 3
+20x4: slide 2 overflows vertically by 1 row at line 8
+";
+        assert_eq!(report.to_string(), expected);
+    }
+
+    #[test]
+    fn no_overflows() {
+        let slides = vec![Slide::from(text_lines(2))];
+        let mut presentation = Presentation::from(slides);
+        let sizes = [WindowSize { rows: 4, columns: 8, width: 80, height: 80 }];
+        let report = OverflowValidator::find_all(&mut presentation, &sizes).expect("render failed");
+        assert!(report.is_empty());
+    }
+}
@@ -0,0 +1,248 @@
+use super::{properties::WindowSize, terminal::TerminalIo};
+use crate::{
+    media::{
+        image::Image,
+        printer::{PrintImageError, PrintOptions},
+    },
+    style::{Color, Colors, TextStyle},
+};
+use std::io;
+use unicode_width::UnicodeWidthChar;
+
+/// A character in the virtual terminal's grid along with its style.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct StyledChar {
+    pub(crate) character: char,
+    pub(crate) style: TextStyle,
+}
+
+impl StyledChar {
+    /// The character used to fill in the cell that follows a double width character.
+    pub(crate) const WIDE_CHAR_PADDING: char = '\0';
+
+    fn blank(colors: Colors) -> Self {
+        Self { character: ' ', style: TextStyle::colored(Colors { background: colors.background, foreground: None }) }
+    }
+}
+
+/// An image that was printed into the virtual terminal.
+#[derive(Clone, Debug)]
+pub(crate) struct PrintedImage {
+    pub(crate) image: Image,
+    pub(crate) row: u16,
+    pub(crate) column: u16,
+    pub(crate) rows: u16,
+    pub(crate) columns: u16,
+    pub(crate) z_index: i32,
+}
+
+/// The contents of a virtual terminal after rendering something into it.
+#[derive(Clone, Debug)]
+pub(crate) struct TerminalGrid {
+    pub(crate) rows: Vec<Vec<StyledChar>>,
+    pub(crate) background_color: Option<Color>,
+    pub(crate) images: Vec<PrintedImage>,
+}
+
+/// A terminal that keeps everything in memory.
+///
+/// This keeps track of every character printed along with its style and the position of every
+/// image in it, which allows rendering presentations without a real terminal.
+pub(crate) struct VirtualTerminal {
+    row: u16,
+    column: u16,
+    colors: Colors,
+    background_color: Option<Color>,
+    rows: Vec<Vec<StyledChar>>,
+    images: Vec<PrintedImage>,
+}
+
+impl VirtualTerminal {
+    pub(crate) fn new(dimensions: &WindowSize) -> Self {
+        let row = vec![StyledChar::blank(Colors::default()); dimensions.columns as usize];
+        let rows = vec![row; dimensions.rows as usize];
+        Self { row: 0, column: 0, colors: Default::default(), background_color: None, rows, images: Default::default() }
+    }
+
+    /// Consume this terminal and get the contents that were rendered into it.
+    pub(crate) fn into_contents(self) -> TerminalGrid {
+        TerminalGrid { rows: self.rows, background_color: self.background_color, images: self.images }
+    }
+
+    fn print(&mut self, text: &str, style: TextStyle) {
+        for character in text.chars() {
+            let width = character.width().unwrap_or(0) as u16;
+            if width == 0 {
+                continue;
+            }
+            self.set_cell(self.column, StyledChar { character, style });
+            for offset in 1..width {
+                let padding = StyledChar { character: StyledChar::WIDE_CHAR_PADDING, style };
+                self.set_cell(self.column + offset, padding);
+            }
+            self.column = self.column.saturating_add(width);
+        }
+    }
+
+    fn set_cell(&mut self, column: u16, cell: StyledChar) {
+        // Anything that falls outside of the screen is lost, same as in a real terminal.
+        if let Some(target) = self.rows.get_mut(self.row as usize).and_then(|row| row.get_mut(column as usize)) {
+            *target = cell;
+        }
+    }
+
+    fn current_style(&self) -> TextStyle {
+        TextStyle::colored(self.colors)
+    }
+}
+
+impl TerminalIo for VirtualTerminal {
+    fn begin_update(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn end_update(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn cursor_row(&self) -> u16 {
+        self.row
+    }
+
+    fn move_to(&mut self, column: u16, row: u16) -> io::Result<()> {
+        self.column = column;
+        self.row = row;
+        Ok(())
+    }
+
+    fn move_to_row(&mut self, row: u16) -> io::Result<()> {
+        self.row = row;
+        Ok(())
+    }
+
+    fn move_to_column(&mut self, column: u16) -> io::Result<()> {
+        self.column = column;
+        Ok(())
+    }
+
+    fn move_down(&mut self, amount: u16) -> io::Result<()> {
+        self.row = self.row.saturating_add(amount);
+        Ok(())
+    }
+
+    fn move_to_next_line(&mut self, amount: u16) -> io::Result<()> {
+        self.row = self.row.saturating_add(amount);
+        self.column = 0;
+        Ok(())
+    }
+
+    fn print_line(&mut self, text: &str) -> io::Result<()> {
+        self.print(text, self.current_style());
+        Ok(())
+    }
+
+    fn print_styled_line(&mut self, text: &str, style: &TextStyle) -> io::Result<()> {
+        let mut style = *style;
+        style.merge(&self.current_style());
+        self.print(text, style);
+        Ok(())
+    }
+
+    fn clear_screen(&mut self) -> io::Result<()> {
+        let blank = StyledChar::blank(self.colors);
+        for row in &mut self.rows {
+            row.fill(blank.clone());
+        }
+        self.images.clear();
+        self.background_color = self.colors.background;
+        self.row = 0;
+        Ok(())
+    }
+
+    fn set_colors(&mut self, colors: Colors) -> io::Result<()> {
+        self.colors = colors;
+        Ok(())
+    }
+
+    fn set_background_color(&mut self, color: Color) -> io::Result<()> {
+        self.colors.background = Some(color);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn print_image(&mut self, image: &Image, options: &PrintOptions) -> Result<(), PrintImageError> {
+        let image = PrintedImage {
+            image: image.clone(),
+            row: self.row,
+            column: options.cursor_position.column,
+            rows: options.rows,
+            columns: options.columns,
+            z_index: options.z_index,
+        };
+        self.images.push(image);
+        self.row = self.row.saturating_add(options.rows);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        markdown::text::WeightedLine,
+        presentation::RenderOperation,
+        render::engine::{RenderEngine, RenderEngineOptions},
+        theme::Alignment,
+    };
+
+    fn render(operations: &[RenderOperation]) -> TerminalGrid {
+        let dimensions = WindowSize { rows: 3, columns: 10, width: 100, height: 60 };
+        let mut terminal = VirtualTerminal::new(&dimensions);
+        let engine = RenderEngine::new(&mut terminal, dimensions, RenderEngineOptions::default());
+        engine.render(operations.iter()).expect("render failed");
+        terminal.into_contents()
+    }
+
+    fn row_text(grid: &TerminalGrid, row: usize) -> String {
+        grid.rows[row].iter().map(|c| c.character).collect()
+    }
+
+    #[test]
+    fn render_text() {
+        let colors = Colors { background: Some(Color::Black), foreground: Some(Color::Red) };
+        let grid = render(&[
+            RenderOperation::SetColors(colors),
+            RenderOperation::ClearScreen,
+            RenderOperation::JumpToRow { index: 1 },
+            RenderOperation::RenderText { line: WeightedLine::from("hi"), alignment: Alignment::default() },
+        ]);
+        assert_eq!(row_text(&grid, 0), "          ");
+        assert_eq!(row_text(&grid, 1), "hi        ");
+        assert_eq!(grid.rows[1][0].style.colors, colors);
+        assert_eq!(grid.rows[1][2].style.colors.foreground, None);
+        assert_eq!(grid.background_color, Some(Color::Black));
+    }
+
+    #[test]
+    fn wide_characters() {
+        let grid = render(&[RenderOperation::RenderText {
+            line: WeightedLine::from("漢字"),
+            alignment: Alignment::default(),
+        }]);
+        let characters: Vec<_> = grid.rows[0].iter().take(4).map(|c| c.character).collect();
+        let padding = StyledChar::WIDE_CHAR_PADDING;
+        assert_eq!(characters, &['漢', padding, '字', padding]);
+    }
+
+    #[test]
+    fn out_of_bounds_text_is_dropped() {
+        let grid = render(&[RenderOperation::JumpToRow { index: 10 }, RenderOperation::RenderText {
+            line: WeightedLine::from("hi"),
+            alignment: Alignment::default(),
+        }]);
+        assert!(grid.rows.iter().all(|row| row.iter().all(|c| c.character == ' ')));
+    }
+}
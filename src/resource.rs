@@ -25,7 +25,7 @@ pub struct Resources {
     base_path: PathBuf,
     images: HashMap<PathBuf, Image>,
     themes: HashMap<PathBuf, PresentationTheme>,
-    external_snippets: HashMap<PathBuf, String>,
+    external_files: HashMap<PathBuf, String>,
     image_registry: ImageRegistry,
     watcher: FileWatcherHandle,
 }
@@ -40,7 +40,7 @@ impl Resources {
             base_path: base_path.into(),
             images: Default::default(),
             themes: Default::default(),
-            external_snippets: Default::default(),
+            external_files: Default::default(),
             image_registry,
             watcher,
         }
@@ -76,14 +76,29 @@ impl Resources {
 
     /// Get the external snippet at the given path.
     pub(crate) fn external_snippet<P: AsRef<Path>>(&mut self, path: P) -> io::Result<String> {
+        self.external_file(path)
+    }
+
+    /// Get the contents of the markdown file at the given path that's included in the presentation.
+    pub(crate) fn included_file<P: AsRef<Path>>(&mut self, path: P) -> io::Result<String> {
+        self.external_file(path)
+    }
+
+    /// Get the canonical form of the given path.
+    pub(crate) fn canonicalize<P: AsRef<Path>>(&self, path: P) -> io::Result<PathBuf> {
+        fs::canonicalize(self.base_path.join(path))
+    }
+
+    // Loads a file and watches it so the presentation is reloaded if it changes.
+    fn external_file<P: AsRef<Path>>(&mut self, path: P) -> io::Result<String> {
         let path = self.base_path.join(path);
-        if let Some(contents) = self.external_snippets.get(&path) {
+        if let Some(contents) = self.external_files.get(&path) {
             return Ok(contents.clone());
         }
 
         let contents = fs::read_to_string(&path)?;
         self.watcher.send(WatchEvent::WatchFile { path: path.clone(), watch_forever: false });
-        self.external_snippets.insert(path, contents.clone());
+        self.external_files.insert(path, contents.clone());
         Ok(contents)
     }
 
@@ -94,7 +109,7 @@ impl Resources {
     pub(crate) fn clear_watches(&mut self) {
         self.watcher.send(WatchEvent::ClearWatches);
         // We could do better than this but this works for now.
-        self.external_snippets.clear();
+        self.external_files.clear();
     }
 
     /// Clears all resources.
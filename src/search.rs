@@ -0,0 +1,77 @@
+use std::ops::Range;
+
+/// A chunk in a slide that contains a match for a search query.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct SearchMatch {
+    pub(crate) slide: usize,
+    pub(crate) chunk: usize,
+}
+
+/// The state of a search over the presentation.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SearchState {
+    /// The text being searched for.
+    pub(crate) query: String,
+
+    /// Whether the query is still being typed in.
+    pub(crate) editing: bool,
+
+    /// The chunks that matched the query, in presentation order.
+    pub(crate) matches: Vec<SearchMatch>,
+
+    /// The index of the match that was last jumped to.
+    pub(crate) current: Option<usize>,
+}
+
+impl SearchState {
+    /// Whether there's an ongoing search.
+    pub(crate) fn is_active(&self) -> bool {
+        self.editing || !self.query.is_empty()
+    }
+}
+
+/// Find every case insensitive occurrence of `query` in `text`.
+///
+/// The returned ranges are character indexes, not byte indexes.
+pub(crate) fn find_matches(text: &str, query: &str) -> Vec<Range<usize>> {
+    let text: Vec<_> = text.chars().map(lowercase).collect();
+    let query: Vec<_> = query.chars().map(lowercase).collect();
+    let mut matches = Vec::new();
+    if query.is_empty() {
+        return matches;
+    }
+    let mut start = 0;
+    while start + query.len() <= text.len() {
+        if text[start..start + query.len()] == query[..] {
+            matches.push(start..start + query.len());
+            start += query.len();
+        } else {
+            start += 1;
+        }
+    }
+    matches
+}
+
+// Only take the first character so that character indexes in the lowercased text match the
+// original one.
+fn lowercase(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case::none("hello world", "potato", &[])]
+    #[case::empty_query("hello", "", &[])]
+    #[case::single("hello world", "world", &[(6, 11)])]
+    #[case::case_insensitive("Hello HELLO", "hello", &[(0, 5), (6, 11)])]
+    #[case::non_overlapping("aaaa", "aa", &[(0, 2), (2, 4)])]
+    #[case::multibyte("añoño", "ño", &[(1, 3), (3, 5)])]
+    fn matches(#[case] text: &str, #[case] query: &str, #[case] expected: &[(usize, usize)]) {
+        let matches: Vec<_> = find_matches(text, query).into_iter().map(|range| (range.start, range.end)).collect();
+        assert_eq!(matches, expected);
+    }
+}
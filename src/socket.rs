@@ -0,0 +1,86 @@
+use std::{io, path::Path};
+
+/// Create a directory that only the current user can access, if it doesn't exist already.
+///
+/// This fails if the directory exists but it belongs to someone else or other users can access it.
+#[cfg(unix)]
+pub(crate) fn create_private_directory(path: &Path) -> io::Result<()> {
+    use std::{
+        fs,
+        os::unix::fs::{DirBuilderExt, MetadataExt},
+    };
+
+    fs::DirBuilder::new().recursive(true).mode(0o700).create(path)?;
+    let metadata = fs::symlink_metadata(path)?;
+    let uid = unsafe { libc::getuid() };
+    if !metadata.is_dir() || metadata.uid() != uid || metadata.mode() & 0o077 != 0 {
+        let message = format!("{} is not a directory only the current user can access", path.display());
+        return Err(io::Error::new(io::ErrorKind::PermissionDenied, message));
+    }
+    Ok(())
+}
+
+/// Bind a unix socket in the given path.
+///
+/// If the path exists but nobody is listening on it, it is assumed to be left over from a process
+/// that didn't exit cleanly and it's replaced. The socket is only accessible by the current user.
+#[cfg(unix)]
+pub(crate) fn bind_unix_socket(path: &Path) -> io::Result<std::os::unix::net::UnixListener> {
+    use std::{
+        fs,
+        os::unix::{
+            fs::PermissionsExt,
+            net::{UnixListener, UnixStream},
+        },
+    };
+
+    if path.exists() {
+        if UnixStream::connect(path).is_ok() {
+            return Err(io::Error::new(io::ErrorKind::AddrInUse, format!("{} is already in use", path.display())));
+        }
+        fs::remove_file(path)?;
+    }
+    // Create the socket with the right permissions rather than fixing them up after the fact, so
+    // nobody else can connect to it in between.
+    let umask = unsafe { libc::umask(0o177) };
+    let listener = UnixListener::bind(path);
+    unsafe { libc::umask(umask) };
+    let listener = listener?;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+    Ok(listener)
+}
+
+#[cfg(test)]
+#[cfg(unix)]
+mod test {
+    use super::*;
+    use std::os::unix::net::UnixListener;
+
+    #[test]
+    fn stale_socket_is_replaced() {
+        let directory = tempfile::tempdir().expect("creating directory failed");
+        let path = directory.path().join("test.sock");
+        drop(UnixListener::bind(&path).expect("bind failed"));
+        assert!(path.exists());
+
+        let _listener = bind_unix_socket(&path).expect("bind failed");
+        let error = bind_unix_socket(&path).expect_err("bind succeeded");
+        assert_eq!(error.kind(), io::ErrorKind::AddrInUse);
+    }
+
+    #[test]
+    fn private_directory() {
+        use std::{fs, os::unix::fs::PermissionsExt};
+
+        let directory = tempfile::tempdir().expect("creating directory failed");
+        let path = directory.path().join("private");
+        create_private_directory(&path).expect("create failed");
+        assert_eq!(fs::metadata(&path).expect("no metadata").permissions().mode() & 0o777, 0o700);
+        // Creating it again is fine as long as it's still private.
+        create_private_directory(&path).expect("create failed");
+
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).expect("chmod failed");
+        let error = create_private_directory(&path).expect_err("create succeeded");
+        assert_eq!(error.kind(), io::ErrorKind::PermissionDenied);
+    }
+}
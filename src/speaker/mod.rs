@@ -0,0 +1,163 @@
+use crate::presentation::Presentation;
+use serde::{Deserialize, Serialize};
+use std::{
+    env,
+    hash::{DefaultHasher, Hash, Hasher},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+pub(crate) mod publisher;
+pub(crate) mod view;
+
+/// The state of a running presentation, as seen by the presenter view.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct PresentationStatus {
+    /// The current slide, starting at 1.
+    pub(crate) slide: usize,
+
+    /// The total number of slides.
+    pub(crate) total_slides: usize,
+
+    /// The current chunk within the current slide, starting at 1.
+    pub(crate) chunk: usize,
+
+    /// The total number of chunks in the current slide.
+    pub(crate) total_chunks: usize,
+
+    /// The current slide's title.
+    pub(crate) title: String,
+
+    /// The next slide's title, if there is one.
+    pub(crate) next_slide_title: Option<String>,
+
+    /// The speaker notes for the current slide.
+    pub(crate) speaker_notes: Vec<String>,
+
+    /// The time the presentation was started at, in seconds since the unix epoch.
+    pub(crate) started_at: u64,
+}
+
+impl PresentationStatus {
+    pub(crate) fn new(presentation: &Presentation, started_at: u64) -> Option<Self> {
+        let slides: Vec<_> = presentation.iter_slides().collect();
+        let index = presentation.current_slide_index();
+        let slide = slides.get(index)?;
+        Some(Self {
+            slide: index + 1,
+            total_slides: slides.len(),
+            chunk: presentation.current_chunk() + 1,
+            total_chunks: slide.iter_chunks().count(),
+            title: slide.title().to_string(),
+            next_slide_title: slides.get(index + 1).map(|slide| slide.title().to_string()),
+            speaker_notes: slide.speaker_notes().to_vec(),
+            started_at,
+        })
+    }
+}
+
+/// Get the path to the socket a presentation publishes its status into.
+///
+/// The path is derived from the presentation's path so both the presentation and the presenter
+/// view can find it without any extra configuration. It lives in a directory only the current
+/// user can access so nobody else can take over the path or connect to it.
+pub(crate) fn socket_path(presentation_path: &Path) -> PathBuf {
+    let path = presentation_path.canonicalize().unwrap_or_else(|_| presentation_path.to_path_buf());
+    let mut hasher = DefaultHasher::new();
+    path.hash(&mut hasher);
+    socket_directory().join(format!("presenterm-{:016x}.sock", hasher.finish()))
+}
+
+fn socket_directory() -> PathBuf {
+    match env::var_os("XDG_RUNTIME_DIR") {
+        Some(directory) if !directory.is_empty() => directory.into(),
+        _ => env::temp_dir().join(format!("presenterm-{}", user_id())),
+    }
+}
+
+#[cfg(unix)]
+fn user_id() -> u32 {
+    unsafe { libc::getuid() }
+}
+
+#[cfg(not(unix))]
+fn user_id() -> u32 {
+    0
+}
+
+/// Get the current time in seconds since the unix epoch.
+pub(crate) fn unix_time() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_secs()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::presentation::{SlideBuilder, SlideChunk};
+
+    fn build_slide(title: &str, notes: &[&str], chunks: usize) -> crate::presentation::Slide {
+        let chunks = (0..chunks).map(|_| SlideChunk::default()).collect();
+        let notes = notes.iter().map(|note| note.to_string()).collect();
+        SlideBuilder::default().chunks(chunks).title(title.into()).speaker_notes(notes).build()
+    }
+
+    #[test]
+    fn status() {
+        let slides = vec![build_slide("one", &["note"], 2), build_slide("two", &[], 1)];
+        let mut presentation = Presentation::from(slides);
+        presentation.jump_next();
+
+        let status = PresentationStatus::new(&presentation, 42).expect("no status");
+        let expected = PresentationStatus {
+            slide: 1,
+            total_slides: 2,
+            chunk: 2,
+            total_chunks: 2,
+            title: "one".into(),
+            next_slide_title: Some("two".into()),
+            speaker_notes: vec!["note".into()],
+            started_at: 42,
+        };
+        assert_eq!(status, expected);
+
+        presentation.jump_next();
+        let status = PresentationStatus::new(&presentation, 42).expect("no status");
+        assert_eq!(status.slide, 2);
+        assert_eq!(status.next_slide_title, None);
+    }
+
+    #[test]
+    fn empty_presentation() {
+        assert_eq!(PresentationStatus::new(&Presentation::from(vec![]), 0), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn publish() {
+        use publisher::StatusPublisher;
+        use std::{
+            io::{BufRead, BufReader},
+            os::unix::net::UnixStream,
+        };
+
+        let directory = tempfile::tempdir().expect("creating directory failed");
+        let path = directory.path().join("run/presentation.sock");
+        let mut publisher = StatusPublisher::new(path.clone()).expect("creating publisher failed");
+        let slides = vec![build_slide("one", &[], 1), build_slide("two", &[], 1)];
+        let mut presentation = Presentation::from(slides);
+        publisher.publish(&PresentationStatus::new(&presentation, 0).unwrap());
+
+        let client = UnixStream::connect(&path).expect("connecting failed");
+        publisher.publish(&PresentationStatus::new(&presentation, 0).unwrap());
+        presentation.jump_next();
+        publisher.publish(&PresentationStatus::new(&presentation, 0).unwrap());
+
+        let mut lines = BufReader::new(client).lines();
+        let mut next_status = || serde_json::from_str::<PresentationStatus>(&lines.next().unwrap().unwrap()).unwrap();
+        assert_eq!(next_status().slide, 1);
+        assert_eq!(next_status().slide, 2);
+
+        drop(publisher);
+        assert!(!path.exists());
+    }
+}
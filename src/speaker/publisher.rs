@@ -0,0 +1,69 @@
+use super::PresentationStatus;
+use std::{io, path::PathBuf};
+
+/// Publishes the status of a presentation into a unix socket.
+///
+/// Every client that connects to the socket gets the current status as soon as it connects and
+/// then every time it changes, as a JSON object per line.
+#[cfg(unix)]
+pub(crate) struct StatusPublisher {
+    listener: std::os::unix::net::UnixListener,
+    path: PathBuf,
+    clients: Vec<std::os::unix::net::UnixStream>,
+    last_message: Vec<u8>,
+}
+
+#[cfg(unix)]
+impl StatusPublisher {
+    pub(crate) fn new(path: PathBuf) -> io::Result<Self> {
+        if let Some(directory) = path.parent() {
+            crate::socket::create_private_directory(directory)?;
+        }
+        // This fails if the same presentation is already running somewhere else.
+        let listener = crate::socket::bind_unix_socket(&path)?;
+        listener.set_nonblocking(true)?;
+        Ok(Self { listener, path, clients: Vec::new(), last_message: Vec::new() })
+    }
+
+    /// Publish the given status.
+    ///
+    /// This also accepts any clients that connected since the last time this was called.
+    pub(crate) fn publish(&mut self, status: &PresentationStatus) {
+        use std::io::Write;
+
+        let mut message = serde_json::to_vec(status).expect("serialization failed");
+        message.push(b'\n');
+        if message != self.last_message {
+            // Clients that can't keep up or went away are simply dropped.
+            self.clients.retain_mut(|client| client.write_all(&message).is_ok());
+            self.last_message = message;
+        }
+        while let Ok((mut client, _)) = self.listener.accept() {
+            if client.set_nonblocking(true).is_ok() && client.write_all(&self.last_message).is_ok() {
+                self.clients.push(client);
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for StatusPublisher {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Publishes the status of a presentation.
+///
+/// This is only supported on unix systems.
+#[cfg(not(unix))]
+pub(crate) struct StatusPublisher;
+
+#[cfg(not(unix))]
+impl StatusPublisher {
+    pub(crate) fn new(_path: PathBuf) -> io::Result<Self> {
+        Err(io::Error::new(io::ErrorKind::Unsupported, "presenter view is only supported on unix systems"))
+    }
+
+    pub(crate) fn publish(&mut self, _status: &PresentationStatus) {}
+}
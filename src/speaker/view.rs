@@ -0,0 +1,241 @@
+use super::{PresentationStatus, socket_path, unix_time};
+use crate::{
+    input::{
+        source::Command,
+        user::{CommandKeyBindings, UserInput},
+    },
+    markdown::{elements::Text, text::WeightedLine},
+    presentation::RenderOperation,
+    render::{
+        draw::{RenderError, TerminalDrawer},
+        terminal::TerminalWrite,
+    },
+    style::{Colors, TextStyle},
+    theme::{Alignment, Margin, PresentationTheme},
+};
+use std::{
+    io,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Sender},
+    thread,
+    time::Duration,
+};
+
+const REFRESH_INTERVAL: Duration = Duration::from_millis(250);
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(1);
+const MARGIN: Margin = Margin::Fixed(2);
+
+/// A view that follows a running presentation and displays its speaker notes.
+pub struct PresenterView<W: TerminalWrite> {
+    socket_path: PathBuf,
+    input: UserInput,
+    drawer: TerminalDrawer<W>,
+    style: ViewStyle,
+}
+
+impl<W: TerminalWrite> PresenterView<W> {
+    /// Construct a presenter view that follows the presentation in the given path.
+    pub fn new(
+        presentation_path: &Path,
+        theme: &PresentationTheme,
+        bindings: CommandKeyBindings,
+        writer: W,
+        font_size_fallback: u8,
+    ) -> io::Result<Self> {
+        let input = UserInput::new(bindings);
+        let drawer = TerminalDrawer::new(writer, Default::default(), font_size_fallback)?;
+        let style = ViewStyle {
+            colors: theme.default_style.colors,
+            title: TextStyle::colored(theme.slide_title.colors).bold(),
+            label: TextStyle::default().bold(),
+        };
+        Ok(Self { socket_path: socket_path(presentation_path), input, drawer, style })
+    }
+
+    /// Run the presenter view until the user exits.
+    pub fn run(mut self) -> Result<(), RenderError> {
+        let (sender, receiver) = mpsc::channel();
+        let path = self.socket_path.clone();
+        thread::spawn(move || listen(path, sender));
+
+        let mut status: Option<PresentationStatus> = None;
+        let mut needs_render = true;
+        let mut last_elapsed = None;
+        loop {
+            let elapsed = status.as_ref().map(|status| unix_time().saturating_sub(status.started_at));
+            if needs_render || elapsed != last_elapsed {
+                let operations = build_operations(status.as_ref(), elapsed.unwrap_or_default(), &self.style);
+                match self.drawer.render_operations(operations.iter()) {
+                    // If the screen is too small, simply ignore this until it's resized.
+                    Ok(()) | Err(RenderError::TerminalTooSmall) => (),
+                    Err(e) => return Err(e),
+                };
+                needs_render = false;
+                last_elapsed = elapsed;
+            }
+            match self.input.poll_next_command(REFRESH_INTERVAL)? {
+                Some(Command::Exit) => return Ok(()),
+                Some(Command::Redraw) => needs_render = true,
+                _ => (),
+            };
+            while let Ok(update) = receiver.try_recv() {
+                status = update;
+                needs_render = true;
+            }
+        }
+    }
+}
+
+struct ViewStyle {
+    colors: Colors,
+    title: TextStyle,
+    label: TextStyle,
+}
+
+/// Connect to the presentation and forward every status update it publishes.
+///
+/// A `None` is sent every time the connection is lost.
+#[cfg(unix)]
+fn listen(path: PathBuf, sender: Sender<Option<PresentationStatus>>) {
+    use std::{
+        io::{BufRead, BufReader},
+        os::unix::net::UnixStream,
+    };
+
+    loop {
+        if let Ok(stream) = UnixStream::connect(&path) {
+            for line in BufReader::new(stream).lines() {
+                let Ok(line) = line else { break };
+                if let Ok(status) = serde_json::from_str(&line) {
+                    if sender.send(Some(status)).is_err() {
+                        return;
+                    }
+                }
+            }
+            if sender.send(None).is_err() {
+                return;
+            }
+        }
+        thread::sleep(RECONNECT_INTERVAL);
+    }
+}
+
+#[cfg(not(unix))]
+fn listen(_path: PathBuf, _sender: Sender<Option<PresentationStatus>>) {}
+
+fn build_operations(status: Option<&PresentationStatus>, elapsed: u64, style: &ViewStyle) -> Vec<RenderOperation> {
+    let left = Alignment::Left { margin: MARGIN };
+    let text = |line: Vec<Text>, alignment: &Alignment| RenderOperation::RenderText {
+        line: WeightedLine::from(line),
+        alignment: alignment.clone(),
+    };
+    let mut operations =
+        vec![RenderOperation::SetColors(style.colors), RenderOperation::ClearScreen, RenderOperation::JumpToRow {
+            index: 1,
+        }];
+    let Some(status) = status else {
+        let waiting = Text::new("Waiting for the presentation to start...", TextStyle::default().italics());
+        operations.push(text(vec![waiting], &left));
+        return operations;
+    };
+
+    let position = vec![
+        Text::new("Slide ", style.label),
+        Text::from(format!("{} / {}", status.slide, status.total_slides)),
+        Text::new("    Step ", style.label),
+        Text::from(format!("{} / {}", status.chunk, status.total_chunks)),
+    ];
+    let (hours, minutes, seconds) = (elapsed / 3600, elapsed / 60 % 60, elapsed % 60);
+    let timer = Text::new(format!("{hours:02}:{minutes:02}:{seconds:02}"), style.label);
+    operations.extend([
+        text(position, &left),
+        RenderOperation::JumpToRow { index: 1 },
+        text(vec![timer], &Alignment::Right { margin: MARGIN }),
+        RenderOperation::RenderLineBreak,
+        RenderOperation::RenderLineBreak,
+        text(vec![Text::new(status.title.clone(), style.title)], &left),
+        RenderOperation::RenderLineBreak,
+    ]);
+    let next = match &status.next_slide_title {
+        Some(title) => Text::from(title.clone()),
+        None => Text::new("end of presentation", TextStyle::default().italics()),
+    };
+    operations.extend([
+        text(vec![Text::new("Next: ", style.label), next], &left),
+        RenderOperation::RenderLineBreak,
+        RenderOperation::RenderLineBreak,
+        text(vec![Text::new("Notes", style.label)], &left),
+        RenderOperation::RenderLineBreak,
+        RenderOperation::RenderLineBreak,
+    ]);
+    if status.speaker_notes.is_empty() {
+        let empty = Text::new("This slide has no speaker notes", TextStyle::default().italics());
+        operations.extend([text(vec![empty], &left), RenderOperation::RenderLineBreak]);
+    }
+    for (index, note) in status.speaker_notes.iter().enumerate() {
+        if index > 0 {
+            operations.push(RenderOperation::RenderLineBreak);
+        }
+        for line in note.lines() {
+            operations.extend([text(vec![Text::from(line)], &left), RenderOperation::RenderLineBreak]);
+        }
+    }
+    operations
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::render::{
+        engine::{RenderEngine, RenderEngineOptions},
+        properties::WindowSize,
+        virt::{TerminalGrid, VirtualTerminal},
+    };
+
+    fn render(status: Option<&PresentationStatus>, elapsed: u64) -> Vec<String> {
+        let style = ViewStyle { colors: Default::default(), title: Default::default(), label: Default::default() };
+        let operations = build_operations(status, elapsed, &style);
+        let dimensions = WindowSize { rows: 12, columns: 40, width: 400, height: 240 };
+        let mut terminal = VirtualTerminal::new(&dimensions);
+        let engine = RenderEngine::new(&mut terminal, dimensions, RenderEngineOptions::default());
+        engine.render(operations.iter()).expect("render failed");
+        let TerminalGrid { rows, .. } = terminal.into_contents();
+        rows.into_iter().map(|row| row.into_iter().map(|c| c.character).collect::<String>().trim_end().into()).collect()
+    }
+
+    #[test]
+    fn waiting() {
+        let rows = render(None, 0);
+        assert_eq!(&rows[1..3], &["  Waiting for the presentation to", "  start..."]);
+    }
+
+    #[test]
+    fn status() {
+        let status = PresentationStatus {
+            slide: 2,
+            total_slides: 5,
+            chunk: 1,
+            total_chunks: 3,
+            title: "Intro".into(),
+            next_slide_title: Some("Details".into()),
+            speaker_notes: vec!["first\nsecond".into(), "third".into()],
+            started_at: 0,
+        };
+        let rows = render(Some(&status), 3723);
+        let expected = &[
+            "",
+            "  Slide 2 / 5    Step 1 / 3   01:02:03",
+            "",
+            "  Intro",
+            "  Next: Details",
+            "",
+            "  Notes",
+            "",
+            "  first",
+            "  second",
+            "",
+            "  third",
+        ];
+        assert_eq!(rows, expected);
+    }
+}
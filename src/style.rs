@@ -44,6 +44,16 @@ impl TextStyle {
         self.add_flag(TextFormatFlags::Underlined)
     }
 
+    /// Indicate this text is a reference to a footnote.
+    pub(crate) fn footnote_reference(self) -> Self {
+        self.add_flag(TextFormatFlags::FootnoteReference)
+    }
+
+    /// Indicate this text is a math formula.
+    pub(crate) fn math(self) -> Self {
+        self.add_flag(TextFormatFlags::Math)
+    }
+
     /// Indicate this is a link label.
     pub(crate) fn link_label(self) -> Self {
         self.bold()
@@ -92,6 +102,16 @@ impl TextStyle {
         self.has_flag(TextFormatFlags::Code)
     }
 
+    /// Check whether this text is a reference to a footnote.
+    pub(crate) fn is_footnote_reference(&self) -> bool {
+        self.has_flag(TextFormatFlags::FootnoteReference)
+    }
+
+    /// Check whether this text is a math formula.
+    pub(crate) fn is_math(&self) -> bool {
+        self.has_flag(TextFormatFlags::Math)
+    }
+
     /// Check whether this text style is strikethrough.
     pub(crate) fn is_strikethrough(&self) -> bool {
         self.has_flag(TextFormatFlags::Strikethrough)
@@ -156,6 +176,8 @@ enum TextFormatFlags {
     Code = 4,
     Strikethrough = 8,
     Underlined = 16,
+    FootnoteReference = 32,
+    Math = 64,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, SerializeDisplay, DeserializeFromStr)]
@@ -191,6 +213,31 @@ impl Color {
         }
     }
 
+    /// Get the RGB representation of this color.
+    ///
+    /// Named colors are mapped to the values used in xterm's default palette.
+    pub(crate) fn to_palette_rgb(self) -> (u8, u8, u8) {
+        match self {
+            Self::Black => (0, 0, 0),
+            Self::DarkGrey => (127, 127, 127),
+            Self::Red => (255, 0, 0),
+            Self::DarkRed => (205, 0, 0),
+            Self::Green => (0, 255, 0),
+            Self::DarkGreen => (0, 205, 0),
+            Self::Yellow => (255, 255, 0),
+            Self::DarkYellow => (205, 205, 0),
+            Self::Blue => (92, 92, 255),
+            Self::DarkBlue => (0, 0, 238),
+            Self::Magenta => (255, 0, 255),
+            Self::DarkMagenta => (205, 0, 205),
+            Self::Cyan => (0, 255, 255),
+            Self::DarkCyan => (0, 205, 205),
+            Self::White => (255, 255, 255),
+            Self::Grey => (229, 229, 229),
+            Self::Rgb { r, g, b } => (r, g, b),
+        }
+    }
+
     pub(crate) fn from_ansi(color: u8) -> Option<Self> {
         let color = match color {
             30 | 40 => Color::Black,
@@ -144,12 +144,24 @@ pub struct PresentationTheme {
 
     /// The style for a table.
     #[serde(default)]
-    pub(crate) table: Option<Alignment>,
+    pub(crate) table: TableStyle,
 
     /// The style for a block quote.
     #[serde(default)]
     pub(crate) block_quote: BlockQuoteStyle,
 
+    /// The style for footnotes.
+    #[serde(default)]
+    pub(crate) footnotes: FootnoteStyle,
+
+    /// The style for task lists.
+    #[serde(default)]
+    pub(crate) task_list: TaskListStyle,
+
+    /// The style for tables of contents.
+    #[serde(default)]
+    pub(crate) table_of_contents: TableOfContentsStyle,
+
     /// The default style.
     #[serde(rename = "default", default)]
     pub(crate) default_style: DefaultStyle,
@@ -177,6 +189,10 @@ pub struct PresentationTheme {
     /// The style for modals.
     #[serde(default)]
     pub(crate) modals: ModalStyle,
+
+    /// The style for the search bar and search matches.
+    #[serde(default)]
+    pub(crate) search: SearchStyle,
 }
 
 impl PresentationTheme {
@@ -210,7 +226,7 @@ impl PresentationTheme {
             PresentationLocation => &self.intro_slide.location.alignment,
             PresentationDate => &self.intro_slide.date.alignment,
             PresentationAuthor => &self.intro_slide.author.alignment,
-            Table => &self.table,
+            Table => &self.table.alignment,
             BlockQuote => &self.block_quote.alignment,
         };
         alignment.clone().unwrap_or_default()
@@ -317,6 +333,133 @@ pub(crate) struct BlockQuoteStyle {
     pub(crate) colors: BlockQuoteColors,
 }
 
+/// The style of a table.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub(crate) struct TableStyle {
+    /// The alignment.
+    #[serde(flatten, default)]
+    pub(crate) alignment: Option<Alignment>,
+
+    /// The set of characters used to draw the table's borders.
+    #[serde(default)]
+    pub(crate) border: TableBorder,
+
+    /// Whether to draw a border around the table.
+    ///
+    /// By default borders are only drawn between columns and below the header.
+    #[serde(default)]
+    pub(crate) outer_border: bool,
+
+    /// The colors to use for the borders.
+    #[serde(default)]
+    pub(crate) border_colors: Colors,
+
+    /// The number of spaces on each side of a cell's contents.
+    #[serde(default)]
+    pub(crate) padding: Option<u8>,
+
+    /// The style for the table's header.
+    #[serde(default)]
+    pub(crate) header: TableHeaderStyle,
+
+    /// The style for the table's rows.
+    #[serde(default)]
+    pub(crate) rows: TableRowsStyle,
+}
+
+/// The set of characters used to draw a table's borders.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum TableBorder {
+    /// Don't draw any borders, only separate columns with spaces.
+    None,
+
+    /// Thin box drawing characters.
+    #[default]
+    Light,
+
+    /// Thick box drawing characters.
+    Heavy,
+
+    /// Thin box drawing characters with rounded corners.
+    Rounded,
+
+    /// Plain ASCII characters.
+    Ascii,
+}
+
+/// The style of a table's header.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub(crate) struct TableHeaderStyle {
+    /// The colors to be used.
+    #[serde(default)]
+    pub(crate) colors: Colors,
+
+    /// Whether to use bold font for the header.
+    #[serde(default)]
+    pub(crate) bold: Option<bool>,
+}
+
+/// The style of a table's rows.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub(crate) struct TableRowsStyle {
+    /// The colors to be used.
+    #[serde(default)]
+    pub(crate) colors: Colors,
+
+    /// The colors to be used in every other row, starting from the second one.
+    #[serde(default)]
+    pub(crate) alternate_colors: Option<Colors>,
+}
+
+/// The style of footnotes.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub(crate) struct FootnoteStyle {
+    /// The colors to use for the footnote definitions at the bottom of a slide.
+    #[serde(default)]
+    pub(crate) colors: Colors,
+
+    /// The colors to use for the superscript markers that reference a footnote.
+    #[serde(default)]
+    pub(crate) reference_colors: Colors,
+}
+
+/// The style of task lists.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub(crate) struct TaskListStyle {
+    /// The style for tasks that are done.
+    #[serde(default)]
+    pub(crate) done: TaskListItemStyle,
+
+    /// The style for tasks that are still pending.
+    #[serde(default)]
+    pub(crate) pending: TaskListItemStyle,
+}
+
+/// The style of an item in a task list.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub(crate) struct TaskListItemStyle {
+    /// The symbol to use as the item's checkbox.
+    #[serde(default)]
+    pub(crate) symbol: Option<String>,
+
+    /// The colors to use for the item.
+    #[serde(default)]
+    pub(crate) colors: Colors,
+}
+
+/// The style of a table of contents.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub(crate) struct TableOfContentsStyle {
+    /// The colors to use for the entries.
+    #[serde(default)]
+    pub(crate) colors: Colors,
+
+    /// The colors to use for the entries in the section the table of contents is in.
+    #[serde(default)]
+    pub(crate) current_colors: Colors,
+}
+
 /// The colors of a block quote.
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub(crate) struct BlockQuoteColors {
@@ -527,6 +670,14 @@ pub(crate) struct ExecutionStatusBlockStyle {
     /// The colors for the "not started" status.
     #[serde(default)]
     pub(crate) not_started: Colors,
+
+    /// The colors for the "timed out" status.
+    #[serde(default)]
+    pub(crate) timed_out: Colors,
+
+    /// The colors for the "cancelled" status.
+    #[serde(default)]
+    pub(crate) cancelled: Colors,
 }
 
 /// The style for inline code.
@@ -654,6 +805,18 @@ pub(crate) struct ModalStyle {
     pub(crate) selection_colors: Colors,
 }
 
+/// Search style.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub(crate) struct SearchStyle {
+    /// The colors to use in the search bar.
+    #[serde(default)]
+    pub(crate) colors: Colors,
+
+    /// The colors to use for text that matches the search query.
+    #[serde(default)]
+    pub(crate) match_colors: Colors,
+}
+
 /// An error loading a presentation theme.
 #[derive(thiserror::Error, Debug)]
 pub enum LoadThemeError {
@@ -54,10 +54,22 @@ impl ThirdPartyRender {
         slide: usize,
         width: Option<Percent>,
     ) -> Result<RenderOperation, ThirdPartyRenderError> {
-        let result = self.render_pool.render(request);
-        let operation = Rc::new(RenderThirdParty::new(result, theme.default_style.colors, error_holder, slide, width));
+        let operation = self.render_async(request, theme, error_holder, slide, width);
         Ok(RenderOperation::RenderAsync(operation))
     }
+
+    /// Start rendering a request and get a handle to its result.
+    pub(crate) fn render_async(
+        &self,
+        request: ThirdPartyRenderRequest,
+        theme: &PresentationTheme,
+        error_holder: AsyncPresentationErrorHolder,
+        slide: usize,
+        width: Option<Percent>,
+    ) -> Rc<RenderThirdParty> {
+        let result = self.render_pool.render(request);
+        Rc::new(RenderThirdParty::new(result, theme.default_style.colors, error_holder, slide, width))
+    }
 }
 
 impl Default for ThirdPartyRender {
@@ -75,6 +87,7 @@ impl Default for ThirdPartyRender {
 pub(crate) enum ThirdPartyRenderRequest {
     Typst(String, TypstStyle),
     Latex(String, TypstStyle),
+    Math(String, TypstStyle),
     Mermaid(String, MermaidStyle),
 }
 
@@ -82,6 +95,7 @@ pub(crate) enum ThirdPartyRenderRequest {
 enum RenderResult {
     Success(Image),
     Failure(String),
+    Fallback(String),
     #[default]
     Pending,
 }
@@ -152,6 +166,14 @@ impl Worker {
         let output = match request {
             ThirdPartyRenderRequest::Typst(input, style) => self.render_typst(input, &style),
             ThirdPartyRenderRequest::Latex(input, style) => self.render_latex(input, &style),
+            ThirdPartyRenderRequest::Math(input, style) => match self.render_latex(input.clone(), &style) {
+                // Formulas in text are displayed as is rather than failing if pandoc isn't installed.
+                Err(ThirdPartyRenderError::Execution(ExecutionError::SpawnNotFound { command: "pandoc" })) => {
+                    *result.lock().unwrap() = RenderResult::Fallback(input);
+                    return;
+                }
+                output => output,
+            },
             ThirdPartyRenderRequest::Mermaid(input, style) => self.render_mermaid(input, &style),
         };
         let mut result = result.lock().unwrap();
@@ -304,6 +326,7 @@ struct ImageSnippet {
 #[derive(Debug)]
 pub(crate) struct RenderThirdParty {
     contents: Arc<Mutex<Option<Image>>>,
+    fallback: Arc<Mutex<Option<String>>>,
     pending_result: Arc<Mutex<RenderResult>>,
     default_colors: Colors,
     error_holder: AsyncPresentationErrorHolder,
@@ -319,7 +342,27 @@ impl RenderThirdParty {
         slide: usize,
         width: Option<Percent>,
     ) -> Self {
-        Self { contents: Default::default(), pending_result, default_colors, error_holder, slide, width }
+        Self {
+            contents: Default::default(),
+            fallback: Default::default(),
+            pending_result,
+            default_colors,
+            error_holder,
+            slide,
+            width,
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn with_image(image: Option<Image>) -> Self {
+        let mut this = Self::new(Default::default(), Default::default(), Default::default(), 0, None);
+        this.contents = Arc::new(Mutex::new(image));
+        this
+    }
+
+    /// Get the rendered image, if rendering is done and it succeeded.
+    pub(crate) fn image(&self) -> Option<Image> {
+        self.contents.lock().unwrap().clone()
     }
 }
 
@@ -330,7 +373,8 @@ impl RenderAsync for RenderThirdParty {
 
     fn poll_state(&self) -> RenderAsyncState {
         let mut contents = self.contents.lock().unwrap();
-        if contents.is_some() {
+        let mut fallback = self.fallback.lock().unwrap();
+        if contents.is_some() || fallback.is_some() {
             return RenderAsyncState::Rendered;
         }
         match mem::take(&mut *self.pending_result.lock().unwrap()) {
@@ -342,6 +386,10 @@ impl RenderAsync for RenderThirdParty {
                 *self.error_holder.lock().unwrap() = Some(AsyncPresentationError { slide: self.slide, error });
                 RenderAsyncState::JustFinishedRendering
             }
+            RenderResult::Fallback(text) => {
+                *fallback = Some(text);
+                RenderAsyncState::JustFinishedRendering
+            }
             RenderResult::Pending => RenderAsyncState::Rendering { modified: false },
         }
     }
@@ -349,6 +397,13 @@ impl RenderAsync for RenderThirdParty {
 
 impl AsRenderOperations for RenderThirdParty {
     fn as_render_operations(&self, _: &WindowSize) -> Vec<RenderOperation> {
+        if let Some(text) = &*self.fallback.lock().unwrap() {
+            let line = Line::from(Text::new(text, TextStyle::default().math()));
+            return vec![RenderOperation::RenderText {
+                line: line.into(),
+                alignment: Alignment::Center { minimum_margin: Default::default(), minimum_size: 0 },
+            }];
+        }
         match &*self.contents.lock().unwrap() {
             Some(image) => {
                 let size = match &self.width {
@@ -377,3 +432,39 @@ impl AsRenderOperations for RenderThirdParty {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::markdown::text::WeightedLine;
+    use std::{
+        process::Command,
+        time::{Duration, Instant},
+    };
+
+    #[test]
+    fn math_without_pandoc() {
+        if Command::new("pandoc").arg("--version").output().is_ok() {
+            return;
+        }
+        let render = ThirdPartyRender::default();
+        let request = ThirdPartyRenderRequest::Math("$x^2$".into(), Default::default());
+        let error_holder = AsyncPresentationErrorHolder::default();
+        let operation = render.render_async(request, &Default::default(), error_holder.clone(), 1, None);
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while let RenderAsyncState::Rendering { .. } = operation.poll_state() {
+            assert!(Instant::now() < deadline, "render didn't finish");
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert!(error_holder.lock().unwrap().is_none());
+        assert!(operation.image().is_none());
+
+        let operations = operation.as_render_operations(&WindowSize { rows: 10, columns: 10, width: 0, height: 0 });
+        let [RenderOperation::RenderText { line, .. }] = operations.as_slice() else {
+            panic!("unexpected operations: {operations:?}")
+        };
+        let expected: WeightedLine = Line::from(Text::new("$x^2$", TextStyle::default().math())).into();
+        assert_eq!(line, &expected);
+    }
+}
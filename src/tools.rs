@@ -21,10 +21,6 @@ impl ThirdPartyTools {
         let mmdc = if cfg!(windows) { "mmdc.cmd" } else { "mmdc" };
         Tool::new(mmdc, args)
     }
-
-    pub(crate) fn presenterm_export(args: &[&str]) -> Tool {
-        Tool::new("presenterm-export", args).inherit_stdout().max_error_lines(100)
-    }
 }
 
 pub(crate) struct Tool {
@@ -46,16 +42,6 @@ impl Tool {
         self
     }
 
-    pub(crate) fn inherit_stdout(mut self) -> Self {
-        self.command.stdout(Stdio::inherit());
-        self
-    }
-
-    pub(crate) fn max_error_lines(mut self, value: usize) -> Self {
-        self.max_error_lines = value;
-        self
-    }
-
     pub(crate) fn run(self) -> Result<(), ExecutionError> {
         self.spawn()?;
         Ok(())